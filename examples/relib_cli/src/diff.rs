@@ -0,0 +1,72 @@
+use {
+  crate::inspect::inspect,
+  relib_host::InterfaceSignature,
+  std::collections::{BTreeMap, BTreeSet},
+};
+
+/// Compares signatures of exports and imports traits which relib_interface embeds into modules
+pub fn run(a: &str, b: &str) -> Result<(), String> {
+  let a_inspection = inspect(a)?;
+  let b_inspection = inspect(b)?;
+
+  let mut differs = false;
+
+  if a_inspection.compilation_info != b_inspection.compilation_info {
+    differs = true;
+    println!("compilation info differs:");
+    println!("  {a}: {:?}", a_inspection.compilation_info);
+    println!("  {b}: {:?}", b_inspection.compilation_info);
+  }
+
+  let a_interfaces = interfaces_by_name(&a_inspection.interfaces);
+  let b_interfaces = interfaces_by_name(&b_inspection.interfaces);
+  let trait_names: BTreeSet<&str> = a_interfaces
+    .keys()
+    .chain(b_interfaces.keys())
+    .copied()
+    .collect();
+
+  for trait_name in trait_names {
+    let (Some(a_functions), Some(b_functions)) =
+      (a_interfaces.get(trait_name), b_interfaces.get(trait_name))
+    else {
+      differs = true;
+      let sign = if a_interfaces.contains_key(trait_name) {
+        "-"
+      } else {
+        "+"
+      };
+      println!("{sign} trait {trait_name}");
+      continue;
+    };
+    if a_functions == b_functions {
+      continue;
+    }
+    differs = true;
+
+    println!("trait {trait_name}:");
+    for function in a_functions.difference(b_functions) {
+      println!("  - {function}");
+    }
+    for function in b_functions.difference(a_functions) {
+      println!("  + {function}");
+    }
+  }
+
+  if differs {
+    Err("interfaces of modules differ".to_owned())
+  } else {
+    println!("interfaces are identical");
+    Ok(())
+  }
+}
+
+fn interfaces_by_name(interfaces: &[InterfaceSignature]) -> BTreeMap<&str, BTreeSet<&str>> {
+  interfaces
+    .iter()
+    .map(|interface| {
+      let functions = interface.functions.iter().map(String::as_str).collect();
+      (interface.trait_name.as_str(), functions)
+    })
+    .collect()
+}
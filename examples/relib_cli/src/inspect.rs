@@ -0,0 +1,91 @@
+use relib_host::ModuleInspection;
+
+pub fn run(path: &str) -> Result<(), String> {
+  let inspection = inspect(path)?;
+  print_inspection(&inspection);
+
+  if !inspection.is_compatible_with_host() {
+    return Err(
+      "module is compiled with different configuration \
+      (this tool is built with \"unloading\" feature of relib)"
+        .to_owned(),
+    );
+  }
+  if inspection.unloading_will_fail() {
+    return Err("module is guaranteed to fail unloading".to_owned());
+  }
+  Ok(())
+}
+
+pub fn inspect(path: &str) -> Result<ModuleInspection, String> {
+  relib_host::inspect(path).map_err(|e| format!("failed to inspect {path}: {e}"))
+}
+
+fn print_inspection(inspection: &ModuleInspection) {
+  let ModuleInspection {
+    format,
+    has_compilation_info_symbol,
+    compilation_info,
+    exports,
+    post_exports,
+    imports,
+    interfaces,
+    needed_libraries,
+    unloading_blockers,
+  } = inspection;
+
+  println!("format: {format:?}");
+
+  match (has_compilation_info_symbol, compilation_info) {
+    (_, Some(info)) => println!("compilation info:\n{}", indent(info)),
+    (true, None) => println!("compilation info: symbol is present but its value can't be read"),
+    (false, None) => println!("compilation info: not found (not a relib module?)"),
+  }
+
+  print_list("exports", exports.iter());
+  print_list("post exports", post_exports.iter());
+  print_list("imports", imports.iter());
+  print_list(
+    "interfaces",
+    interfaces.iter().map(|interface| {
+      let functions = interface
+        .functions
+        .iter()
+        .map(|function| format!("\n    {function}"))
+        .collect::<String>();
+      format!("trait {}:{functions}", interface.trait_name)
+    }),
+  );
+  print_list("needed libraries", needed_libraries.iter());
+  print_list(
+    "unload blockers",
+    unloading_blockers.iter().map(|blocker| {
+      let kind = if blocker.is_certain() {
+        "certain"
+      } else {
+        "possible"
+      };
+      format!("{blocker} ({kind})")
+    }),
+  );
+}
+
+fn print_list(title: &str, items: impl ExactSizeIterator<Item = impl std::fmt::Display>) {
+  if items.len() == 0 {
+    println!("{title}: none");
+    return;
+  }
+
+  println!("{title}:");
+  for item in items {
+    println!("  {item}");
+  }
+}
+
+fn indent(text: &str) -> String {
+  text
+    .lines()
+    .map(|line| format!("  {line}"))
+    .collect::<Vec<_>>()
+    .join("\n")
+}
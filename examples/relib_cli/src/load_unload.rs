@@ -0,0 +1,76 @@
+use {
+  crate::inspect::inspect,
+  relib_host::{Module, SoakOptions, SoakSample},
+};
+
+pub fn run(path: &str, times: usize) -> Result<(), String> {
+  let has_main = inspect(path)?
+    .exports
+    .iter()
+    .any(|export| export == "__relib__main");
+  if !has_main {
+    println!("module doesn't export main, it will only be loaded and unloaded");
+  }
+
+  // bytes which module didn't deallocate before each unload (relib frees them)
+  let mut leaked_bytes = Vec::with_capacity(times);
+
+  // SAFETY: module is trusted to follow the rules of relib (it's what this command tests)
+  let report = unsafe {
+    relib_host::soak(
+      path,
+      SoakOptions::default().cycles(times),
+      || (),
+      |module: &Module<()>| {
+        if has_main {
+          // SAFETY: module main must not return anything, see usage
+          let returned = module.call_main::<()>();
+          if returned.is_none() {
+            println!("main panicked");
+          }
+        }
+        leaked_bytes.push(module.memory_stats().live_bytes);
+      },
+    )
+  };
+  let report = report.map_err(|e| format!("{e:#}"))?;
+
+  println!("before: {}", format_sample(&report.baseline));
+  for (idx, (sample, leaked_bytes)) in report.samples.iter().zip(&leaked_bytes).enumerate() {
+    println!(
+      "cycle {}: leaked bytes: {leaked_bytes}, {}",
+      idx + 1,
+      format_sample(sample)
+    );
+  }
+  println!("{report}");
+  println!("leaked bytes total: {}", leaked_bytes.iter().sum::<u64>());
+
+  if report.is_leak_free() {
+    Ok(())
+  } else {
+    Err("some resources grew after every cycle".to_owned())
+  }
+}
+
+fn format_sample(sample: &SoakSample) -> String {
+  let SoakSample {
+    resident_bytes,
+    tracked_allocations: _,
+    memory_mappings,
+    file_descriptors,
+    threads,
+  } = sample;
+
+  let values = [
+    ("resident bytes", resident_bytes),
+    ("mappings", memory_mappings),
+    ("fds", file_descriptors),
+    ("threads", threads),
+  ];
+  values
+    .into_iter()
+    .filter_map(|(name, value)| Some(format!("{name}: {}", (*value)?)))
+    .collect::<Vec<_>>()
+    .join(", ")
+}
@@ -0,0 +1,48 @@
+use std::{env, process::ExitCode};
+
+mod inspect;
+mod load_unload;
+mod diff;
+
+const USAGE: &str = "\
+usage:
+  relib inspect <dylib>
+    prints compilation info, interface symbols and signatures and unload blockers of the module,
+    exits with an error if module is incompatible with this build of relib or can't be unloaded
+
+  relib load-unload <dylib> [--times N]
+    loads module, calls its main (if it's exported) and unloads it N times (1 by default),
+    reports bytes the module didn't deallocate before each unload (they are freed by relib)
+    and resources of the process after each cycle (see relib_host::soak),
+    exits with an error if some of them grew after every cycle
+    note: module must not expect any imports and its main must not take or return anything
+
+  relib diff <a> <b>
+    compares signatures of interface traits (exports and imports) and compilation info of two modules";
+
+fn main() -> ExitCode {
+  let args: Vec<String> = env::args().skip(1).collect();
+  let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+  let result = match args.as_slice() {
+    ["inspect", path] => inspect::run(path),
+    ["load-unload", path] => load_unload::run(path, 1),
+    ["load-unload", path, "--times", times] => match times.parse() {
+      Ok(times) => load_unload::run(path, times),
+      Err(e) => Err(format!("invalid --times value: {e}")),
+    },
+    ["diff", a, b] => diff::run(a, b),
+    _ => {
+      eprintln!("{USAGE}");
+      return ExitCode::FAILURE;
+    }
+  };
+
+  match result {
+    Ok(()) => ExitCode::SUCCESS,
+    Err(e) => {
+      eprintln!("error: {e}");
+      ExitCode::FAILURE
+    }
+  }
+}
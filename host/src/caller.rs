@@ -0,0 +1,109 @@
+use {
+  crate::LoadError,
+  relib_interface::exports_types::{__current_caller, __while_loading},
+  relib_internal_shared::ModuleId,
+  std::{any::Any, collections::HashSet, ptr, sync::Arc},
+};
+
+pub(crate) type ImportsContext = Arc<dyn Any + Send + Sync>;
+pub(crate) type ImportGrants = Arc<HashSet<String>>;
+
+/// Module which called currently executed import, see [`caller`].
+#[derive(Clone, Debug)]
+pub struct Caller {
+  module_id: ModuleId,
+  context: Option<ImportsContext>,
+  granted_imports: Option<ImportGrants>,
+}
+
+impl Caller {
+  pub(crate) fn new(
+    module_id: ModuleId,
+    context: Option<ImportsContext>,
+    granted_imports: Option<ImportGrants>,
+  ) -> Self {
+    Self {
+      module_id,
+      context,
+      granted_imports,
+    }
+  }
+
+  /// Same as [`Module::id`](crate::Module::id) of the calling module.
+  pub fn module_id(&self) -> ModuleId {
+    self.module_id
+  }
+
+  /// Context which was passed to [`LoadOptions::imports_context`](crate::LoadOptions::imports_context)
+  /// when the calling module was loaded.
+  ///
+  /// Returns `None` if context wasn't passed or it has a different type.
+  pub fn context<C: Any>(&self) -> Option<&C> {
+    self.context.as_deref()?.downcast_ref()
+  }
+}
+
+/// Returns the module which called currently executed import on this thread.
+///
+/// Returns `None` if it's called outside of imports implementation
+/// or if imports were initialized not by [`load_module`](crate::load_module).
+///
+/// # Example
+/// ```ignore
+/// impl shared::imports::Imports for ModuleImportsImpl {
+///   fn log(message: RStr) {
+///     let caller = relib_host::caller().unwrap();
+///     let plugin_name = caller.context::<PluginName>().unwrap();
+///     println!("[{plugin_name}] (module id: {}) {message}", caller.module_id());
+///   }
+/// }
+/// ```
+pub fn caller() -> Option<Caller> {
+  let caller = __current_caller().cast::<Caller>();
+  // SAFETY: pointer is only set while import is executed,
+  // caller is freed only after its module is unloaded
+  unsafe { caller.as_ref() }.cloned()
+}
+
+/// Caller is allocated once for every loaded module and freed only when module is unloaded
+/// (module may still call imports if it's leaked)
+pub(crate) fn leak(caller: Caller) -> &'static Caller {
+  Box::leak(Box::new(caller))
+}
+
+/// # Safety
+/// Module of this caller must be unloaded (or it must not be initialized yet).
+pub(crate) unsafe fn free(caller: &'static Caller) {
+  drop(unsafe { Box::from_raw(ptr::from_ref(caller).cast_mut()) });
+}
+
+/// Returns an error if some of the granted imports are unknown to `init_imports` (most likely a typo)
+pub(crate) fn while_loading<R>(
+  caller: &'static Caller,
+  f: impl FnOnce() -> R,
+) -> Result<R, LoadError> {
+  let (value, imports) = __while_loading(
+    ptr::from_ref(caller).cast(),
+    caller.granted_imports.as_deref(),
+    f,
+  );
+
+  if let Some(granted_imports) = &caller.granted_imports {
+    let mut unknown = granted_imports
+      .iter()
+      .filter(|granted| {
+        !imports.iter().any(|(trait_name, fn_name)| {
+          granted == trait_name || **granted == format!("{trait_name}::{fn_name}")
+        })
+      })
+      .cloned()
+      .collect::<Vec<_>>();
+
+    if !unknown.is_empty() {
+      unknown.sort();
+      return Err(LoadError::UnknownGrantedImports(unknown));
+    }
+  }
+
+  Ok(value)
+}
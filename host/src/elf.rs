@@ -0,0 +1,264 @@
+//! Minimal reader of 64-bit little-endian ELF files (the only kind relib supports on Linux),
+//! only what's needed to inspect modules without loading them.
+
+use std::{ffi::CStr, ops::Range};
+
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+
+pub const SHT_RELA: u32 = 4;
+pub const SHT_DYNAMIC: u32 = 6;
+pub const SHT_NOBITS: u32 = 8;
+pub const SHT_DYNSYM: u32 = 11;
+pub const SHT_INIT_ARRAY: u32 = 14;
+
+pub const STB_GNU_UNIQUE: u8 = 10;
+pub const STT_FUNC: u8 = 2;
+
+pub const R_X86_64_RELATIVE: u32 = 8;
+pub const R_AARCH64_RELATIVE: u32 = 1027;
+
+pub const DT_NULL: u64 = 0;
+pub const DT_NEEDED: u64 = 1;
+pub const DT_SONAME: u64 = 14;
+pub const DT_FLAGS_1: u64 = 0x6fff_fffb;
+pub const DF_1_NODELETE: u64 = 0x8;
+
+pub struct Elf<'data> {
+  data: &'data [u8],
+  sections: Vec<SectionHeader>,
+}
+
+#[derive(Clone)]
+pub struct SectionHeader {
+  pub type_: u32,
+  pub addr: u64,
+  pub offset: u64,
+  pub size: u64,
+  pub link: u32,
+  pub entsize: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Symbol {
+  pub name: String,
+  pub bind: u8,
+  pub type_: u8,
+  pub value: u64,
+  pub size: u64,
+  /// 0 if symbol is undefined (imported)
+  pub section_index: u16,
+}
+
+impl<'data> Elf<'data> {
+  /// Returns `None` if it's not a 64-bit little-endian ELF or it's malformed
+  pub fn parse(data: &'data [u8]) -> Option<Self> {
+    if data.get(..4)? != ELF_MAGIC || *data.get(4)? != ELFCLASS64 || *data.get(5)? != ELFDATA2LSB {
+      return None;
+    }
+
+    let shoff = read_u64(data, 0x28)? as usize;
+    let shentsize = read_u16(data, 0x3a)? as usize;
+    let shnum = read_u16(data, 0x3c)? as usize;
+
+    let sections = (0..shnum)
+      .map(|idx| {
+        let header = data.get(shoff.checked_add(idx.checked_mul(shentsize)?)?..)?;
+        Some(SectionHeader {
+          type_: read_u32(header, 0x4)?,
+          addr: read_u64(header, 0x10)?,
+          offset: read_u64(header, 0x18)?,
+          size: read_u64(header, 0x20)?,
+          link: read_u32(header, 0x28)?,
+          entsize: read_u64(header, 0x38)?,
+        })
+      })
+      .collect::<Option<Vec<_>>>()?;
+
+    Some(Self { data, sections })
+  }
+
+  fn section_data(&self, section: &SectionHeader) -> Option<&'data [u8]> {
+    let range = file_range(section.offset, section.size)?;
+    self.data.get(range)
+  }
+
+  /// Symbols of .dynsym section (exported and imported ones)
+  pub fn dynamic_symbols(&self) -> Vec<Symbol> {
+    let Some(dynsym) = self.sections.iter().find(|s| s.type_ == SHT_DYNSYM) else {
+      return Vec::new();
+    };
+    self.symbols(dynsym).unwrap_or_default()
+  }
+
+  fn symbols(&self, table: &SectionHeader) -> Option<Vec<Symbol>> {
+    let strings = self.sections.get(table.link as usize)?;
+    let data = self.section_data(table)?;
+    let entsize = if table.entsize == 0 {
+      24
+    } else {
+      table.entsize as usize
+    };
+
+    let symbols = data
+      .chunks_exact(entsize)
+      // first symbol is always null
+      .skip(1)
+      .filter_map(|entry| {
+        let info = *entry.get(4)?;
+        Some(Symbol {
+          name: self
+            .string_at(strings, read_u32(entry, 0)? as u64)?
+            .to_owned(),
+          bind: info >> 4,
+          type_: info & 0xf,
+          section_index: read_u16(entry, 6)?,
+          value: read_u64(entry, 8)?,
+          size: read_u64(entry, 16)?,
+        })
+      })
+      .collect();
+
+    Some(symbols)
+  }
+
+  /// Entries of .dynamic section as (tag, value) pairs
+  pub fn dynamic_entries(&self) -> Vec<(u64, u64)> {
+    let Some(dynamic) = self.sections.iter().find(|s| s.type_ == SHT_DYNAMIC) else {
+      return Vec::new();
+    };
+    let Some(data) = self.section_data(dynamic) else {
+      return Vec::new();
+    };
+
+    data
+      .chunks_exact(16)
+      .map_while(|entry| {
+        let tag = read_u64(entry, 0)?;
+        let value = read_u64(entry, 8)?;
+        (tag != DT_NULL).then_some((tag, value))
+      })
+      .collect()
+  }
+
+  /// String of dynamic string table (for example, value of `DT_NEEDED`)
+  pub fn dynamic_string(&self, offset: u64) -> Option<&'data str> {
+    let dynamic = self.sections.iter().find(|s| s.type_ == SHT_DYNAMIC)?;
+    let strings = self.sections.get(dynamic.link as usize)?;
+    self.string_at(strings, offset)
+  }
+
+  pub fn needed_libraries(&self) -> Vec<&'data str> {
+    self
+      .dynamic_entries()
+      .into_iter()
+      .filter(|(tag, _)| *tag == DT_NEEDED)
+      .filter_map(|(_, offset)| self.dynamic_string(offset))
+      .collect()
+  }
+
+  #[cfg_attr(
+    not(all(target_os = "linux", feature = "unloading")),
+    expect(dead_code)
+  )]
+  pub fn soname(&self) -> Option<&'data str> {
+    let (_, offset) = self
+      .dynamic_entries()
+      .into_iter()
+      .find(|(tag, _)| *tag == DT_SONAME)?;
+    self.dynamic_string(offset)
+  }
+
+  pub fn has_nodelete_flag(&self) -> bool {
+    self
+      .dynamic_entries()
+      .into_iter()
+      .any(|(tag, value)| tag == DT_FLAGS_1 && value & DF_1_NODELETE != 0)
+  }
+
+  pub fn gnu_unique_symbols(&self) -> Vec<String> {
+    self
+      .dynamic_symbols()
+      .into_iter()
+      .filter(|symbol| symbol.bind == STB_GNU_UNIQUE && symbol.section_index != 0)
+      .map(|symbol| symbol.name)
+      .collect()
+  }
+
+  /// Number of constructors in `.init_array` sections
+  pub fn init_array_entries(&self) -> usize {
+    self
+      .sections
+      .iter()
+      .filter(|s| s.type_ == SHT_INIT_ARRAY)
+      .map(|s| (s.size / 8) as usize)
+      .fold(0, usize::saturating_add)
+  }
+
+  /// Contents of the file which will be mapped at `address` (virtual address relative to the load base)
+  pub fn data_at_address(&self, address: u64, len: u64) -> Option<&'data [u8]> {
+    let section = self.sections.iter().find(|s| {
+      s.type_ != SHT_NOBITS
+        && s.addr != 0
+        && address >= s.addr
+        && address
+          .checked_add(len)
+          .zip(s.addr.checked_add(s.size))
+          .is_some_and(|(end, section_end)| end <= section_end)
+    })?;
+    let range = file_range(section.offset.checked_add(address - section.addr)?, len)?;
+    self.data.get(range)
+  }
+
+  /// Pointer stored at `address` (relative to the load base) as it will be after relocation,
+  /// only relative relocations are supported
+  pub fn pointer_at(&self, address: u64) -> Option<u64> {
+    let relocated = self
+      .sections
+      .iter()
+      .filter(|s| s.type_ == SHT_RELA)
+      .filter_map(|s| self.section_data(s))
+      .flat_map(|data| data.chunks_exact(24))
+      .find_map(|entry| {
+        let offset = read_u64(entry, 0)?;
+        let type_ = read_u64(entry, 8)? as u32;
+        let addend = read_u64(entry, 16)?;
+        (offset == address && matches!(type_, R_X86_64_RELATIVE | R_AARCH64_RELATIVE))
+          .then_some(addend)
+      });
+
+    // RELR and REL relocations keep addend in place
+    relocated.or_else(|| read_u64(self.data_at_address(address, 8)?, 0))
+  }
+
+  fn string_at(&self, strings: &SectionHeader, offset: u64) -> Option<&'data str> {
+    let data = self.section_data(strings)?;
+    let string = CStr::from_bytes_until_nul(data.get(offset as usize..)?).ok()?;
+    string.to_str().ok()
+  }
+}
+
+fn file_range(offset: u64, len: u64) -> Option<Range<usize>> {
+  let start = usize::try_from(offset).ok()?;
+  let end = start.checked_add(usize::try_from(len).ok()?)?;
+  Some(start..end)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+  Some(u16::from_le_bytes(
+    data.get(offset..offset.checked_add(2)?)?.try_into().ok()?,
+  ))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+  Some(u32::from_le_bytes(
+    data.get(offset..offset.checked_add(4)?)?.try_into().ok()?,
+  ))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+  Some(u64::from_le_bytes(
+    data.get(offset..offset.checked_add(8)?)?.try_into().ok()?,
+  ))
+}
@@ -24,4 +24,25 @@ pub enum LoadError {
     note: make sure that compiled .so/.dll has relib_module crate in it"
   )]
   CouldNotGetCompilationInfo,
+
+  #[error(
+    "value of \"{name}\" constant of {trait_path} trait file is different in the module\n\
+    note: make sure that host and module are compiled with the same shared crate code"
+  )]
+  ConstMismatch { trait_path: String, name: String },
+
+  #[error(
+    "granted imports are not imported by the module: {0:?}\n\
+    note: make sure that names of imports traits and functions are correct, for example: \"Imports\" or \"Imports::foo\""
+  )]
+  UnknownGrantedImports(Vec<String>),
+}
+
+#[derive(Error, Debug)]
+pub enum InspectError {
+  #[error("failed to read module file: {0}")]
+  Io(#[from] std::io::Error),
+
+  #[error("unsupported file format, only 64-bit little-endian ELF and PE files are supported")]
+  UnsupportedFormat,
 }
@@ -1,28 +1,7 @@
-use libloading::Library;
+pub use relib_interface::exports_types::{InitImports, Library, ModuleExportsForHost};
 
-pub trait ModuleExportsForHost {
-  fn new(library: &Library) -> Self;
-}
-
-/// For cases when module doesn't export anything
-impl ModuleExportsForHost for () {
-  fn new(_library: &Library) {}
-}
-
-pub trait InitImports {
-  fn init(self, library: &Library);
-}
-
-impl<F> InitImports for F
-where
-  F: FnOnce(&Library),
-{
-  fn init(self, library: &Library) {
-    self(library)
-  }
-}
-
-/// For cases when module doesn't import anything
-impl InitImports for () {
-  fn init(self, _library: &Library) {}
-}
+// used by generated code of imports
+#[doc(hidden)]
+pub use relib_interface::exports_types::{
+  __const_mismatch, __is_import_granted, __loading_caller, __with_caller,
+};
@@ -66,6 +66,28 @@ pub unsafe fn call_module_pub_export<R>(
 where
   R: Clone,
 {
+  let export = unsafe { get_module_pub_export(library, name) }?;
+  Ok(unsafe { export.call() })
+}
+
+/// Raw function pointers of module export without args,
+/// can be called later (for example, from another thread) with [`PubExport::call`]
+pub enum PubExport<R> {
+  /// return value may not be Copy and needs dropping by post function
+  Boxed {
+    fn_: extern "C" fn(*mut bool) -> MaybeUninit<*mut R>,
+    post_fn: extern "C" fn(*mut R),
+  },
+  /// return value is simple Copy type
+  Unboxed {
+    fn_: extern "C" fn(*mut bool) -> MaybeUninit<R>,
+  },
+}
+
+pub unsafe fn get_module_pub_export<R>(
+  library: &Library,
+  name: &str,
+) -> Result<PubExport<R>, libloading::Error> {
   // !!! keep in sync with relib_interface crate !!!
 
   let mangled_name = format!("__relib__{name}");
@@ -78,50 +100,68 @@ where
 
   // if library has post function for this export return value
   // may not be Copy and needs dropping
-  let return_value = if let Ok(post_fn) = post_fn {
+  let export = if let Ok(post_fn) = post_fn {
     let fn_ = unsafe { get_library_export(library, &mangled_name) }?;
     let fn_: Symbol<extern "C" fn(*mut bool) -> MaybeUninit<*mut R>> = fn_;
 
-    let mut ____success____ = MaybeUninit::<bool>::uninit();
+    PubExport::Boxed {
+      fn_: *fn_,
+      post_fn: *post_fn,
+    }
+  } else {
+    let fn_ = unsafe { get_library_export(library, &mangled_name) }?;
+    let fn_: Symbol<extern "C" fn(*mut bool) -> MaybeUninit<R>> = fn_;
 
-    let return_ptr = fn_(____success____.as_mut_ptr());
+    PubExport::Unboxed { fn_: *fn_ }
+  };
 
-    // SAFETY: this bool is guaranteed to be initialized by the module
-    if !unsafe { ____success____.assume_init() } {
-      return Ok(None);
-    }
+  Ok(export)
+}
 
-    // SAFETY: function returned true so we are allowed to read the pointer
-    let (return_ptr, return_value) = unsafe {
-      let return_ptr = return_ptr.assume_init();
-      let return_value: R = Clone::clone(&*return_ptr);
-      (return_ptr, return_value)
-    };
+impl<R: Clone> PubExport<R> {
+  /// # Safety
+  /// Module which exported this function must still be loaded.
+  pub unsafe fn call(self) -> Option<R> {
+    let return_value = match self {
+      PubExport::Boxed { fn_, post_fn } => {
+        let mut ____success____ = MaybeUninit::<bool>::uninit();
 
-    post_fn(return_ptr);
+        let return_ptr = fn_(____success____.as_mut_ptr());
 
-    return_value
-  }
-  // else return value is simple Copy type
-  else {
-    let fn_ = unsafe { get_library_export(library, &mangled_name) }?;
-    let fn_: Symbol<extern "C" fn(*mut bool) -> MaybeUninit<R>> = fn_;
+        // SAFETY: this bool is guaranteed to be initialized by the module
+        if !unsafe { ____success____.assume_init() } {
+          return None;
+        }
 
-    let mut ____success____ = MaybeUninit::<bool>::uninit();
+        // SAFETY: function returned true so we are allowed to read the pointer
+        let (return_ptr, return_value) = unsafe {
+          let return_ptr = return_ptr.assume_init();
+          let return_value: R = Clone::clone(&*return_ptr);
+          (return_ptr, return_value)
+        };
 
-    let return_value = fn_(____success____.as_mut_ptr());
+        post_fn(return_ptr);
 
-    // SAFETY: this bool is guaranteed to be initialized by the module
-    let success = unsafe { ____success____.assume_init() };
-    if !success {
-      return Ok(None);
-    }
+        return_value
+      }
+      PubExport::Unboxed { fn_ } => {
+        let mut ____success____ = MaybeUninit::<bool>::uninit();
 
-    // SAFETY: function returned true so we are allowed to read the pointer
-    unsafe { return_value.assume_init() }
-  };
+        let return_value = fn_(____success____.as_mut_ptr());
+
+        // SAFETY: this bool is guaranteed to be initialized by the module
+        let success = unsafe { ____success____.assume_init() };
+        if !success {
+          return None;
+        }
+
+        // SAFETY: function returned true so we are allowed to read the pointer
+        unsafe { return_value.assume_init() }
+      }
+    };
 
-  Ok(Some(return_value))
+    Some(return_value)
+  }
 }
 
 #[cfg(target_os = "linux")]
@@ -0,0 +1,307 @@
+use {
+  crate::{
+    InspectError,
+    elf::{Elf, STT_FUNC},
+    pe::Pe,
+  },
+  std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs,
+    path::Path,
+  },
+};
+
+// !!! keep in sync with relib_module and relib_interface crates !!!
+const COMPILATION_INFO_SYMBOL: &str = "__RELIB__CRATE_COMPILATION_INFO__";
+const EXPORT_PREFIX: &str = "__relib__";
+const POST_EXPORT_PREFIX: &str = "__post__relib__";
+const SIGNATURE_PREFIX: &str = "__relib_signature__";
+
+/// What is known about the module (dynamic library) without loading it, see [`inspect`].
+#[derive(Debug, Clone)]
+pub struct ModuleInspection {
+  pub format: BinaryFormat,
+  /// `false` if it's not a relib module (or it's stripped)
+  pub has_compilation_info_symbol: bool,
+  /// Compilation info of relib_module (rustc version, relib version and features),
+  /// see [`LoadError::ModuleCompilationMismatch`](crate::LoadError::ModuleCompilationMismatch).
+  /// `None` if there is no symbol or its value can't be read from the file
+  pub compilation_info: Option<String>,
+  /// Exported functions with `__relib__` prefix (exports of the module, `main`, `before_unload` and internal ones)
+  pub exports: Vec<String>,
+  /// Exported functions with `__post__relib__` prefix (used to deallocate return values of exports)
+  pub post_exports: Vec<String>,
+  /// Exported static function pointers with `__relib__` or `__post__relib__` prefix,
+  /// host writes its imports implementation into them when module is loaded
+  pub imports: Vec<String>,
+  /// Signatures of exports and imports traits the module was compiled with, sorted by trait name
+  pub interfaces: Vec<InterfaceSignature>,
+  /// Dynamic libraries this module depends on (`DT_NEEDED` on Linux, import table on Windows)
+  pub needed_libraries: Vec<String>,
+  /// Things in the module which may prevent unloading of it
+  pub unloading_blockers: Vec<StaticUnloadBlocker>,
+}
+
+/// Exports or imports trait of the module, see [`ModuleInspection::interfaces`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceSignature {
+  /// Name of the trait (without path)
+  pub trait_name: String,
+  /// Signatures of the trait functions (including cfg attributes) as they are generated by relib_interface
+  pub functions: Vec<String>,
+}
+
+impl InterfaceSignature {
+  fn new(trait_name: &str, signature: &str) -> Self {
+    Self {
+      trait_name: trait_name.to_owned(),
+      functions: signature
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(ToOwned::to_owned)
+        .collect(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryFormat {
+  /// 64-bit little-endian ELF (.so)
+  Elf,
+  /// PE (.dll)
+  Pe,
+}
+
+/// Construct found in the module file which is known to prevent (or may prevent) unloading of it.
+#[derive(Debug, Clone)]
+pub enum StaticUnloadBlocker {
+  /// Library is marked with `DF_1_NODELETE` flag (for example, it's linked with `-z nodelete`),
+  /// it will never be unloaded
+  NoDeleteFlag,
+  /// Library defines `STB_GNU_UNIQUE` symbols, glibc never unloads such libraries
+  GnuUniqueSymbols(Vec<String>),
+  /// Number of constructors in `.init_array`, they may register something
+  /// which keeps the library loaded (for example, `atexit` handler or thread-local destructor).
+  /// **note:** standard library always has some of them, so it's only a hint
+  InitArrayEntries(usize),
+}
+
+impl StaticUnloadBlocker {
+  /// Returns `true` if module with it is guaranteed to fail unloading
+  pub fn is_certain(&self) -> bool {
+    match self {
+      Self::NoDeleteFlag | Self::GnuUniqueSymbols(_) => true,
+      Self::InitArrayEntries(_) => false,
+    }
+  }
+}
+
+impl Display for StaticUnloadBlocker {
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    match self {
+      Self::NoDeleteFlag => write!(f, "library has DF_1_NODELETE flag"),
+      Self::GnuUniqueSymbols(symbols) => write!(
+        f,
+        "library defines STB_GNU_UNIQUE symbols: {}",
+        symbols.join(", ")
+      ),
+      Self::InitArrayEntries(count) => write!(f, "library has {count} .init_array entries"),
+    }
+  }
+}
+
+impl ModuleInspection {
+  /// Returns `true` if compilation info of the module is identical to the host one
+  /// (otherwise [`load_module`](crate::load_module) will return an error)
+  pub fn is_compatible_with_host(&self) -> bool {
+    self.compilation_info.as_deref() == Some(relib_internal_crate_compilation_info::get!())
+  }
+
+  /// Returns `true` if unloading of the module is guaranteed to fail,
+  /// see [`StaticUnloadBlocker::is_certain`].
+  pub fn unloading_will_fail(&self) -> bool {
+    self
+      .unloading_blockers
+      .iter()
+      .any(StaticUnloadBlocker::is_certain)
+  }
+}
+
+/// Reads the module (dynamic library) file by specified path without loading or executing it
+/// and reports what relib would see in it.
+///
+/// Can be used, for example, in CI to reject modules which are guaranteed to fail unloading
+/// or compiled with different configuration.
+///
+/// # Example
+/// ```no_run
+/// use {std::path::Path, libloading::library_filename};
+///
+/// let dylib_path = Path::new("target/debug").join(library_filename("module"));
+///
+/// let inspection = relib_host::inspect(dylib_path).unwrap_or_else(|e| {
+///   panic!("module inspection failed: {e:#}");
+/// });
+/// assert!(inspection.is_compatible_with_host());
+/// assert!(!inspection.unloading_will_fail());
+/// ```
+pub fn inspect(path: impl AsRef<Path>) -> Result<ModuleInspection, InspectError> {
+  let data = fs::read(path)?;
+
+  if let Some(elf) = Elf::parse(&data) {
+    Ok(inspect_elf(&elf))
+  } else if let Some(pe) = Pe::parse(&data) {
+    Ok(inspect_pe(&pe))
+  } else {
+    Err(InspectError::UnsupportedFormat)
+  }
+}
+
+fn inspect_elf(elf: &Elf) -> ModuleInspection {
+  let symbols: Vec<_> = elf
+    .dynamic_symbols()
+    .into_iter()
+    .filter(|symbol| symbol.section_index != 0)
+    .collect();
+
+  let compilation_info_symbol = symbols
+    .iter()
+    .find(|symbol| symbol.name == COMPILATION_INFO_SYMBOL);
+
+  let compilation_info = compilation_info_symbol.and_then(|symbol| {
+    // it's relib_internal_shared::Str (pointer and length)
+    let ptr = elf.pointer_at(symbol.value)?;
+    let len = u64::from_le_bytes(
+      elf
+        .data_at_address(symbol.value.checked_add(8)?, 8)?
+        .try_into()
+        .ok()?,
+    );
+    let bytes = elf.data_at_address(ptr, len)?;
+    String::from_utf8(bytes.to_vec()).ok()
+  });
+
+  let interfaces = collect_interfaces(symbols.iter().filter_map(|symbol| {
+    let trait_name = symbol.name.strip_prefix(SIGNATURE_PREFIX)?;
+    let bytes = elf.data_at_address(symbol.value, symbol.size)?;
+    let signature = std::str::from_utf8(bytes.strip_suffix(b"\0")?).ok()?;
+    Some(InterfaceSignature::new(trait_name, signature))
+  }));
+
+  let mut unloading_blockers = Vec::new();
+  if elf.has_nodelete_flag() {
+    unloading_blockers.push(StaticUnloadBlocker::NoDeleteFlag);
+  }
+  let unique_symbols = elf.gnu_unique_symbols();
+  if !unique_symbols.is_empty() {
+    unloading_blockers.push(StaticUnloadBlocker::GnuUniqueSymbols(unique_symbols));
+  }
+  let init_array_entries = elf.init_array_entries();
+  if init_array_entries != 0 {
+    unloading_blockers.push(StaticUnloadBlocker::InitArrayEntries(init_array_entries));
+  }
+
+  let (exports, post_exports, imports) = classify_symbols(
+    symbols
+      .iter()
+      .map(|symbol| (symbol.name.as_str(), symbol.type_ == STT_FUNC)),
+  );
+
+  ModuleInspection {
+    format: BinaryFormat::Elf,
+    has_compilation_info_symbol: compilation_info_symbol.is_some(),
+    compilation_info,
+    exports,
+    post_exports,
+    imports,
+    interfaces,
+    needed_libraries: elf
+      .needed_libraries()
+      .into_iter()
+      .map(ToOwned::to_owned)
+      .collect(),
+    unloading_blockers,
+  }
+}
+
+fn inspect_pe(pe: &Pe) -> ModuleInspection {
+  let pe_exports = pe.exports();
+
+  let compilation_info_export = pe_exports
+    .iter()
+    .find(|export| export.name == COMPILATION_INFO_SYMBOL);
+
+  let compilation_info = compilation_info_export.and_then(|export| {
+    // it's relib_internal_shared::Str (pointer and length)
+    let ptr = pe.pointer_at(export.rva)?;
+    let len = pe.usize_at(export.rva.checked_add(pe.pointer_size())?)?;
+    let bytes = pe.data_at_rva(ptr, u32::try_from(len).ok()?)?;
+    String::from_utf8(bytes.to_vec()).ok()
+  });
+
+  let interfaces = collect_interfaces(pe_exports.iter().filter_map(|export| {
+    let trait_name = export.name.strip_prefix(SIGNATURE_PREFIX)?;
+    let signature = pe.string_at_rva(export.rva)?;
+    Some(InterfaceSignature::new(trait_name, signature))
+  }));
+
+  let (exports, post_exports, imports) = classify_symbols(
+    pe_exports
+      .iter()
+      .map(|export| (export.name.as_str(), pe.is_executable(export.rva))),
+  );
+
+  ModuleInspection {
+    format: BinaryFormat::Pe,
+    has_compilation_info_symbol: compilation_info_export.is_some(),
+    compilation_info,
+    exports,
+    post_exports,
+    imports,
+    interfaces,
+    needed_libraries: pe
+      .imported_libraries()
+      .into_iter()
+      .map(ToOwned::to_owned)
+      .collect(),
+    // Windows doesn't have such things as NODELETE or STB_GNU_UNIQUE
+    unloading_blockers: Vec::new(),
+  }
+}
+
+fn collect_interfaces(
+  interfaces: impl Iterator<Item = InterfaceSignature>,
+) -> Vec<InterfaceSignature> {
+  let mut interfaces: Vec<_> = interfaces.collect();
+  interfaces.sort_by(|a, b| a.trait_name.cmp(&b.trait_name));
+  interfaces
+}
+
+/// Splits relib symbols (name and whether it's a function) into exports, post exports and imports
+fn classify_symbols<'a>(
+  symbols: impl Iterator<Item = (&'a str, bool)>,
+) -> (Vec<String>, Vec<String>, Vec<String>) {
+  let mut exports = Vec::new();
+  let mut post_exports = Vec::new();
+  let mut imports = Vec::new();
+
+  for (name, is_function) in symbols {
+    let is_post = name.starts_with(POST_EXPORT_PREFIX);
+    if !is_post && !name.starts_with(EXPORT_PREFIX) {
+      continue;
+    }
+
+    let list = match (is_function, is_post) {
+      (true, false) => &mut exports,
+      (true, true) => &mut post_exports,
+      (false, _) => &mut imports,
+    };
+    list.push(name.to_owned());
+  }
+
+  exports.sort();
+  post_exports.sort();
+  imports.sort();
+
+  (exports, post_exports, imports)
+}
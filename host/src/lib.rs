@@ -1,11 +1,12 @@
 use {
   libloading::Symbol,
+  relib_interface::exports_types::{__ConstMismatch, __check_consts},
   relib_internal_shared::Str,
   std::{ffi::OsStr, path::Path},
 };
 
 mod errors;
-pub use errors::LoadError;
+pub use errors::{InspectError, LoadError};
 
 #[cfg(feature = "unloading")]
 mod unloading;
@@ -19,7 +20,17 @@ use helpers::{
   LIBRARY_LOADING_GUARD, is_library_loaded, next_module_id, open_library, path_to_str,
 };
 mod leak_library;
+mod load_options;
+pub use load_options::LoadOptions;
+mod elf;
+mod pe;
+mod inspect;
+pub use inspect::{BinaryFormat, InterfaceSignature, ModuleInspection, StaticUnloadBlocker, inspect};
+mod watchdog;
+pub use watchdog::{HungCall, StuckThread};
 pub mod exports_types;
+mod caller;
+pub use caller::{Caller, caller};
 pub use exports_types::{InitImports, ModuleExportsForHost};
 
 #[cfg(target_os = "windows")]
@@ -82,7 +93,7 @@ pub unsafe fn load_module<E: ModuleExportsForHost>(
   }
 }
 
-/// See [`load_module`].
+/// See [`load_module`] and [`load_module_with`].
 ///
 /// # Safety
 /// See [`load_module`].
@@ -93,6 +104,38 @@ pub unsafe fn load_module_with_options<E: ModuleExportsForHost>(
   // needs to be passed at runtime because host can load different modules with enabled and disabled alloc tracker
   #[cfg(feature = "unloading")] enable_alloc_tracker: bool,
 ) -> Result<Module<E>, crate::LoadError> {
+  #[allow(unused_mut)]
+  let mut options = LoadOptions::default();
+  #[cfg(feature = "unloading")]
+  {
+    options = options.enable_alloc_tracker(enable_alloc_tracker);
+  }
+
+  unsafe { load_module_with(path, init_imports, options) }
+}
+
+/// Same as [`load_module`] but with options, see [`LoadOptions`].
+///
+/// # Safety
+/// See [`load_module`].
+pub unsafe fn load_module_with<E: ModuleExportsForHost>(
+  path: impl AsRef<OsStr>,
+  init_imports: impl InitImports,
+  options: LoadOptions,
+) -> Result<Module<E>, crate::LoadError> {
+  #[cfg(feature = "unloading")]
+  let LoadOptions {
+    enable_alloc_tracker,
+    memory_budget,
+    imports_context,
+    granted_imports,
+  } = options;
+  #[cfg(not(feature = "unloading"))]
+  let LoadOptions {
+    imports_context,
+    granted_imports,
+  } = options;
+
   // prevent parallel loading of the same dynamic library
   // to guarantee that LoadError::ModuleAlreadyLoaded is returned
   let _loading_guard = LIBRARY_LOADING_GUARD
@@ -136,11 +179,34 @@ pub unsafe fn load_module_with_options<E: ModuleExportsForHost>(
     });
   }
 
+  let module_id = next_module_id();
+
+  let caller = caller::leak(Caller::new(module_id, imports_context, granted_imports));
+  let (pub_exports, const_mismatch) = __check_consts(|| {
+    let pub_exports = E::new(&library);
+    caller::while_loading(caller, || init_imports.init(&library)).map(|()| pub_exports)
+  });
+  let pub_exports = match const_mismatch {
+    Some(__ConstMismatch { trait_path, name }) => Err(LoadError::ConstMismatch {
+      trait_path: trait_path.to_owned(),
+      name: name.to_owned(),
+    }),
+    None => pub_exports,
+  };
+  let pub_exports = match pub_exports {
+    Ok(pub_exports) => pub_exports,
+    Err(e) => {
+      // SAFETY: module is not initialized yet and it will be unloaded when library is dropped
+      unsafe {
+        caller::free(caller);
+      }
+      return Err(e);
+    }
+  };
+
   #[cfg(target_os = "windows")]
   windows::dbghelp::add_module(path_str);
 
-  let module_id = next_module_id();
-
   #[cfg(feature = "unloading")]
   let internal_exports = {
     unloading::init_internal_imports(&library);
@@ -150,18 +216,23 @@ pub unsafe fn load_module_with_options<E: ModuleExportsForHost>(
     unsafe {
       internal_exports.init(thread_id::get(), module_id, enable_alloc_tracker);
     }
+    if memory_budget.is_some() {
+      unloading::memory_budget::set(module_id, &internal_exports, memory_budget);
+    }
     internal_exports
   };
 
-  let pub_exports = E::new(&library);
-  init_imports.init(&library);
-
   let module = Module::new(
     module_id,
     library,
     pub_exports,
     #[cfg(feature = "unloading")]
-    (internal_exports, path.to_owned(), enable_alloc_tracker),
+    (
+      internal_exports,
+      path.to_owned(),
+      enable_alloc_tracker,
+      caller,
+    ),
   );
 
   #[cfg(all(target_os = "windows", feature = "unloading"))]
@@ -208,23 +279,3 @@ pub unsafe fn forcibly_reinit_dbghelp() {
     windows::enable_hooks();
   }
 }
-
-// TODO: fix it
-#[doc(hidden)]
-#[cfg(all(target_os = "windows", feature = "unloading"))]
-pub unsafe fn __suppress_unused_warning_for_linux_only_exports(
-  exports: unloading::InternalModuleExports,
-) {
-  unsafe {
-    exports.spawned_threads_count();
-  }
-}
-
-#[doc(hidden)]
-#[expect(unreachable_code)]
-#[cfg(all(target_os = "linux", feature = "unloading"))]
-pub unsafe fn __suppress_unused_warning_for_windows_only_exports(
-  exports: unloading::InternalModuleExports,
-) {
-  unsafe { exports.set_dealloc_callback(todo!()) }
-}
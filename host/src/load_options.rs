@@ -0,0 +1,85 @@
+#[cfg(feature = "unloading")]
+use crate::MemoryBudget;
+use {
+  crate::caller::{ImportGrants, ImportsContext},
+  std::{any::Any, sync::Arc},
+};
+
+/// Options of [`load_module_with`](crate::load_module_with).
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct LoadOptions {
+  /// Needs to be passed at runtime because host can load different modules
+  /// with enabled and disabled alloc tracker.
+  ///
+  /// Default is `true`.
+  #[cfg(feature = "unloading")]
+  pub enable_alloc_tracker: bool,
+
+  /// Limit of live heap memory of the module, can be changed later using
+  /// [`Module::set_memory_budget`](crate::Module::set_memory_budget).
+  ///
+  /// `None` (default) means no limit.
+  #[cfg(feature = "unloading")]
+  pub memory_budget: Option<MemoryBudget>,
+
+  /// Context of the module which is available in implementations of imports,
+  /// see [`caller`](crate::caller).
+  ///
+  /// `None` (default) means no context.
+  pub imports_context: Option<ImportsContext>,
+
+  /// Imports which module is allowed to call, see [`LoadOptions::grant_imports`].
+  ///
+  /// `None` (default) means that all imports are granted.
+  pub granted_imports: Option<ImportGrants>,
+}
+
+#[allow(clippy::derivable_impls)] // it's derivable only without "unloading" feature
+impl Default for LoadOptions {
+  fn default() -> Self {
+    Self {
+      #[cfg(feature = "unloading")]
+      enable_alloc_tracker: true,
+      #[cfg(feature = "unloading")]
+      memory_budget: None,
+      imports_context: None,
+      granted_imports: None,
+    }
+  }
+}
+
+impl LoadOptions {
+  pub fn imports_context(mut self, context: impl Any + Send + Sync) -> Self {
+    self.imports_context = Some(Arc::new(context));
+    self
+  }
+
+  /// Grants module access only to specified imports, other imports are not passed to the module
+  /// and it will panic if it tries to call them.
+  ///
+  /// Every item is either name of imports trait (for example, `"FsImports"`), which grants all of its functions,
+  /// or name of a function with its trait (`"FsImports::read_file"`).
+  /// Can be called multiple times.
+  ///
+  /// If some of the names are not imported by the module, [`load_module_with`](crate::load_module_with)
+  /// returns [`LoadError::UnknownGrantedImports`](crate::LoadError::UnknownGrantedImports).
+  pub fn grant_imports(mut self, imports: impl IntoIterator<Item = impl Into<String>>) -> Self {
+    let granted_imports = self.granted_imports.get_or_insert_default();
+    Arc::make_mut(granted_imports).extend(imports.into_iter().map(Into::into));
+    self
+  }
+}
+
+#[cfg(feature = "unloading")]
+impl LoadOptions {
+  pub fn enable_alloc_tracker(mut self, enable: bool) -> Self {
+    self.enable_alloc_tracker = enable;
+    self
+  }
+
+  pub fn memory_budget(mut self, budget: MemoryBudget) -> Self {
+    self.memory_budget = Some(budget);
+    self
+  }
+}
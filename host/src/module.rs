@@ -3,15 +3,23 @@ use std::{marker::PhantomData, path::PathBuf};
 
 use {
   crate::{
-    exports_types::ModuleExportsForHost, helpers::call_module_pub_export, leak_library::LeakLibrary,
+    HungCall,
+    exports_types::ModuleExportsForHost,
+    helpers::{call_module_pub_export, get_module_pub_export},
+    leak_library::LeakLibrary,
+    watchdog,
   },
   libloading::Library,
   relib_internal_shared::ModuleId,
-  std::fmt::Debug,
+  std::{
+    fmt::Debug,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+  },
 };
 
 #[cfg(feature = "unloading")]
-use crate::unloading::InternalModuleExports;
+use crate::{Caller, unloading::InternalModuleExports};
 
 #[cfg(all(target_os = "windows", feature = "unloading"))]
 pub(crate) type WindowsLibraryHandle = isize;
@@ -33,6 +41,10 @@ pub struct Module<E: ModuleExportsForHost> {
 
   pub_exports: E,
 
+  /// Set when some call didn't finish in time (see [`Module::call_with_timeout`]),
+  /// such module can never be unloaded
+  pub(crate) hung: AtomicBool,
+
   #[cfg(feature = "unloading")]
   /// Module must be loaded and unloaded from the same thread
   /// for thread locals destructors to work correctly.
@@ -40,6 +52,11 @@ pub struct Module<E: ModuleExportsForHost> {
 
   #[cfg(feature = "unloading")]
   pub(crate) alloc_tracker_enabled: bool,
+
+  /// Passed to the module, so it's available in implementations of imports,
+  /// freed when module is unloaded
+  #[cfg(feature = "unloading")]
+  pub(crate) caller: &'static Caller,
 }
 
 impl<E: ModuleExportsForHost> Module<E> {
@@ -48,10 +65,11 @@ impl<E: ModuleExportsForHost> Module<E> {
     library: Library,
     pub_exports: E,
 
-    #[cfg(feature = "unloading")] (internal_exports, library_path, alloc_tracker_enabled): (
+    #[cfg(feature = "unloading")] (internal_exports, library_path, alloc_tracker_enabled, caller): (
       InternalModuleExports,
       PathBuf,
       bool,
+      &'static Caller,
     ),
   ) -> Self {
     #[cfg(all(target_os = "windows", feature = "unloading"))]
@@ -61,6 +79,7 @@ impl<E: ModuleExportsForHost> Module<E> {
       id,
       library: LeakLibrary::new(library),
       pub_exports,
+      hung: AtomicBool::new(false),
 
       #[cfg(feature = "unloading")]
       _not_thread_safe: PhantomData,
@@ -71,6 +90,8 @@ impl<E: ModuleExportsForHost> Module<E> {
       internal_exports,
       #[cfg(feature = "unloading")]
       alloc_tracker_enabled,
+      #[cfg(feature = "unloading")]
+      caller,
 
       #[cfg(all(target_os = "windows", feature = "unloading"))]
       library_handle,
@@ -121,6 +142,80 @@ impl<E: ModuleExportsForHost> Module<E> {
       panic!("Failed to get main fn from module, reason: {e:#}");
     })
   }
+
+  /// Same as [`Module::call_main`] but if main doesn't return in `timeout`
+  /// [`HungCall`] is returned and module is marked as hung (see [`Module::call_with_timeout`]).
+  ///
+  /// # Safety
+  /// See [`Module::call_main`].
+  ///
+  /// # Panics
+  /// If main function is not exported from the module.
+  pub unsafe fn call_main_with_timeout<R>(&self, timeout: Duration) -> Result<Option<R>, HungCall>
+  where
+    R: Clone + Send + 'static,
+  {
+    let main = unsafe { get_module_pub_export::<R>(self.library(), "main") };
+    let main = main.unwrap_or_else(|e| {
+      panic!("Failed to get main fn from module, reason: {e:#}");
+    });
+
+    // SAFETY: if it hangs module is marked as hung and will never be unloaded
+    self.watch("main", timeout, move || unsafe { main.call() })
+  }
+
+  /// Calls `f` with module exports in a separate thread and waits for it no longer than `timeout`.
+  ///
+  /// If it takes longer (for example, module deadlocked) [`HungCall`] is returned
+  /// with what is known about the stuck thread, and module is marked as hung:
+  /// it's leaked and [`Module::unload`] will return an error since it's not possible to stop the thread safely.
+  ///
+  /// ```ignore
+  /// let value = module.call_with_timeout(Duration::from_secs(1), |exports| unsafe {
+  ///   exports.foo()
+  /// });
+  /// match value {
+  ///   Ok(Some(value)) => {}
+  ///   Ok(None) => println!("module panicked"),
+  ///   Err(hung) => println!("{hung}"),
+  /// }
+  /// ```
+  ///
+  /// **note:** thread-locals of the module initialized in `f` are destroyed when the thread exits.
+  pub fn call_with_timeout<R>(
+    &self,
+    timeout: Duration,
+    f: impl FnOnce(&E) -> R + Send + 'static,
+  ) -> Result<R, HungCall>
+  where
+    E: Clone + Send + 'static,
+    R: Send + 'static,
+  {
+    let exports = self.pub_exports.clone();
+    self.watch("exports", timeout, move || f(&exports))
+  }
+
+  /// Returns `true` if some call didn't finish in time,
+  /// see [`Module::call_with_timeout`].
+  pub fn is_hung(&self) -> bool {
+    self.hung.load(Ordering::SeqCst)
+  }
+
+  fn watch<R>(
+    &self,
+    call: &str,
+    timeout: Duration,
+    f: impl FnOnce() -> R + Send + 'static,
+  ) -> Result<R, HungCall>
+  where
+    R: Send + 'static,
+  {
+    let result = watchdog::call_with_timeout(call, timeout, f);
+    if result.is_err() {
+      self.hung.store(true, Ordering::SeqCst);
+    }
+    result
+  }
 }
 
 impl<E: ModuleExportsForHost> Debug for Module<E> {
@@ -0,0 +1,247 @@
+//! Minimal reader of PE files (32-bit and 64-bit),
+//! only what's needed to inspect modules without loading them.
+
+use std::{ffi::CStr, ops::Range};
+
+const DOS_MAGIC: &[u8] = b"MZ";
+const PE_SIGNATURE: &[u8] = b"PE\0\0";
+const PE32_MAGIC: u16 = 0x10b;
+const PE32_PLUS_MAGIC: u16 = 0x20b;
+
+const EXPORT_DIRECTORY: usize = 0;
+const IMPORT_DIRECTORY: usize = 1;
+
+pub const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+
+pub struct Pe<'data> {
+  data: &'data [u8],
+  is_64_bit: bool,
+  image_base: u64,
+  data_directories: Vec<DataDirectory>,
+  sections: Vec<SectionHeader>,
+}
+
+#[derive(Clone, Copy)]
+struct DataDirectory {
+  rva: u32,
+  size: u32,
+}
+
+#[derive(Clone)]
+pub struct SectionHeader {
+  pub virtual_address: u32,
+  pub virtual_size: u32,
+  pub raw_data_offset: u32,
+  pub raw_data_size: u32,
+  pub characteristics: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Export {
+  pub name: String,
+  pub rva: u32,
+}
+
+impl<'data> Pe<'data> {
+  /// Returns `None` if it's not a PE file or it's malformed
+  pub fn parse(data: &'data [u8]) -> Option<Self> {
+    if data.get(..2)? != DOS_MAGIC {
+      return None;
+    }
+
+    let pe_offset = read_u32(data, 0x3c)? as usize;
+    if data.get(pe_offset..pe_offset.checked_add(4)?)? != PE_SIGNATURE {
+      return None;
+    }
+
+    let coff = data.get(pe_offset.checked_add(4)?..)?;
+    let sections_count = read_u16(coff, 2)? as usize;
+    let optional_header_size = read_u16(coff, 16)? as usize;
+
+    let optional = coff.get(20..)?;
+    let (is_64_bit, image_base, directories_count_offset) = match read_u16(optional, 0)? {
+      PE32_MAGIC => (false, read_u32(optional, 28)? as u64, 92),
+      PE32_PLUS_MAGIC => (true, read_u64(optional, 24)?, 108),
+      _ => return None,
+    };
+
+    let directories = optional.get(directories_count_offset..)?;
+    let directories_count = read_u32(directories, 0)? as usize;
+    let data_directories = (0..directories_count.min(16))
+      .map(|idx| {
+        let base = 4 + idx * 8;
+        Some(DataDirectory {
+          rva: read_u32(directories, base)?,
+          size: read_u32(directories, base + 4)?,
+        })
+      })
+      .collect::<Option<Vec<_>>>()?;
+
+    let sections_table = optional.get(optional_header_size..)?;
+    let sections = (0..sections_count)
+      .map(|idx| {
+        let header = sections_table.get(idx * 40..)?;
+        Some(SectionHeader {
+          virtual_size: read_u32(header, 8)?,
+          virtual_address: read_u32(header, 12)?,
+          raw_data_size: read_u32(header, 16)?,
+          raw_data_offset: read_u32(header, 20)?,
+          characteristics: read_u32(header, 36)?,
+        })
+      })
+      .collect::<Option<Vec<_>>>()?;
+
+    Some(Self {
+      data,
+      is_64_bit,
+      image_base,
+      data_directories,
+      sections,
+    })
+  }
+
+  fn section_of(&self, rva: u32) -> Option<&SectionHeader> {
+    self.sections.iter().find(|s| {
+      let size = s.virtual_size.max(s.raw_data_size);
+      rva >= s.virtual_address && rva - s.virtual_address < size
+    })
+  }
+
+  /// Contents of the file which will be mapped at `rva` (address relative to the image base)
+  pub fn data_at_rva(&self, rva: u32, len: u32) -> Option<&'data [u8]> {
+    let section = self.section_of(rva)?;
+    let offset_in_section = rva - section.virtual_address;
+    if offset_in_section.checked_add(len)? > section.raw_data_size {
+      return None;
+    }
+    let range = file_range(section.raw_data_offset.checked_add(offset_in_section)?, len)?;
+    self.data.get(range)
+  }
+
+  pub fn is_executable(&self, rva: u32) -> bool {
+    self
+      .section_of(rva)
+      .is_some_and(|s| s.characteristics & IMAGE_SCN_MEM_EXECUTE != 0)
+  }
+
+  pub fn pointer_size(&self) -> u32 {
+    if self.is_64_bit { 8 } else { 4 }
+  }
+
+  /// Pointer-sized integer stored at `rva`
+  pub fn usize_at(&self, rva: u32) -> Option<u64> {
+    let data = self.data_at_rva(rva, self.pointer_size())?;
+    if self.is_64_bit {
+      read_u64(data, 0)
+    } else {
+      read_u32(data, 0).map(u64::from)
+    }
+  }
+
+  /// Pointer stored at `rva` converted to rva
+  /// (pointers are stored as absolute addresses relative to the preferred image base)
+  pub fn pointer_at(&self, rva: u32) -> Option<u32> {
+    let address = self.usize_at(rva)?;
+    u32::try_from(address.checked_sub(self.image_base)?).ok()
+  }
+
+  /// Named exports, forwarded exports are skipped
+  pub fn exports(&self) -> Vec<Export> {
+    self.read_exports().unwrap_or_default()
+  }
+
+  fn read_exports(&self) -> Option<Vec<Export>> {
+    let directory = self.data_directories.get(EXPORT_DIRECTORY)?;
+    if directory.rva == 0 {
+      return Some(Vec::new());
+    }
+    let table = self.data_at_rva(directory.rva, 40)?;
+
+    let names_count = read_u32(table, 24)?;
+    let functions_rva = read_u32(table, 28)?;
+    let names_rva = read_u32(table, 32)?;
+    let ordinals_rva = read_u32(table, 36)?;
+
+    let forwarded = |rva: u32| rva >= directory.rva && rva - directory.rva < directory.size;
+
+    // entry of the table at `table_rva` with `idx` index
+    let entry = |table_rva: u32, idx: u32, size: u32| {
+      self.data_at_rva(table_rva.checked_add(idx.checked_mul(size)?)?, size)
+    };
+
+    let exports = (0..names_count)
+      .filter_map(|idx| {
+        let name_rva = read_u32(entry(names_rva, idx, 4)?, 0)?;
+        let ordinal = read_u16(entry(ordinals_rva, idx, 2)?, 0)? as u32;
+        let rva = read_u32(entry(functions_rva, ordinal, 4)?, 0)?;
+        if forwarded(rva) {
+          return None;
+        }
+
+        Some(Export {
+          name: self.string_at_rva(name_rva)?.to_owned(),
+          rva,
+        })
+      })
+      .collect();
+
+    Some(exports)
+  }
+
+  /// Names of imported DLLs
+  pub fn imported_libraries(&self) -> Vec<&'data str> {
+    let Some(directory) = self.data_directories.get(IMPORT_DIRECTORY) else {
+      return Vec::new();
+    };
+    if directory.rva == 0 {
+      return Vec::new();
+    }
+
+    (0..)
+      .map_while(|idx: u32| {
+        let rva = directory.rva.checked_add(idx.checked_mul(20)?)?;
+        let descriptor = self.data_at_rva(rva, 20)?;
+        // descriptors are terminated with zeroed one
+        if descriptor.iter().all(|byte| *byte == 0) {
+          return None;
+        }
+        Some(read_u32(descriptor, 12))
+      })
+      .filter_map(|name_rva| self.string_at_rva(name_rva?))
+      .collect()
+  }
+
+  /// Null-terminated string stored at `rva`
+  pub fn string_at_rva(&self, rva: u32) -> Option<&'data str> {
+    let section = self.section_of(rva)?;
+    let offset_in_section = rva - section.virtual_address;
+    let start = section.raw_data_offset.checked_add(offset_in_section)? as usize;
+    let end = section.raw_data_offset.checked_add(section.raw_data_size)? as usize;
+    let string = CStr::from_bytes_until_nul(self.data.get(start..end)?).ok()?;
+    string.to_str().ok()
+  }
+}
+
+fn file_range(offset: u32, len: u32) -> Option<Range<usize>> {
+  let start = offset as usize;
+  let end = start.checked_add(len as usize)?;
+  Some(start..end)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+  Some(u16::from_le_bytes(
+    data.get(offset..offset.checked_add(2)?)?.try_into().ok()?,
+  ))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+  Some(u32::from_le_bytes(
+    data.get(offset..offset.checked_add(4)?)?.try_into().ok()?,
+  ))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+  Some(u64::from_le_bytes(
+    data.get(offset..offset.checked_add(8)?)?.try_into().ok()?,
+  ))
+}
@@ -1,8 +1,24 @@
 mod module;
 mod errors;
 pub use errors::UnloadError;
+mod options;
+pub use options::{UnloadOptions, UntrackedThreadsPolicy};
+mod diagnosis;
+pub use diagnosis::{UnloadBlocker, UnloadDiagnosis};
+pub(crate) mod memory_budget;
+pub use memory_budget::{BudgetExceeded, MemoryBudget, MemoryBudgetCallback, MemoryBudgetPolicy};
+mod memory_stats;
+pub use memory_stats::{MemoryStats, SizeClass};
+mod soak;
+pub use soak::{ResourceGrowth, SoakError, SoakOptions, SoakReport, SoakResource, SoakSample, soak};
+mod module_threads;
+pub use module_threads::{ModuleThread, UntrackedThread, UntrackedThreadEvidence};
 pub(crate) mod module_allocs;
+pub(crate) mod module_arena;
+pub(crate) mod module_host_alloc;
 pub(crate) mod helpers;
+#[cfg(target_os = "linux")]
+mod guard_pages;
 mod imports_impl;
 #[cfg(target_os = "windows")]
 mod windows_dealloc;
@@ -0,0 +1,205 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// What is known about why module is still loaded after unloading,
+/// see [`UnloadError::UnloadingFail`](crate::UnloadError::UnloadingFail).
+#[derive(Debug, Clone, Default)]
+pub struct UnloadDiagnosis {
+  /// Empty if nothing suspicious was found (always empty on Windows)
+  pub findings: Vec<UnloadBlocker>,
+}
+
+/// Known reason which can keep dynamic library loaded.
+#[derive(Debug, Clone)]
+pub enum UnloadBlocker {
+  /// Dynamic linker still has the library loaded,
+  /// someone else holds a `dlopen` reference to it
+  StillReferenced,
+  /// Dynamic linker unloaded the library but its file is still mapped into memory
+  /// (for example, someone leaked mmap of it)
+  FileStillMapped,
+  /// Library is marked with `DF_1_NODELETE` flag (for example, it's linked with `-z nodelete`)
+  NoDeleteFlag,
+  /// Library defines `STB_GNU_UNIQUE` symbols, glibc never unloads such libraries
+  GnuUniqueSymbols(Vec<String>),
+  /// Still running threads (os ids) which registered destructors of thread-locals of the module,
+  /// glibc won't unload it until they exit
+  ForeignThreadLocalDestructors(Vec<u64>),
+  /// Other loaded libraries which depend on the module (`DT_NEEDED`)
+  Dependents(Vec<String>),
+}
+
+impl Display for UnloadBlocker {
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    match self {
+      Self::StillReferenced => write!(
+        f,
+        "dynamic linker still has it loaded (something else holds dlopen reference to it)"
+      ),
+      Self::FileStillMapped => write!(
+        f,
+        "dynamic linker unloaded it but the file is still mapped (leaked mmap?)"
+      ),
+      Self::NoDeleteFlag => write!(f, "library has DF_1_NODELETE flag"),
+      Self::GnuUniqueSymbols(symbols) => write!(
+        f,
+        "library defines STB_GNU_UNIQUE symbols: {}",
+        symbols.join(", ")
+      ),
+      Self::ForeignThreadLocalDestructors(threads) => write!(
+        f,
+        "threads with registered destructors of module thread-locals are still running (os ids): {threads:?}"
+      ),
+      Self::Dependents(libraries) => write!(
+        f,
+        "loaded libraries which depend on it: {}",
+        libraries.join(", ")
+      ),
+    }
+  }
+}
+
+impl Display for UnloadDiagnosis {
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    if self.findings.is_empty() {
+      return write!(f, "unknown reason");
+    }
+
+    for finding in &self.findings {
+      write!(f, "\n- {finding}")?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(target_os = "linux")]
+mod linux_impl {
+  use {
+    super::{UnloadBlocker, UnloadDiagnosis},
+    crate::{elf::Elf, unloading::InternalModuleExports},
+    std::{
+      ffi::{CStr, CString, c_void},
+      fs,
+      path::Path,
+    },
+  };
+
+  /// Must be called before module is closed
+  pub fn foreign_tls_dtor_threads(exports: &InternalModuleExports) -> Vec<u64> {
+    let mut threads = Vec::<u64>::new();
+
+    extern "C" fn visit(ctx: *mut c_void, os_id: u64) {
+      let threads = unsafe { &mut *(ctx as *mut Vec<u64>) };
+      threads.push(os_id);
+    }
+
+    unsafe {
+      exports.visit_foreign_tls_dtor_threads(visit, &mut threads as *mut _ as *mut c_void);
+    }
+
+    threads
+  }
+
+  pub fn diagnose(library_path: &str, foreign_tls_dtor_threads: Vec<u64>) -> UnloadDiagnosis {
+    let mut findings = Vec::new();
+
+    if is_loaded_by_dynamic_linker(library_path) {
+      findings.push(UnloadBlocker::StillReferenced);
+    } else {
+      findings.push(UnloadBlocker::FileStillMapped);
+    }
+
+    let file = fs::read(library_path).ok();
+    let elf = file.as_deref().and_then(Elf::parse);
+    if let Some(elf) = &elf {
+      if elf.has_nodelete_flag() {
+        findings.push(UnloadBlocker::NoDeleteFlag);
+      }
+
+      let unique_symbols = elf.gnu_unique_symbols();
+      if !unique_symbols.is_empty() {
+        findings.push(UnloadBlocker::GnuUniqueSymbols(unique_symbols));
+      }
+    }
+
+    let alive_threads: Vec<u64> = foreign_tls_dtor_threads
+      .into_iter()
+      .filter(|os_id| Path::new(&format!("/proc/self/task/{os_id}")).exists())
+      .collect();
+    if !alive_threads.is_empty() {
+      findings.push(UnloadBlocker::ForeignThreadLocalDestructors(alive_threads));
+    }
+
+    let file_name = Path::new(library_path)
+      .file_name()
+      .map(|name| name.to_string_lossy().into_owned());
+    let soname = elf.as_ref().and_then(|elf| elf.soname());
+    let names: Vec<&str> = file_name.as_deref().into_iter().chain(soname).collect();
+
+    let dependents = dependents(&names);
+    if !dependents.is_empty() {
+      findings.push(UnloadBlocker::Dependents(dependents));
+    }
+
+    UnloadDiagnosis { findings }
+  }
+
+  fn is_loaded_by_dynamic_linker(library_path: &str) -> bool {
+    let Ok(path) = CString::new(library_path) else {
+      return false;
+    };
+
+    let handle = unsafe { libc::dlopen(path.as_ptr(), libc::RTLD_LAZY | libc::RTLD_NOLOAD) };
+    if handle.is_null() {
+      return false;
+    }
+
+    // RTLD_NOLOAD still increments reference count
+    unsafe {
+      libc::dlclose(handle);
+    }
+    true
+  }
+
+  /// Loaded libraries which have one of the `names` in `DT_NEEDED`
+  fn dependents(names: &[&str]) -> Vec<String> {
+    unsafe extern "C" fn callback(
+      info: *mut libc::dl_phdr_info,
+      _size: libc::size_t,
+      ctx: *mut c_void,
+    ) -> libc::c_int {
+      let libraries = unsafe { &mut *(ctx as *mut Vec<String>) };
+      let name = unsafe { (*info).dlpi_name };
+      if !name.is_null() {
+        let name = unsafe { CStr::from_ptr(name) };
+        if !name.is_empty() {
+          libraries.push(name.to_string_lossy().into_owned());
+        }
+      }
+      0
+    }
+
+    let mut libraries = Vec::<String>::new();
+    unsafe {
+      libc::dl_iterate_phdr(Some(callback), &mut libraries as *mut _ as *mut c_void);
+    }
+
+    libraries
+      .into_iter()
+      .filter(|library| {
+        let Ok(file) = fs::read(library) else {
+          return false;
+        };
+        let Some(elf) = Elf::parse(&file) else {
+          return false;
+        };
+        elf
+          .needed_libraries()
+          .iter()
+          .any(|needed| names.contains(needed))
+      })
+      .collect()
+  }
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) use linux_impl::{diagnose, foreign_tls_dtor_threads};
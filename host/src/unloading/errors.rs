@@ -1,4 +1,11 @@
-use thiserror::Error;
+use {
+  super::{
+    diagnosis::UnloadDiagnosis,
+    module_threads::{ModuleThread, UntrackedThread, display_threads},
+  },
+  crate::HungCall,
+  thiserror::Error,
+};
 
 #[cfg(feature = "unloading")]
 #[derive(Error, Debug)]
@@ -9,13 +16,44 @@ pub enum UnloadError {
   )]
   BeforeUnloadPanicked(String),
 
+  #[error(
+    "module export \"before_unload\" timed out, module is leaked\n\
+    module path: {0}\n\
+    {1}"
+  )]
+  BeforeUnloadTimedOut(String, Box<HungCall>),
+
+  #[error(
+    "module has a hung call, it's not possible to unload it (module is leaked)\n\
+    module path: {0}"
+  )]
+  ModuleHung(String),
+
+  #[error(
+    "module still has {1} alive `ModuleBox` handle(s), they must be dropped before unloading (module is leaked)\n\
+    module path: {0}\n\
+    note: use `Module::module_boxes_count` to check it before unloading, \
+    boxes kept by the module itself can be dropped in `before_unload`"
+  )]
+  ModuleBoxesAlive(String, u64),
+
   #[error(
     "module still has running threads\n\
     module path: {0}\n\
+    threads: {threads}\n\
     note: module can export \"before_unload\" function to join spawned threads: \
-    https://docs.rs/relib/latest/relib/docs/index.html#before_unload"
+    https://docs.rs/relib/latest/relib/docs/index.html#before_unload",
+    threads = display_threads(.1)
   )]
-  ThreadsStillRunning(String),
+  ThreadsStillRunning(String, Vec<ModuleThread>),
+
+  #[error(
+    "module code is still running on threads which weren't spawned by it\n\
+    module path: {0}\n\
+    threads: {threads}",
+    threads = display_threads(.1)
+  )]
+  UntrackedThreadsRunning(String, Vec<UntrackedThread>),
 
   #[error(
     "libloading unload error\n\
@@ -24,8 +62,9 @@ pub enum UnloadError {
   Libloading(#[from] libloading::Error),
 
   #[error(
-    "unloading failed for unknown reason (called destructors of thread-locals, checked running threads but it still failed)\n\
-    module path: {0}"
+    "unloading failed, module is still loaded (called destructors of thread-locals, checked running threads but it still failed)\n\
+    module path: {0}\n\
+    diagnosis: {1}"
   )]
-  UnloadingFail(String),
+  UnloadingFail(String, UnloadDiagnosis),
 }
@@ -0,0 +1,150 @@
+//! Use-after-unload detection (see [`UnloadOptions::guard_unloaded_memory`](super::UnloadOptions::guard_unloaded_memory)):
+//! after unloading, address ranges where module was mapped are reserved as inaccessible memory,
+//! SIGSEGV handler reports faults in them instead of executing or reading whatever is mapped there later.
+
+use std::{
+  fs,
+  ops::Range,
+  ptr,
+  sync::{
+    Once,
+    atomic::{AtomicPtr, Ordering},
+  },
+};
+
+struct Guard {
+  range: Range<usize>,
+  /// Preformatted since only async-signal-safe functions can be used in the signal handler
+  message: Box<[u8]>,
+  next: *mut Guard,
+}
+
+/// Append-only linked list, guards are never removed so signal handler can read it without locking
+static GUARDS: AtomicPtr<Guard> = AtomicPtr::new(ptr::null_mut());
+
+static INSTALL_HANDLER: Once = Once::new();
+// SAFETY: written once in install_handler before any guard is added
+static mut PREVIOUS_ACTION: libc::sigaction = unsafe { std::mem::zeroed() };
+
+/// Address ranges mapped from the file of the module, must be called before `dlclose`
+pub fn module_ranges(library_path: &str) -> Vec<Range<usize>> {
+  let Ok(maps) = fs::read_to_string("/proc/self/maps") else {
+    return Vec::new();
+  };
+
+  maps
+    .lines()
+    .filter(|line| line.contains(library_path))
+    .filter_map(|line| {
+      let (start, end) = line.split_whitespace().next()?.split_once('-')?;
+      let start = usize::from_str_radix(start, 16).ok()?;
+      let end = usize::from_str_radix(end, 16).ok()?;
+      Some(start..end)
+    })
+    .collect()
+}
+
+/// Must be called after module is unloaded, ranges which are already reused by something else are skipped
+pub fn guard(library_path: &str, ranges: Vec<Range<usize>>) {
+  INSTALL_HANDLER.call_once(install_handler);
+
+  let message = format!(
+    "[relib] called into unloaded module (or accessed its memory): {library_path}\n\
+    note: function pointers, vtables and `&'static` references obtained from module \
+    must not be used after unloading\n\
+    aborting\n"
+  );
+
+  for range in ranges {
+    let ptr = unsafe {
+      libc::mmap(
+        range.start as *mut libc::c_void,
+        range.end - range.start,
+        libc::PROT_NONE,
+        libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_NORESERVE | libc::MAP_FIXED_NOREPLACE,
+        -1,
+        0,
+      )
+    };
+    if ptr == libc::MAP_FAILED {
+      continue;
+    }
+
+    // older kernels ignore MAP_FIXED_NOREPLACE and treat address only as a hint
+    if ptr as usize != range.start {
+      unsafe {
+        libc::munmap(ptr, range.end - range.start);
+      }
+      continue;
+    }
+
+    let guard = Box::into_raw(Box::new(Guard {
+      range,
+      message: message.as_bytes().into(),
+      next: ptr::null_mut(),
+    }));
+
+    let mut head = GUARDS.load(Ordering::Acquire);
+    loop {
+      unsafe {
+        (*guard).next = head;
+      }
+      match GUARDS.compare_exchange_weak(head, guard, Ordering::AcqRel, Ordering::Acquire) {
+        Ok(_) => break,
+        Err(actual) => head = actual,
+      }
+    }
+  }
+}
+
+fn install_handler() {
+  unsafe {
+    let mut action: libc::sigaction = std::mem::zeroed();
+    action.sa_sigaction = handler as *const () as usize;
+    action.sa_flags = libc::SA_SIGINFO | libc::SA_ONSTACK;
+    libc::sigemptyset(&mut action.sa_mask);
+
+    libc::sigaction(libc::SIGSEGV, &action, &raw mut PREVIOUS_ACTION);
+  }
+}
+
+extern "C" fn handler(signal: libc::c_int, info: *mut libc::siginfo_t, context: *mut libc::c_void) {
+  let addr = unsafe { (*info).si_addr() } as usize;
+
+  let mut guard = GUARDS.load(Ordering::Acquire);
+  while !guard.is_null() {
+    let Guard {
+      range,
+      message,
+      next,
+    } = unsafe { &*guard };
+
+    if range.contains(&addr) {
+      unsafe {
+        libc::write(libc::STDERR_FILENO, message.as_ptr().cast(), message.len());
+        libc::abort();
+      }
+    }
+    guard = *next;
+  }
+
+  // not ours, passing it to the previous handler (for example, stack overflow handler of std)
+  unsafe {
+    let previous = ptr::read(&raw const PREVIOUS_ACTION);
+    match previous.sa_sigaction {
+      libc::SIG_DFL | libc::SIG_IGN => {
+        // fault will happen again after return and kill the process
+        libc::signal(signal, libc::SIG_DFL);
+      }
+      previous_handler if previous.sa_flags & libc::SA_SIGINFO != 0 => {
+        let previous_handler: extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void) =
+          std::mem::transmute(previous_handler);
+        previous_handler(signal, info, context);
+      }
+      previous_handler => {
+        let previous_handler: extern "C" fn(libc::c_int) = std::mem::transmute(previous_handler);
+        previous_handler(signal);
+      }
+    }
+  }
+}
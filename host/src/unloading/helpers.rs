@@ -1,3 +1,23 @@
+use std::{
+  thread,
+  time::{Duration, Instant},
+};
+
+/// Polls `condition` until it returns `true` or `timeout` is exceeded,
+/// `condition` is always checked at least once
+pub fn wait_for(timeout: Duration, mut condition: impl FnMut() -> bool) -> bool {
+  let deadline = Instant::now() + timeout;
+  loop {
+    if condition() {
+      return true;
+    }
+    if Instant::now() >= deadline {
+      return false;
+    }
+    thread::sleep(Duration::from_millis(5));
+  }
+}
+
 pub fn unrecoverable(message: &str) -> ! {
   let message = format!("something unrecoverable happened: {message}");
   unrecoverable_impl(&message);
@@ -1,5 +1,8 @@
 use {
-  super::{gen_imports::ModuleImportsImpl, helpers, module_allocs},
+  super::{
+    gen_imports::ModuleImportsImpl, helpers, memory_budget, module_allocs, module_arena,
+    module_host_alloc,
+  },
   relib_internal_shared::{
     ModuleId, SliceAllocatorOp, StableLayout, Str, imports::___Internal___Imports___ as Imports,
   },
@@ -22,4 +25,32 @@ impl Imports for ModuleImportsImpl {
   fn is_ptr_allocated(module: ModuleId, ptr: *mut u8) -> bool {
     module_allocs::is_ptr_allocated(module, ptr)
   }
+
+  fn on_memory_budget_exceeded(module: ModuleId, requested: usize, live_bytes: u64) -> bool {
+    memory_budget::on_exceeded(module, requested, live_bytes)
+  }
+
+  fn arena_map(module: ModuleId, size: usize, align: usize) -> *mut u8 {
+    module_arena::map(module, size, align)
+  }
+
+  fn arena_unmap(module: ModuleId, ptr: *mut u8) {
+    module_arena::unmap(module, ptr);
+  }
+
+  fn host_alloc(module: ModuleId, layout: StableLayout) -> *mut u8 {
+    module_host_alloc::alloc(module, layout)
+  }
+
+  fn host_dealloc(_module: ModuleId, ptr: *mut u8, layout: StableLayout) {
+    module_host_alloc::dealloc(ptr, layout);
+  }
+
+  fn host_alloc_adopt(module: ModuleId, ptr: *mut u8, layout: StableLayout) {
+    module_host_alloc::adopt(module, ptr, layout);
+  }
+
+  fn host_alloc_release(module: ModuleId, ptr: *mut u8) {
+    module_host_alloc::release(module, ptr);
+  }
 }
@@ -0,0 +1,178 @@
+use {
+  super::{InternalModuleExports, helpers::unrecoverable},
+  crate::{Module, ModuleExportsForHost},
+  relib_internal_shared::{BudgetPolicy, ModuleId},
+  std::{
+    collections::HashMap,
+    fmt::{Debug, Formatter, Result as FmtResult},
+    panic::{AssertUnwindSafe, catch_unwind},
+    sync::{Arc, LazyLock, Mutex, MutexGuard},
+  },
+};
+
+/// Limit of live heap memory of the module, checked by module alloc tracker on every allocation.
+/// It's ignored (with a warning) if module uses `ArenaAlloc` or `HostAlloc` instead.
+///
+/// Check is approximate: allocations made concurrently from different threads may exceed it a bit.
+#[derive(Debug, Clone)]
+pub struct MemoryBudget {
+  pub limit_bytes: u64,
+  /// What to do when allocation would exceed the limit,
+  /// default is [`MemoryBudgetPolicy::Fail`]
+  pub policy: MemoryBudgetPolicy,
+}
+
+impl MemoryBudget {
+  pub fn new(limit_bytes: u64) -> Self {
+    Self {
+      limit_bytes,
+      policy: MemoryBudgetPolicy::Fail,
+    }
+  }
+
+  pub fn policy(mut self, policy: MemoryBudgetPolicy) -> Self {
+    self.policy = policy;
+    self
+  }
+}
+
+/// Called on the thread of the module which tried to allocate, inside of its global allocator,
+/// so it must not call the module. Returns `true` if allocation should be allowed.
+pub type MemoryBudgetCallback = Arc<dyn Fn(&BudgetExceeded) -> bool + Send + Sync>;
+
+/// See [`MemoryBudget::policy`]. In any case module is marked as over budget,
+/// see [`Module::is_over_budget`].
+#[derive(Clone)]
+pub enum MemoryBudgetPolicy {
+  /// Global allocator of the module returns null.
+  ///
+  /// **note:** standard library aborts the whole process on failed infallible allocation
+  /// (for example, `Vec::push`), so it's only useful if module uses fallible APIs (for example, `Vec::try_reserve`)
+  Fail,
+  /// Allocation succeeds, host can check [`Module::is_over_budget`] later (for example, to unload the module)
+  Allow,
+  /// Callback decides if allocation should be allowed (if callback panics allocation fails)
+  Callback(MemoryBudgetCallback),
+}
+
+impl Debug for MemoryBudgetPolicy {
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    match self {
+      Self::Fail => write!(f, "Fail"),
+      Self::Allow => write!(f, "Allow"),
+      Self::Callback(_) => write!(f, "Callback(..)"),
+    }
+  }
+}
+
+/// Passed to [`MemoryBudgetCallback`].
+#[derive(Debug, Clone)]
+pub struct BudgetExceeded {
+  pub module: ModuleId,
+  /// Size of allocation which would exceed the budget
+  pub requested_bytes: usize,
+  pub live_bytes: u64,
+  pub limit_bytes: u64,
+}
+
+struct Callback {
+  limit_bytes: u64,
+  callback: MemoryBudgetCallback,
+}
+
+static CALLBACKS: LazyLock<Mutex<HashMap<ModuleId, Callback>>> = LazyLock::new(Default::default);
+
+fn lock_callbacks() -> MutexGuard<'static, HashMap<ModuleId, Callback>> {
+  let Ok(callbacks) = CALLBACKS.lock() else {
+    unrecoverable("failed to lock memory budget CALLBACKS");
+  };
+
+  callbacks
+}
+
+pub fn set(module: ModuleId, exports: &InternalModuleExports, budget: Option<MemoryBudget>) {
+  if budget.is_some() && !unsafe { exports.alloc_tracker_is_used() } {
+    eprintln!(
+      "[relib] warning: memory budget of the module with id {module} is ignored \
+      since its global allocator is relib_module::ArenaAlloc or relib_module::HostAlloc\n\
+      note: memory budget is only supported by relib_module::AllocTracker"
+    );
+    return;
+  }
+
+  let Some(MemoryBudget {
+    limit_bytes,
+    policy,
+  }) = budget
+  else {
+    lock_callbacks().remove(&module);
+    unsafe {
+      exports.set_memory_budget(u64::MAX, BudgetPolicy::Allow);
+    }
+    return;
+  };
+
+  let policy = match policy {
+    MemoryBudgetPolicy::Fail => BudgetPolicy::Fail,
+    MemoryBudgetPolicy::Allow => BudgetPolicy::Allow,
+    MemoryBudgetPolicy::Callback(callback) => {
+      lock_callbacks().insert(
+        module,
+        Callback {
+          limit_bytes,
+          callback,
+        },
+      );
+      BudgetPolicy::Callback
+    }
+  };
+  if policy != BudgetPolicy::Callback {
+    lock_callbacks().remove(&module);
+  }
+
+  unsafe {
+    exports.set_memory_budget(limit_bytes, policy);
+  }
+}
+
+pub fn remove_module(module: ModuleId) {
+  lock_callbacks().remove(&module);
+}
+
+pub fn on_exceeded(module: ModuleId, requested_bytes: usize, live_bytes: u64) -> bool {
+  let callback = {
+    let callbacks = lock_callbacks();
+    let Some(Callback {
+      limit_bytes,
+      callback,
+    }) = callbacks.get(&module)
+    else {
+      // budget was changed concurrently
+      return true;
+    };
+    (*limit_bytes, callback.clone())
+  };
+  let (limit_bytes, callback) = callback;
+
+  let exceeded = BudgetExceeded {
+    module,
+    requested_bytes,
+    live_bytes,
+    limit_bytes,
+  };
+  catch_unwind(AssertUnwindSafe(|| callback(&exceeded))).unwrap_or(false)
+}
+
+impl<E: ModuleExportsForHost> Module<E> {
+  /// Sets (or removes if `None`) memory budget of the module at runtime,
+  /// also resets [`Module::is_over_budget`] flag. See also [`LoadOptions::memory_budget`](crate::LoadOptions::memory_budget).
+  pub fn set_memory_budget(&self, budget: Option<MemoryBudget>) {
+    set(self.id, &self.internal_exports, budget);
+  }
+
+  /// Returns `true` if some allocation exceeded (or would exceed) memory budget of the module
+  /// since it was set.
+  pub fn is_over_budget(&self) -> bool {
+    unsafe { self.internal_exports.is_over_budget() }
+  }
+}
@@ -0,0 +1,86 @@
+use {
+  crate::{Module, ModuleExportsForHost},
+  relib_internal_shared::{AllocStats, alloc_size_class_max},
+  std::sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Statistics of global allocator of the module, see [`Module::memory_stats`].
+#[derive(Debug, Clone)]
+pub struct MemoryStats {
+  /// Allocations which are not deallocated yet
+  pub live_allocations: u64,
+  /// Sum of sizes of live allocations
+  pub live_bytes: u64,
+  /// Maximum of `live_bytes` since module was loaded
+  pub peak_bytes: u64,
+  /// All allocations since module was loaded
+  pub total_allocations: u64,
+  /// All deallocations since module was loaded
+  pub total_deallocations: u64,
+  /// Live allocations by size class, sorted by size
+  pub size_classes: Vec<SizeClass>,
+}
+
+/// See [`MemoryStats::size_classes`].
+#[derive(Debug, Clone, Copy)]
+pub struct SizeClass {
+  /// Allocations bigger than maximum size of previous class and up to this one (inclusive),
+  /// `None` for the last class (everything bigger)
+  pub max_size: Option<usize>,
+  pub live_allocations: u64,
+}
+
+impl From<AllocStats> for MemoryStats {
+  fn from(stats: AllocStats) -> Self {
+    let AllocStats {
+      live_allocations,
+      live_bytes,
+      peak_bytes,
+      total_allocations,
+      total_deallocations,
+      live_by_size_class,
+    } = stats;
+
+    Self {
+      live_allocations,
+      live_bytes,
+      peak_bytes,
+      total_allocations,
+      total_deallocations,
+      size_classes: live_by_size_class
+        .into_iter()
+        .enumerate()
+        .map(|(class, live_allocations)| SizeClass {
+          max_size: alloc_size_class_max(class),
+          live_allocations,
+        })
+        .collect(),
+    }
+  }
+}
+
+impl<E: ModuleExportsForHost> Module<E> {
+  /// Returns statistics of global allocator of this module, can be called at any time.
+  ///
+  /// Allocations are counted by module alloc tracker (see
+  /// [docs](https://docs.rs/relib/latest/relib/docs/index.html#module-alloc-tracker)),
+  /// so if module doesn't have it everything is zero
+  /// (warning is printed if module uses `ArenaAlloc` or `HostAlloc`).
+  pub fn memory_stats(&self) -> MemoryStats {
+    static WARNED: AtomicBool = AtomicBool::new(false);
+
+    let alloc_tracker_is_used = unsafe { self.internal_exports.alloc_tracker_is_used() };
+    if !alloc_tracker_is_used && !WARNED.swap(true, Ordering::Relaxed) {
+      eprintln!(
+        "[relib] warning: memory stats of the module with id {} are always zero \
+        since its global allocator is relib_module::ArenaAlloc or relib_module::HostAlloc\n\
+        note: memory stats are only collected by relib_module::AllocTracker \
+        (this warning is printed once)",
+        self.id
+      );
+    }
+
+    let stats = unsafe { self.internal_exports.alloc_stats() };
+    stats.into()
+  }
+}
@@ -1,42 +1,115 @@
 use {
-  super::errors::UnloadError,
+  super::{errors::UnloadError, helpers::wait_for, options::UnloadOptions},
   crate::{
     Module, ModuleExportsForHost,
-    helpers::{call_module_pub_export, is_library_loaded},
+    helpers::{get_module_pub_export, is_library_loaded},
+    watchdog,
   },
 };
 
 impl<E: ModuleExportsForHost> Module<E> {
   /// Unloads module, if it fails, module may be leaked and never be unloaded.
-  pub fn unload(
+  pub fn unload(self) -> Result<(), UnloadError> {
+    self.unload_with(UnloadOptions::default())
+  }
+
+  /// Returns how many `ModuleBox`es created by the module are still alive.
+  ///
+  /// Module can't be unloaded while they are alive after `before_unload` callback ([`UnloadError::ModuleBoxesAlive`])
+  /// and failed unloading leaks the module, so drop the boxes and check it before calling [`Module::unload`]
+  /// (boxes kept by the module itself can be dropped in `before_unload`).
+  pub fn module_boxes_count(&self) -> u64 {
+    unsafe { self.internal_exports.module_boxes_count() }
+  }
+
+  /// Same as [`Module::unload`] but with options, see [`UnloadOptions`].
+  pub fn unload_with(
     #[allow(unused_mut)] // only used on windows
     mut self,
+    options: UnloadOptions,
   ) -> Result<(), UnloadError> {
     let library = self.library();
     let library_path = self.library_path.to_string_lossy().into_owned();
+    let module_id = self.id;
+    let caller = self.caller;
 
-    // calling before_unload callback
+    if self.is_hung() {
+      return Err(UnloadError::ModuleHung(library_path));
+    }
+
+    // notifying module threads which observe shutdown token,
+    // they will be waited for in running threads check
 
     unsafe {
-      let result = call_module_pub_export(library, "before_unload");
-      match result {
-        Ok(Some(())) => {}
-        Err(_) => {
-          // couldn't get it? it doesn't matter, moving on
-        }
-        Ok(None) => {
-          return Err(UnloadError::BeforeUnloadPanicked(library_path));
+      self.internal_exports.cancel_shutdown_token();
+    }
+
+    // calling before_unload callback
+
+    // couldn't get it? it doesn't matter, moving on
+    if let Ok(before_unload) = unsafe { get_module_pub_export::<()>(library, "before_unload") } {
+      let result = match options.before_unload_timeout {
+        Some(timeout) => {
+          // SAFETY: if it times out module is leaked so it will stay loaded
+          let result = watchdog::call_with_timeout("before_unload", timeout, move || unsafe {
+            before_unload.call()
+          });
+          match result {
+            Ok(result) => result,
+            Err(hung) => {
+              return Err(UnloadError::BeforeUnloadTimedOut(
+                library_path,
+                Box::new(hung),
+              ));
+            }
+          }
         }
+        None => unsafe { before_unload.call() },
+      };
+
+      if result.is_none() {
+        return Err(UnloadError::BeforeUnloadPanicked(library_path));
       }
     }
 
+    // values and vtables of the boxes are in the memory of the module
+    // (checked after before_unload so module can drop boxes it keeps itself)
+
+    let module_boxes = self.module_boxes_count();
+    if module_boxes != 0 {
+      return Err(UnloadError::ModuleBoxesAlive(library_path, module_boxes));
+    }
+
     // running threads check
 
     #[cfg(target_os = "linux")]
     {
-      let spawned_threads = unsafe { self.internal_exports.spawned_threads_count() };
-      if spawned_threads > 0 {
-        return Err(UnloadError::ThreadsStillRunning(library_path));
+      use super::options::UntrackedThreadsPolicy;
+
+      let threads_finished = wait_for(options.wait_for_threads, || unsafe {
+        self.internal_exports.spawned_threads_count() == 0
+      });
+      if !threads_finished {
+        let threads = super::module_threads::running_threads(&self.internal_exports);
+        return Err(UnloadError::ThreadsStillRunning(library_path, threads));
+      }
+
+      if options.untracked_threads != UntrackedThreadsPolicy::Ignore {
+        let threads = super::module_threads::untracked_threads(&self.internal_exports);
+        if !threads.is_empty() {
+          if options.untracked_threads == UntrackedThreadsPolicy::Refuse {
+            return Err(UnloadError::UntrackedThreadsRunning(library_path, threads));
+          }
+
+          // low confidence evidence is labelled in display of the thread
+          eprintln!(
+            "[relib] warning: module code may still be running on threads which weren't spawned by it, \
+            unloading anyway\n\
+            module path: {library_path}\n\
+            threads: {}",
+            super::module_threads::display_threads(&threads)
+          );
+        }
       }
 
       unsafe {
@@ -48,9 +121,20 @@ impl<E: ModuleExportsForHost> Module<E> {
 
     #[cfg(target_os = "windows")]
     {
-      let res = super::windows_thread_spawn_hook::remove_module(self.library_handle);
+      use super::windows_thread_spawn_hook;
+
+      let threads_finished = wait_for(options.wait_for_threads, || {
+        windows_thread_spawn_hook::module_threads_count(self.library_handle) == 0
+      });
+      let threads = if threads_finished {
+        Vec::new()
+      } else {
+        super::module_threads::running_threads(self.library_handle)
+      };
+
+      let res = windows_thread_spawn_hook::remove_module(self.library_handle);
       if res.is_err() {
-        return Err(UnloadError::ThreadsStillRunning(library_path));
+        return Err(UnloadError::ThreadsStillRunning(library_path, threads));
       }
     }
 
@@ -58,6 +142,10 @@ impl<E: ModuleExportsForHost> Module<E> {
     // (removing happens later on windows because thread-local destructors
     // are called by standard library in `library.close()`)
 
+    #[cfg(target_os = "linux")]
+    let foreign_tls_dtor_threads =
+      super::diagnosis::foreign_tls_dtor_threads(&self.internal_exports);
+
     #[cfg(target_os = "linux")]
     super::module_allocs::remove_module(
       self.id,
@@ -66,6 +154,11 @@ impl<E: ModuleExportsForHost> Module<E> {
       self.alloc_tracker_enabled,
     );
 
+    #[cfg(target_os = "linux")]
+    let guarded_ranges = options
+      .guard_unloaded_memory
+      .then(|| super::guard_pages::module_ranges(&library_path));
+
     #[cfg(target_os = "linux")]
     self.library.take().close()?;
 
@@ -88,11 +181,28 @@ impl<E: ModuleExportsForHost> Module<E> {
       );
     }
 
+    super::memory_budget::remove_module(module_id);
+
     // final unload check
 
     let still_loaded = is_library_loaded(&library_path);
     if still_loaded {
-      return Err(UnloadError::UnloadingFail(library_path));
+      #[cfg(target_os = "linux")]
+      let diagnosis = super::diagnosis::diagnose(&library_path, foreign_tls_dtor_threads);
+      #[cfg(target_os = "windows")]
+      let diagnosis = super::UnloadDiagnosis::default();
+
+      return Err(UnloadError::UnloadingFail(library_path, diagnosis));
+    }
+
+    #[cfg(target_os = "linux")]
+    if let Some(ranges) = guarded_ranges {
+      super::guard_pages::guard(&library_path, ranges);
+    }
+
+    // SAFETY: module is unloaded so it can't call imports anymore
+    unsafe {
+      crate::caller::free(caller);
     }
 
     Ok(())
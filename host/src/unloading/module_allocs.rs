@@ -1,20 +1,60 @@
 use {
-  super::{InternalModuleExports, helpers::unrecoverable},
+  super::{InternalModuleExports, helpers::unrecoverable, module_arena, module_host_alloc},
   relib_internal_shared::{
-    Allocation, AllocatorOp, AllocatorPtr, ModuleId, SliceAllocatorOp, StableLayout,
+    ALLOC_SHARDS, Allocation, AllocatorOp, AllocatorPtr, ModuleId, PtrBuildHasher,
+    SliceAllocatorOp, StableLayout,
   },
   std::{
     collections::HashMap,
-    sync::{LazyLock, Mutex, MutexGuard},
+    sync::{LazyLock, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard},
   },
 };
 
-type Allocs = HashMap<ModuleId, HashMap<AllocatorPtr, Allocation>>;
+type ShardAllocs = HashMap<AllocatorPtr, Allocation, PtrBuildHasher>;
 
-static ALLOCS: LazyLock<Mutex<Allocs>> = LazyLock::new(Default::default);
+/// Allocations of the module split into shards the same way as in the module cache
+/// (see `ALLOC_SHARDS`), so modules and threads flushing different shards don't wait for each other
+struct ModuleAllocs {
+  shards: [Mutex<ShardAllocs>; ALLOC_SHARDS],
+}
+
+impl ModuleAllocs {
+  fn new() -> Self {
+    Self {
+      shards: std::array::from_fn(|_| Default::default()),
+    }
+  }
+
+  fn lock_shard(&self, idx: usize) -> MutexGuard<'_, ShardAllocs> {
+    let Ok(shard) = self.shards[idx].lock() else {
+      unrecoverable("failed to lock shard of module allocs");
+    };
+
+    shard
+  }
+
+  fn len(&self) -> usize {
+    (0..ALLOC_SHARDS)
+      .map(|idx| self.lock_shard(idx).len())
+      .sum()
+  }
+}
+
+type Allocs = HashMap<ModuleId, ModuleAllocs>;
+
+/// Write lock is only taken when module is loaded or unloaded
+static ALLOCS: LazyLock<RwLock<Allocs>> = LazyLock::new(Default::default);
+
+fn read_allocs() -> RwLockReadGuard<'static, Allocs> {
+  let Ok(allocs) = ALLOCS.read() else {
+    unrecoverable("failed to lock ALLOCS");
+  };
+
+  allocs
+}
 
-fn lock_allocs() -> MutexGuard<'static, Allocs> {
-  let Ok(allocs) = ALLOCS.lock() else {
+fn write_allocs() -> RwLockWriteGuard<'static, Allocs> {
+  let Ok(allocs) = ALLOCS.write() else {
     unrecoverable("failed to lock ALLOCS");
   };
 
@@ -22,8 +62,8 @@ fn lock_allocs() -> MutexGuard<'static, Allocs> {
 }
 
 pub fn add_module(module_id: ModuleId) {
-  let mut allocs = lock_allocs();
-  allocs.insert(module_id, Default::default());
+  let mut allocs = write_allocs();
+  allocs.insert(module_id, ModuleAllocs::new());
 }
 
 pub fn remove_module(
@@ -36,14 +76,20 @@ pub fn remove_module(
     internal_exports.take_cached_allocs_before_exit();
   }
 
-  let mut allocs = lock_allocs();
+  let mut allocs = write_allocs();
   let Some(allocs) = allocs.remove(&module_id) else {
     panic!("Failed to take allocs of module with id: {module_id}");
   };
 
   // this check relies on two allocations in alloc tracker of the module,
   // which needed to cache allocation ops
-  if alloc_tracker_enabled && allocs.is_empty() {
+  // (arena allocator doesn't send allocations to host but maps regions,
+  // host allocator allocates them directly in the host)
+  if alloc_tracker_enabled
+    && allocs.len() == 0
+    && !module_arena::has_regions(module_id)
+    && !module_host_alloc::has_allocations(module_id)
+  {
     eprintln!(
       "[relib] warning: seems like this module doesn't have a registered global alloc tracker\n\
       module path: {}\n\
@@ -55,51 +101,86 @@ pub fn remove_module(
     );
   }
 
-  let allocs: Box<[Allocation]> = allocs.into_values().collect();
+  let allocs: Box<[Allocation]> = allocs
+    .shards
+    .into_iter()
+    .flat_map(|shard| {
+      let Ok(shard) = shard.into_inner() else {
+        unrecoverable("shard of module allocs is poisoned");
+      };
+      shard.into_values()
+    })
+    .collect();
   let allocs: &[Allocation] = &allocs;
 
   unsafe {
     internal_exports.exit(allocs.into());
   }
+
+  module_arena::remove_module(module_id);
+  module_host_alloc::remove_module(module_id);
 }
 
 pub fn on_cached_allocs(module_id: ModuleId, ops: SliceAllocatorOp) {
   let ops = unsafe { ops.into_slice() };
 
-  let mut allocs = lock_allocs();
+  let allocs = read_allocs();
   let allocs = allocs
-    .get_mut(&module_id)
+    .get(&module_id)
     .unwrap_or_else(|| unrecoverable("on_cached_allocs unreachable"));
 
+  // module sends ops of one shard at a time, so usually it's locked only once
+  let mut locked_shard: Option<(usize, MutexGuard<'_, ShardAllocs>)> = None;
+
   for op in ops {
+    let (AllocatorOp::Alloc(Allocation(ptr, ..)) | AllocatorOp::Dealloc(Allocation(ptr, ..))) = op;
+
+    let idx = ptr.shard();
+    let shard = match &mut locked_shard {
+      Some((locked_idx, shard)) if *locked_idx == idx => shard,
+      _ => {
+        // previous shard must be unlocked before locking the next one
+        locked_shard = None;
+        &mut locked_shard.insert((idx, allocs.lock_shard(idx))).1
+      }
+    };
+
     match op {
       AllocatorOp::Alloc(allocation) => {
-        let Allocation(ptr, ..) = allocation;
-        allocs.insert(*ptr, allocation.clone());
+        shard.insert(*ptr, allocation.clone());
       }
-      AllocatorOp::Dealloc(Allocation(ptr, ..)) => {
+      AllocatorOp::Dealloc(..) => {
         // doesnt matter if allocs didnt have it
-        let _ = allocs.remove(ptr);
+        let _ = shard.remove(ptr);
       }
     }
   }
 }
 
 pub fn on_alloc(module_id: ModuleId, ptr: *mut u8, layout: StableLayout) {
-  let mut allocs = lock_allocs();
+  let allocs = read_allocs();
   let allocs = allocs
-    .get_mut(&module_id)
+    .get(&module_id)
     .unwrap_or_else(|| unrecoverable("on_alloc unreachable"));
 
   let ptr = AllocatorPtr(ptr);
-  allocs.insert(ptr, Allocation(ptr, layout));
+  allocs
+    .lock_shard(ptr.shard())
+    .insert(ptr, Allocation(ptr, layout));
+}
+
+/// Number of allocations of all loaded modules tracked by host
+pub fn tracked_allocations_count() -> usize {
+  let allocs = read_allocs();
+  allocs.values().map(ModuleAllocs::len).sum()
 }
 
 pub fn is_ptr_allocated(module_id: ModuleId, ptr: *mut u8) -> bool {
-  let allocs = lock_allocs();
+  let allocs = read_allocs();
   let allocs = allocs
     .get(&module_id)
     .unwrap_or_else(|| unrecoverable("is_ptr_allocated unreachable"));
 
-  allocs.contains_key(&AllocatorPtr(ptr))
+  let ptr = AllocatorPtr(ptr);
+  allocs.lock_shard(ptr.shard()).contains_key(&ptr)
 }
@@ -0,0 +1,146 @@
+use {
+  super::helpers::unrecoverable,
+  relib_internal_shared::{AllocatorPtr, ModuleId},
+  std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex, MutexGuard},
+  },
+};
+
+/// Memory regions mapped for arena allocator of the module
+/// (see "global_arena_alloc" feature of relib_module), key is the pointer returned to the module
+type Regions = HashMap<ModuleId, HashMap<AllocatorPtr, Region>>;
+
+struct Region {
+  base: AllocatorPtr,
+  len: usize,
+}
+
+static REGIONS: LazyLock<Mutex<Regions>> = LazyLock::new(Default::default);
+
+/// Alignment of the pointers returned by mmap and VirtualAlloc (at least)
+const PAGE_SIZE: usize = 4096;
+
+fn lock_regions() -> MutexGuard<'static, Regions> {
+  let Ok(regions) = REGIONS.lock() else {
+    unrecoverable("failed to lock arena REGIONS");
+  };
+
+  regions
+}
+
+/// Returns null if memory couldn't be mapped
+pub fn map(module_id: ModuleId, size: usize, align: usize) -> *mut u8 {
+  let padding = if align > PAGE_SIZE { align } else { 0 };
+  let Some(len) = size.checked_add(padding) else {
+    return std::ptr::null_mut();
+  };
+
+  let base = unsafe { platform::map(len) };
+  if base.is_null() {
+    return base;
+  }
+
+  let ptr = base.wrapping_add(base.align_offset(align));
+
+  let mut regions = lock_regions();
+  regions.entry(module_id).or_default().insert(
+    AllocatorPtr(ptr),
+    Region {
+      base: AllocatorPtr(base),
+      len,
+    },
+  );
+
+  ptr
+}
+
+pub fn unmap(module_id: ModuleId, ptr: *mut u8) {
+  let region = {
+    let mut regions = lock_regions();
+    regions
+      .get_mut(&module_id)
+      .and_then(|regions| regions.remove(&AllocatorPtr(ptr)))
+  };
+  let Some(Region { base, len }) = region else {
+    unrecoverable("arena region passed to unmap was not mapped");
+  };
+
+  unsafe {
+    platform::unmap(base.0, len);
+  }
+}
+
+/// Unmaps all regions of the module, it's the whole "deallocation" of arena allocator on unload
+pub fn remove_module(module_id: ModuleId) {
+  let regions = lock_regions().remove(&module_id);
+  for Region { base, len } in regions.into_iter().flat_map(HashMap::into_values) {
+    unsafe {
+      platform::unmap(base.0, len);
+    }
+  }
+}
+
+pub fn has_regions(module_id: ModuleId) -> bool {
+  lock_regions()
+    .get(&module_id)
+    .is_some_and(|regions| !regions.is_empty())
+}
+
+/// Number of mapped regions of all loaded modules
+pub fn mapped_regions_count() -> usize {
+  lock_regions().values().map(HashMap::len).sum()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+  pub unsafe fn map(len: usize) -> *mut u8 {
+    let ptr = unsafe {
+      libc::mmap(
+        std::ptr::null_mut(),
+        len,
+        libc::PROT_READ | libc::PROT_WRITE,
+        libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+        -1,
+        0,
+      )
+    };
+    if ptr == libc::MAP_FAILED {
+      std::ptr::null_mut()
+    } else {
+      ptr.cast()
+    }
+  }
+
+  pub unsafe fn unmap(base: *mut u8, len: usize) {
+    unsafe {
+      libc::munmap(base.cast(), len);
+    }
+  }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+  use crate::windows::imports::{
+    MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_READWRITE, VirtualAlloc, VirtualFree,
+  };
+
+  pub unsafe fn map(len: usize) -> *mut u8 {
+    unsafe {
+      VirtualAlloc(
+        std::ptr::null(),
+        len,
+        MEM_COMMIT | MEM_RESERVE,
+        PAGE_READWRITE,
+      )
+      .cast()
+    }
+  }
+
+  pub unsafe fn unmap(base: *mut u8, _len: usize) {
+    // length must be zero with MEM_RELEASE
+    unsafe {
+      VirtualFree(base.cast(), 0, MEM_RELEASE);
+    }
+  }
+}
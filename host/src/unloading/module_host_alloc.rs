@@ -0,0 +1,108 @@
+use {
+  super::helpers::unrecoverable,
+  relib_internal_shared::{ALLOC_SHARDS, AllocatorPtr, ModuleId, PtrBuildHasher, StableLayout},
+  std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex, MutexGuard},
+  },
+};
+
+/// Memory allocated by global allocator of the host for modules which use it
+/// (see "global_host_alloc" feature of relib_module), key is the pointer since
+/// allocations can be moved between host and modules
+type Shard = HashMap<AllocatorPtr, (ModuleId, StableLayout), PtrBuildHasher>;
+
+static SHARDS: LazyLock<[Mutex<Shard>; ALLOC_SHARDS]> =
+  LazyLock::new(|| std::array::from_fn(|_| Default::default()));
+
+fn lock_shard(ptr: AllocatorPtr) -> MutexGuard<'static, Shard> {
+  let Ok(shard) = SHARDS[ptr.shard()].lock() else {
+    unrecoverable("failed to lock shard of host allocs");
+  };
+
+  shard
+}
+
+/// Returns null if memory couldn't be allocated
+pub fn alloc(module_id: ModuleId, layout: StableLayout) -> *mut u8 {
+  // SAFETY: module global allocator is never called with zero-sized layout
+  let ptr = unsafe { std::alloc::alloc(layout.into()) };
+  if !ptr.is_null() {
+    let ptr = AllocatorPtr(ptr);
+    lock_shard(ptr).insert(ptr, (module_id, layout));
+  }
+
+  ptr
+}
+
+/// Pointer may also be allocated by the host and moved to the module
+pub fn dealloc(ptr: *mut u8, layout: StableLayout) {
+  let ptr = AllocatorPtr(ptr);
+  lock_shard(ptr).remove(&ptr);
+
+  unsafe {
+    std::alloc::dealloc(ptr.0, layout.into());
+  }
+}
+
+pub fn adopt(module_id: ModuleId, ptr: *mut u8, layout: StableLayout) {
+  let ptr = AllocatorPtr(ptr);
+  let previous = lock_shard(ptr).insert(ptr, (module_id, layout));
+  if previous.is_some() {
+    unrecoverable("allocation moved to the module is already owned by a module");
+  }
+}
+
+pub fn release(module_id: ModuleId, ptr: *mut u8) {
+  let ptr = AllocatorPtr(ptr);
+  let owner = lock_shard(ptr).remove(&ptr).map(|(owner, _)| owner);
+  if owner != Some(module_id) {
+    unrecoverable("allocation moved from the module is not owned by it");
+  }
+}
+
+/// Deallocates all memory still owned by the module (leaks)
+pub fn remove_module(module_id: ModuleId) {
+  let mut leaked = Vec::new();
+  for shard in SHARDS.iter() {
+    let Ok(mut shard) = shard.lock() else {
+      unrecoverable("failed to lock shard of host allocs");
+    };
+
+    shard.retain(|ptr, (owner, layout)| {
+      if *owner != module_id {
+        return true;
+      }
+      leaked.push((*ptr, *layout));
+      false
+    });
+  }
+
+  for (ptr, layout) in leaked {
+    unsafe {
+      std::alloc::dealloc(ptr.0, layout.into());
+    }
+  }
+}
+
+pub fn has_allocations(module_id: ModuleId) -> bool {
+  SHARDS.iter().any(|shard| {
+    let Ok(shard) = shard.lock() else {
+      unrecoverable("failed to lock shard of host allocs");
+    };
+    shard.values().any(|(owner, _)| *owner == module_id)
+  })
+}
+
+/// Number of allocations owned by all loaded modules
+pub fn tracked_allocations_count() -> usize {
+  SHARDS
+    .iter()
+    .map(|shard| {
+      let Ok(shard) = shard.lock() else {
+        unrecoverable("failed to lock shard of host allocs");
+      };
+      shard.len()
+    })
+    .sum()
+}
@@ -0,0 +1,415 @@
+use {
+  crate::{Module, ModuleExportsForHost},
+  std::fmt::{Display, Formatter, Result as FmtResult},
+};
+
+/// Thread spawned by module which is still running.
+#[derive(Debug, Clone)]
+pub struct ModuleThread {
+  /// Id of the thread given by operating system
+  /// (`None` if thread was spawned but not started yet)
+  pub os_id: Option<u64>,
+  /// Name of the thread, for example set by `std::thread::Builder::name`
+  pub name: Option<String>,
+  /// Address of the function passed to thread spawn function
+  pub start_routine: usize,
+  /// Symbol of the start routine (or `<library>+<offset>` if the symbol is not exported),
+  /// if it's possible to resolve it
+  pub start_routine_symbol: Option<String>,
+  /// Backtrace of the place where thread was spawned, it's only captured if
+  /// `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` environment variables enable it
+  /// (see [`std::backtrace::Backtrace::capture`])
+  pub spawn_backtrace: Option<String>,
+}
+
+impl Display for ModuleThread {
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    let Self {
+      os_id,
+      name,
+      start_routine,
+      start_routine_symbol,
+      spawn_backtrace,
+    } = self;
+
+    match name {
+      Some(name) => write!(f, "\"{name}\"")?,
+      None => write!(f, "<unnamed>")?,
+    }
+    match os_id {
+      Some(os_id) => write!(f, " (os id: {os_id})")?,
+      None => write!(f, " (not started yet)")?,
+    }
+
+    write!(f, ", start routine: ")?;
+    match start_routine_symbol {
+      Some(symbol) => write!(f, "{symbol} ({start_routine:#x})")?,
+      None => write!(f, "{start_routine:#x}")?,
+    }
+
+    if let Some(spawn_backtrace) = spawn_backtrace {
+      write!(f, "\nspawned at:\n{spawn_backtrace}")?;
+    }
+
+    Ok(())
+  }
+}
+
+impl<E: ModuleExportsForHost> Module<E> {
+  /// Returns threads spawned by this module which are still running.
+  pub fn threads(&self) -> Vec<ModuleThread> {
+    #[cfg(target_os = "linux")]
+    {
+      running_threads(&self.internal_exports)
+    }
+    #[cfg(target_os = "windows")]
+    {
+      running_threads(self.library_handle)
+    }
+  }
+}
+
+/// Thread which runs code of the module but wasn't spawned by it
+/// or was spawned bypassing thread spawn hook (for example, using raw `clone` syscall),
+/// see [`UnloadOptions::untracked_threads`](crate::UnloadOptions::untracked_threads).
+#[derive(Debug, Clone)]
+pub struct UntrackedThread {
+  /// Id of the thread given by operating system
+  pub os_id: u64,
+  /// Name of the thread, if it's possible to get it
+  pub name: Option<String>,
+  /// Address inside of the module code which was found
+  /// (`None` if evidence is [`UntrackedThreadEvidence::Running`])
+  pub module_address: Option<usize>,
+  /// Symbol of the module address (or `<library>+<offset>` if the symbol is not exported)
+  pub module_address_symbol: Option<String>,
+  pub evidence: UntrackedThreadEvidence,
+}
+
+/// How [`UntrackedThread`] was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UntrackedThreadEvidence {
+  /// Thread is currently executing module code
+  InstructionPointer,
+  /// Stack of the thread contains return address into module code
+  /// (it's a heuristic, so it can be a false positive)
+  Stack,
+  /// Thread was running every time it was sampled, so it couldn't be inspected,
+  /// it may be executing module code (or any other code)
+  Running,
+}
+
+impl UntrackedThreadEvidence {
+  /// Returns `false` if thread may not actually run module code
+  /// ([`Stack`](Self::Stack) and [`Running`](Self::Running)).
+  pub fn is_certain(self) -> bool {
+    self == Self::InstructionPointer
+  }
+}
+
+impl Display for UntrackedThread {
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    let Self {
+      os_id,
+      name,
+      module_address,
+      module_address_symbol,
+      evidence,
+    } = self;
+
+    match name {
+      Some(name) => write!(f, "\"{name}\"")?,
+      None => write!(f, "<unnamed>")?,
+    }
+    write!(f, " (os id: {os_id}), ")?;
+
+    match evidence {
+      UntrackedThreadEvidence::InstructionPointer => write!(f, "executing ")?,
+      UntrackedThreadEvidence::Stack => write!(f, "(low confidence) has return address into ")?,
+      UntrackedThreadEvidence::Running => {
+        return write!(
+          f,
+          "(low confidence) was running every time it was sampled, may be executing module code"
+        );
+      }
+    }
+    match (module_address_symbol, module_address) {
+      (Some(symbol), Some(address)) => write!(f, "{symbol} ({address:#x})"),
+      (_, Some(address)) => write!(f, "{address:#x}"),
+      (_, None) => write!(f, "unknown address"),
+    }
+  }
+}
+
+pub(crate) fn display_threads(threads: &[impl Display]) -> String {
+  if threads.is_empty() {
+    return "unknown".to_owned();
+  }
+
+  threads
+    .iter()
+    .map(|thread| format!("\n- {thread}"))
+    .collect()
+}
+
+#[cfg(target_os = "linux")]
+mod linux_impl {
+  use {
+    super::{ModuleThread, UntrackedThread, UntrackedThreadEvidence},
+    crate::unloading::InternalModuleExports,
+    relib_internal_shared::{
+      ThreadInfo, UntrackedThread as RawUntrackedThread,
+      UntrackedThreadEvidence as RawUntrackedThreadEvidence,
+    },
+    std::{
+      ffi::{CStr, c_void},
+      fs,
+      mem::MaybeUninit,
+    },
+  };
+
+  struct Thread {
+    os_id: u64,
+    start_routine: usize,
+    name: String,
+    spawn_backtrace: String,
+  }
+
+  pub fn running_threads(exports: &InternalModuleExports) -> Vec<ModuleThread> {
+    let mut threads = Vec::<Thread>::new();
+
+    extern "C" fn visit(ctx: *mut c_void, thread: ThreadInfo) {
+      let threads = unsafe { &mut *(ctx as *mut Vec<Thread>) };
+      let ThreadInfo {
+        os_id,
+        start_routine,
+        name,
+        spawn_backtrace,
+      } = thread;
+
+      // SAFETY: strings are borrowed from the module until this callback returns
+      threads.push(Thread {
+        os_id,
+        start_routine,
+        name: unsafe { name.to_string() },
+        spawn_backtrace: unsafe { spawn_backtrace.to_string() },
+      });
+    }
+
+    unsafe {
+      exports.visit_spawned_threads(visit, &mut threads as *mut _ as *mut c_void);
+    }
+
+    threads
+      .into_iter()
+      .map(
+        |Thread {
+           os_id,
+           start_routine,
+           name,
+           spawn_backtrace,
+         }| {
+          let os_id = if os_id == 0 { None } else { Some(os_id) };
+          // thread can also be named without std (for example, with prctl)
+          let name = if name.is_empty() {
+            os_id.and_then(thread_name)
+          } else {
+            Some(name)
+          };
+          ModuleThread {
+            os_id,
+            name,
+            start_routine,
+            start_routine_symbol: symbol_name(start_routine),
+            spawn_backtrace: if spawn_backtrace.is_empty() {
+              None
+            } else {
+              Some(spawn_backtrace)
+            },
+          }
+        },
+      )
+      .collect()
+  }
+
+  pub fn untracked_threads(exports: &InternalModuleExports) -> Vec<UntrackedThread> {
+    let mut threads = Vec::<UntrackedThread>::new();
+
+    extern "C" fn visit(ctx: *mut c_void, thread: RawUntrackedThread) {
+      let threads = unsafe { &mut *(ctx as *mut Vec<UntrackedThread>) };
+      let RawUntrackedThread {
+        os_id,
+        module_address,
+        evidence,
+      } = thread;
+
+      let evidence = match evidence {
+        RawUntrackedThreadEvidence::InstructionPointer => {
+          UntrackedThreadEvidence::InstructionPointer
+        }
+        RawUntrackedThreadEvidence::Stack => UntrackedThreadEvidence::Stack,
+        RawUntrackedThreadEvidence::Running => UntrackedThreadEvidence::Running,
+      };
+      let module_address = match evidence {
+        UntrackedThreadEvidence::Running => None,
+        _ => Some(module_address),
+      };
+
+      threads.push(UntrackedThread {
+        os_id,
+        name: thread_name(os_id),
+        module_address,
+        module_address_symbol: module_address.and_then(symbol_name),
+        evidence,
+      });
+    }
+
+    unsafe {
+      exports.visit_untracked_threads(visit, &mut threads as *mut _ as *mut c_void);
+    }
+
+    threads
+  }
+
+  /// **note:** Linux truncates thread names to 15 bytes,
+  /// full names of threads spawned by the module are recorded by its thread spawn hook
+  fn thread_name(os_id: u64) -> Option<String> {
+    let name = fs::read_to_string(format!("/proc/self/task/{os_id}/comm")).ok()?;
+    Some(name.trim_end().to_owned())
+  }
+
+  fn symbol_name(addr: usize) -> Option<String> {
+    let mut info = MaybeUninit::<libc::Dl_info>::uninit();
+    let found = unsafe { libc::dladdr(addr as *const c_void, info.as_mut_ptr()) };
+    if found == 0 {
+      return None;
+    }
+
+    // SAFETY: dladdr returned non-zero so info is initialized
+    let info = unsafe { info.assume_init() };
+
+    if !info.dli_sname.is_null() {
+      let name = unsafe { CStr::from_ptr(info.dli_sname) };
+      return Some(name.to_string_lossy().into_owned());
+    }
+
+    // most of the functions are not exported from dynamic library
+    // (for example, thread start routine of std)
+    if info.dli_fname.is_null() {
+      return None;
+    }
+    let library = unsafe { CStr::from_ptr(info.dli_fname) };
+    let offset = addr - info.dli_fbase as usize;
+    Some(format!("{}+{offset:#x}", library.to_string_lossy()))
+  }
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) use linux_impl::{running_threads, untracked_threads};
+
+#[cfg(target_os = "windows")]
+mod windows_impl {
+  use {
+    super::ModuleThread,
+    crate::{
+      module::WindowsLibraryHandle,
+      unloading::windows_thread_spawn_hook::lock_module_threads,
+      windows::imports::{
+        CloseHandle, GetModuleFileNameW, GetThreadDescription, LocalFree, OpenThread, PWSTR,
+        THREAD_QUERY_LIMITED_INFORMATION,
+      },
+    },
+    std::{
+      backtrace::BacktraceStatus,
+      ffi::{OsString, c_void},
+      os::windows::ffi::OsStringExt,
+      path::Path,
+    },
+  };
+
+  pub fn running_threads(module_handle: WindowsLibraryHandle) -> Vec<ModuleThread> {
+    let module_threads = lock_module_threads();
+    let Some(threads) = module_threads.get(&module_handle) else {
+      return Vec::new();
+    };
+
+    let module_name = module_file_name(module_handle);
+
+    threads
+      .iter()
+      .map(|thread| {
+        let os_id = if thread.os_id == 0 {
+          None
+        } else {
+          Some(thread.os_id)
+        };
+
+        let spawn_backtrace = match thread.spawn_backtrace.status() {
+          BacktraceStatus::Captured => Some(thread.spawn_backtrace.to_string()),
+          _ => None,
+        };
+
+        // start routine may be outside of the module (for example, in the host or std of another dylib),
+        // in which case it's not resolved
+        let offset = thread.start_routine.checked_sub(module_handle as usize);
+
+        ModuleThread {
+          os_id,
+          name: os_id.and_then(thread_name),
+          start_routine: thread.start_routine,
+          start_routine_symbol: module_name
+            .as_ref()
+            .zip(offset)
+            .map(|(module_name, offset)| format!("{module_name}+{offset:#x}")),
+          spawn_backtrace,
+        }
+      })
+      .collect()
+  }
+
+  fn thread_name(os_id: u64) -> Option<String> {
+    unsafe {
+      let thread = OpenThread(THREAD_QUERY_LIMITED_INFORMATION, 0, os_id as u32);
+      if thread.is_null() {
+        return None;
+      }
+
+      let mut description: PWSTR = std::ptr::null_mut();
+      let result = GetThreadDescription(thread, &mut description);
+      CloseHandle(thread);
+
+      // HRESULT, negative values are errors
+      if result < 0 || description.is_null() {
+        return None;
+      }
+
+      let len = (0..).take_while(|&i| *description.add(i) != 0).count();
+      let name = OsString::from_wide(std::slice::from_raw_parts(description, len));
+      LocalFree(description as *mut c_void);
+
+      let name = name.to_string_lossy().into_owned();
+      if name.is_empty() { None } else { Some(name) }
+    }
+  }
+
+  fn module_file_name(module_handle: WindowsLibraryHandle) -> Option<String> {
+    let mut buf = [0_u16; 1024];
+    let len = unsafe {
+      GetModuleFileNameW(
+        module_handle as *const isize,
+        buf.as_mut_ptr(),
+        buf.len() as u32,
+      )
+    } as usize;
+    if len == 0 {
+      return None;
+    }
+
+    let path = OsString::from_wide(&buf[..len]);
+    let file_name = Path::new(&path).file_name()?;
+    Some(file_name.to_string_lossy().into_owned())
+  }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) use windows_impl::running_threads;
@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+/// Options of [`Module::unload_with`](crate::Module::unload_with).
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct UnloadOptions {
+  /// How long `before_unload` export may run, if it takes longer
+  /// [`UnloadError::BeforeUnloadTimedOut`](crate::UnloadError::BeforeUnloadTimedOut)
+  /// is returned and module is leaked.
+  ///
+  /// `None` (default) means no limit.
+  ///
+  /// **note:** with a timeout `before_unload` is called on a separate thread
+  /// (which is joined before unloading continues), not on the thread which calls `unload_with`,
+  /// so it sees different thread-locals of the host and the module.
+  pub before_unload_timeout: Option<Duration>,
+
+  /// How long to wait for threads spawned by module to finish before returning
+  /// [`UnloadError::ThreadsStillRunning`](crate::UnloadError::ThreadsStillRunning).
+  ///
+  /// Useful for detached threads which are already winding down
+  /// and for threads which observe `relib_module::shutdown_token()`
+  /// (it's cancelled before `before_unload` is called).
+  /// Zero means threads are checked only once.
+  /// Waiting stops as soon as all threads are finished, so successful unloading isn't slowed down.
+  ///
+  /// Default is 1 second.
+  pub wait_for_threads: Duration,
+
+  /// What to do if some thread runs module code but wasn't spawned by it
+  /// (or was spawned bypassing thread spawn hook, for example, using raw `clone` syscall),
+  /// see [`UntrackedThread`](crate::UntrackedThread).
+  ///
+  /// Only checked on Linux. Default is [`UntrackedThreadsPolicy::Warn`].
+  pub untracked_threads: UntrackedThreadsPolicy,
+
+  /// Debug mode: after unloading, address ranges where module was mapped are reserved
+  /// as inaccessible memory (`PROT_NONE` guard pages), so a call through a dangling function pointer,
+  /// vtable or `&'static` reference obtained from the module aborts the process with a clear message
+  /// instead of executing or reading whatever is mapped there later.
+  ///
+  /// Guarded ranges are never released, so don't use it for modules which are reloaded a lot of times.
+  ///
+  /// Only supported on Linux. Default is `false`.
+  pub guard_unloaded_memory: bool,
+}
+
+/// See [`UnloadOptions::untracked_threads`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UntrackedThreadsPolicy {
+  /// Don't check
+  Ignore,
+  /// Print a warning to stderr and continue unloading
+  /// (threads with low confidence evidence are labelled as such,
+  /// see [`UntrackedThreadEvidence::is_certain`](crate::UntrackedThreadEvidence::is_certain))
+  #[default]
+  Warn,
+  /// Return [`UnloadError::UntrackedThreadsRunning`](crate::UnloadError::UntrackedThreadsRunning)
+  /// if some thread may be running module code (including low confidence evidence)
+  Refuse,
+}
+
+impl Default for UnloadOptions {
+  fn default() -> Self {
+    Self {
+      before_unload_timeout: None,
+      wait_for_threads: Duration::from_secs(1),
+      untracked_threads: UntrackedThreadsPolicy::default(),
+      guard_unloaded_memory: false,
+    }
+  }
+}
+
+impl UnloadOptions {
+  pub fn before_unload_timeout(mut self, timeout: Duration) -> Self {
+    self.before_unload_timeout = Some(timeout);
+    self
+  }
+
+  pub fn wait_for_threads(mut self, timeout: Duration) -> Self {
+    self.wait_for_threads = timeout;
+    self
+  }
+
+  pub fn untracked_threads(mut self, policy: UntrackedThreadsPolicy) -> Self {
+    self.untracked_threads = policy;
+    self
+  }
+
+  pub fn guard_unloaded_memory(mut self, enable: bool) -> Self {
+    self.guard_unloaded_memory = enable;
+    self
+  }
+}
@@ -0,0 +1,346 @@
+use {
+  super::{UnloadError, UnloadOptions, module_allocs, module_arena, module_host_alloc},
+  crate::{InitImports, LoadError, Module, ModuleExportsForHost, load_module},
+  std::{
+    ffi::OsStr,
+    fmt::{Display, Formatter, Result as FmtResult},
+  },
+  thiserror::Error,
+};
+
+/// Options of [`soak`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SoakOptions {
+  /// How many times module is loaded, run and unloaded.
+  ///
+  /// Default is 10.
+  pub cycles: usize,
+
+  /// How many first cycles are not taken into account when looking for growth
+  /// (for example, first load initializes caches of dynamic linker and allocator of the host).
+  ///
+  /// Default is 1.
+  pub warmup_cycles: usize,
+
+  /// Options of every unload.
+  pub unload: UnloadOptions,
+}
+
+impl Default for SoakOptions {
+  fn default() -> Self {
+    Self {
+      cycles: 10,
+      warmup_cycles: 1,
+      unload: UnloadOptions::default(),
+    }
+  }
+}
+
+impl SoakOptions {
+  pub fn cycles(mut self, cycles: usize) -> Self {
+    self.cycles = cycles;
+    self
+  }
+
+  pub fn warmup_cycles(mut self, cycles: usize) -> Self {
+    self.warmup_cycles = cycles;
+    self
+  }
+
+  pub fn unload(mut self, options: UnloadOptions) -> Self {
+    self.unload = options;
+    self
+  }
+}
+
+/// Class of process resource which is measured after each cycle of [`soak`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoakResource {
+  /// Resident memory of the process in bytes (working set on Windows)
+  ResidentMemory,
+  /// Allocations of loaded modules tracked by host (including ones made by global allocator of the host)
+  /// and memory regions of arena allocators
+  /// (it's expected to return to the same value after every unload)
+  TrackedAllocations,
+  /// Memory mappings (lines of `/proc/self/maps`), Linux only
+  MemoryMappings,
+  /// Open file descriptors (entries of `/proc/self/fd`), Linux only
+  FileDescriptors,
+  /// Threads of the process (entries of `/proc/self/task`), Linux only
+  Threads,
+}
+
+impl Display for SoakResource {
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    let name = match self {
+      Self::ResidentMemory => "resident memory (bytes)",
+      Self::TrackedAllocations => "tracked allocations",
+      Self::MemoryMappings => "memory mappings",
+      Self::FileDescriptors => "file descriptors",
+      Self::Threads => "threads",
+    };
+    write!(f, "{name}")
+  }
+}
+
+/// Resources of the process measured after unloading, `None` if it's not available on current platform.
+#[derive(Debug, Clone)]
+pub struct SoakSample {
+  pub resident_bytes: Option<u64>,
+  pub tracked_allocations: Option<u64>,
+  pub memory_mappings: Option<u64>,
+  pub file_descriptors: Option<u64>,
+  pub threads: Option<u64>,
+}
+
+impl SoakSample {
+  pub fn get(&self, resource: SoakResource) -> Option<u64> {
+    match resource {
+      SoakResource::ResidentMemory => self.resident_bytes,
+      SoakResource::TrackedAllocations => self.tracked_allocations,
+      SoakResource::MemoryMappings => self.memory_mappings,
+      SoakResource::FileDescriptors => self.file_descriptors,
+      SoakResource::Threads => self.threads,
+    }
+  }
+
+  fn measure() -> Self {
+    Self {
+      resident_bytes: platform::resident_bytes(),
+      tracked_allocations: Some(
+        (module_allocs::tracked_allocations_count()
+          + module_arena::mapped_regions_count()
+          + module_host_alloc::tracked_allocations_count()) as u64,
+      ),
+      memory_mappings: platform::memory_mappings(),
+      file_descriptors: platform::file_descriptors(),
+      threads: platform::threads(),
+    }
+  }
+}
+
+/// Resource which grew after every cycle (excluding warmup ones).
+#[derive(Debug, Clone)]
+pub struct ResourceGrowth {
+  pub resource: SoakResource,
+  /// Value after the last warmup cycle
+  pub first: u64,
+  /// Value after the last cycle
+  pub last: u64,
+  /// Average growth per cycle
+  pub per_cycle: u64,
+}
+
+impl Display for ResourceGrowth {
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    let Self {
+      resource,
+      first,
+      last,
+      per_cycle,
+    } = self;
+    write!(
+      f,
+      "{resource} grew from {first} to {last} (+{per_cycle} per cycle)"
+    )
+  }
+}
+
+/// Result of [`soak`].
+#[derive(Debug, Clone)]
+pub struct SoakReport {
+  /// Sample before the first cycle
+  pub baseline: SoakSample,
+  /// Sample after each cycle
+  pub samples: Vec<SoakSample>,
+  /// Resources which grew monotonically
+  pub growth: Vec<ResourceGrowth>,
+}
+
+impl SoakReport {
+  /// Returns `true` if no resource grew monotonically
+  pub fn is_leak_free(&self) -> bool {
+    self.growth.is_empty()
+  }
+}
+
+impl Display for SoakReport {
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    write!(f, "{} cycles", self.samples.len())?;
+    if self.growth.is_empty() {
+      return write!(f, ", no monotonic growth");
+    }
+
+    for growth in &self.growth {
+      write!(f, "\n- {growth}")?;
+    }
+    Ok(())
+  }
+}
+
+#[derive(Error, Debug)]
+pub enum SoakError {
+  #[error("loading failed on cycle {cycle}: {source}")]
+  Load { cycle: usize, source: LoadError },
+
+  #[error("unloading failed on cycle {cycle}: {source}")]
+  Unload { cycle: usize, source: UnloadError },
+}
+
+const RESOURCES: [SoakResource; 5] = [
+  SoakResource::ResidentMemory,
+  SoakResource::TrackedAllocations,
+  SoakResource::MemoryMappings,
+  SoakResource::FileDescriptors,
+  SoakResource::Threads,
+];
+
+/// Loads module, calls `run` with it and unloads it [`SoakOptions::cycles`] times,
+/// measures resources of the process after each cycle and reports the ones which grew monotonically
+/// (each cycle leaks something). For example, it can be used in CI to prove that module can be reloaded without leaks.
+///
+/// `init_imports` is called before each load, see [`load_module`].
+///
+/// # Example
+/// ```no_run
+/// use {std::path::Path, libloading::library_filename, relib_host::SoakOptions};
+///
+/// let dylib_path = Path::new("target/debug").join(library_filename("module"));
+///
+/// let report = unsafe {
+///   relib_host::soak::<(), _>(dylib_path, SoakOptions::default().cycles(20), || (), |module| {
+///     module.call_main::<()>();
+///   })
+/// };
+/// let report = report.unwrap_or_else(|e| {
+///   panic!("soak failed: {e:#}");
+/// });
+/// assert!(report.is_leak_free(), "{report}");
+/// ```
+///
+/// # Safety
+/// Same as [`load_module`].
+pub unsafe fn soak<E: ModuleExportsForHost, I: InitImports>(
+  path: impl AsRef<OsStr>,
+  options: SoakOptions,
+  mut init_imports: impl FnMut() -> I,
+  mut run: impl FnMut(&Module<E>),
+) -> Result<SoakReport, SoakError> {
+  let path = path.as_ref();
+
+  let baseline = SoakSample::measure();
+  let mut samples = Vec::with_capacity(options.cycles);
+
+  for cycle in 1..=options.cycles {
+    let module = unsafe { load_module::<E>(path, init_imports()) };
+    let module = module.map_err(|source| SoakError::Load { cycle, source })?;
+
+    run(&module);
+
+    module
+      .unload_with(options.unload.clone())
+      .map_err(|source| SoakError::Unload { cycle, source })?;
+
+    samples.push(SoakSample::measure());
+  }
+
+  let growth = find_growth(&samples[options.warmup_cycles.min(samples.len())..]);
+
+  Ok(SoakReport {
+    baseline,
+    samples,
+    growth,
+  })
+}
+
+/// Resources which grew after every cycle, at least three samples are needed to tell growth from noise
+fn find_growth(samples: &[SoakSample]) -> Vec<ResourceGrowth> {
+  if samples.len() < 3 {
+    return Vec::new();
+  }
+
+  RESOURCES
+    .into_iter()
+    .filter_map(|resource| {
+      let values = samples
+        .iter()
+        .map(|sample| sample.get(resource))
+        .collect::<Option<Vec<u64>>>()?;
+
+      let grew_every_cycle = values.windows(2).all(|pair| pair[1] > pair[0]);
+      if !grew_every_cycle {
+        return None;
+      }
+
+      let first = *values.first()?;
+      let last = *values.last()?;
+      Some(ResourceGrowth {
+        resource,
+        first,
+        last,
+        per_cycle: (last - first) / (values.len() as u64 - 1),
+      })
+    })
+    .collect()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+  use std::fs;
+
+  pub fn resident_bytes() -> Option<u64> {
+    let statm = fs::read_to_string("/proc/self/statm").ok()?;
+    let resident_pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    Some(resident_pages * u64::try_from(page_size).ok()?)
+  }
+
+  pub fn memory_mappings() -> Option<u64> {
+    let maps = fs::read_to_string("/proc/self/maps").ok()?;
+    Some(maps.lines().count() as u64)
+  }
+
+  pub fn file_descriptors() -> Option<u64> {
+    // minus descriptor of the directory itself which is open while reading it
+    entries_count("/proc/self/fd").map(|count| count.saturating_sub(1))
+  }
+
+  pub fn threads() -> Option<u64> {
+    entries_count("/proc/self/task")
+  }
+
+  fn entries_count(path: &str) -> Option<u64> {
+    Some(fs::read_dir(path).ok()?.count() as u64)
+  }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+  use crate::windows::imports::{GetCurrentProcess, K32GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+
+  pub fn resident_bytes() -> Option<u64> {
+    let mut counters = PROCESS_MEMORY_COUNTERS {
+      cb: size_of::<PROCESS_MEMORY_COUNTERS>() as u32,
+      ..Default::default()
+    };
+    let result =
+      unsafe { K32GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, counters.cb) };
+    if result == 0 {
+      return None;
+    }
+    Some(counters.WorkingSetSize as u64)
+  }
+
+  pub fn memory_mappings() -> Option<u64> {
+    None
+  }
+
+  pub fn file_descriptors() -> Option<u64> {
+    None
+  }
+
+  pub fn threads() -> Option<u64> {
+    None
+  }
+}
@@ -4,14 +4,18 @@ use {
     module::WindowsLibraryHandle,
     windows::{
       get_dylib_handle_from_addr,
-      imports::{CreateThread, HANDLE},
+      imports::{CreateThread, GetCurrentThreadId, HANDLE},
     },
   },
   minhook::MinHook,
   std::{
+    backtrace::Backtrace,
     collections::HashMap,
     ffi::c_void,
-    sync::{LazyLock, Mutex, MutexGuard, Once},
+    sync::{
+      LazyLock, Mutex, MutexGuard, Once,
+      atomic::{AtomicU64, Ordering},
+    },
   },
 };
 
@@ -19,17 +23,29 @@ type Payload = (
   unsafe extern "system" fn(main: *mut c_void) -> u32,
   *mut c_void,
   WindowsLibraryHandle,
+  u64,
 );
 
 static mut ORIG: CreateThread = CreateThread;
 
 static INIT: Once = Once::new();
 
-type ModuleThreadsCount = HashMap<WindowsLibraryHandle, u64>;
+pub struct SpawnedThread {
+  key: u64,
+  /// 0 if thread is not started yet
+  pub os_id: u64,
+  pub start_routine: usize,
+  /// Captured only if it's enabled by `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE`
+  /// environment variables (see `std::backtrace::Backtrace::capture`)
+  pub spawn_backtrace: Backtrace,
+}
+
+type ModuleThreads = HashMap<WindowsLibraryHandle, Vec<SpawnedThread>>;
 
-static MODULE_THREADS: LazyLock<Mutex<ModuleThreadsCount>> = LazyLock::new(Default::default);
+static MODULE_THREADS: LazyLock<Mutex<ModuleThreads>> = LazyLock::new(Default::default);
+static NEXT_THREAD_KEY: AtomicU64 = AtomicU64::new(0);
 
-fn lock_module_threads() -> MutexGuard<'static, ModuleThreadsCount> {
+pub fn lock_module_threads() -> MutexGuard<'static, ModuleThreads> {
   let Ok(module_threads) = MODULE_THREADS.lock() else {
     unrecoverable("failed to lock MODULE_THREADS");
   };
@@ -39,7 +55,7 @@ fn lock_module_threads() -> MutexGuard<'static, ModuleThreadsCount> {
 
 pub fn add_module(module_handle: WindowsLibraryHandle) {
   let mut module_threads = lock_module_threads();
-  module_threads.insert(module_handle, 0);
+  module_threads.insert(module_handle, Vec::new());
 }
 
 pub fn remove_module(module_handle: WindowsLibraryHandle) -> Result<(), ()> {
@@ -48,7 +64,15 @@ pub fn remove_module(module_handle: WindowsLibraryHandle) -> Result<(), ()> {
     panic!("Failed to remove module_threads of module with handle: {module_handle}");
   };
 
-  if threads == 0 { Ok(()) } else { Err(()) }
+  if threads.is_empty() { Ok(()) } else { Err(()) }
+}
+
+pub fn module_threads_count(module_handle: WindowsLibraryHandle) -> u64 {
+  let module_threads = lock_module_threads();
+  module_threads
+    .get(&module_handle)
+    .map(|threads| threads.len() as u64)
+    .unwrap_or(0)
 }
 
 pub unsafe fn init() {
@@ -77,17 +101,26 @@ unsafe extern "system" fn hook(
   if let Some(module_handle) = module_handle {
     let module_handle = module_handle as WindowsLibraryHandle;
 
+    // thread is registered before it's started since it can finish
+    // even before original CreateThread returns
+    let key = NEXT_THREAD_KEY.fetch_add(1, Ordering::Relaxed);
+    let spawn_backtrace = Backtrace::capture();
     lock_module_threads()
       .entry(module_handle)
-      .and_modify(|count| {
-        *count += 1;
+      .and_modify(|threads| {
+        threads.push(SpawnedThread {
+          key,
+          os_id: 0,
+          start_routine: lpstartaddress as usize,
+          spawn_backtrace,
+        });
       });
 
-    let payload: Payload = (lpstartaddress, lpparameter, module_handle);
+    let payload: Payload = (lpstartaddress, lpparameter, module_handle, key);
     let payload = Box::new(payload);
     let payload = Box::into_raw(payload);
 
-    unsafe {
+    let handle = unsafe {
       ORIG(
         lpthreadattributes,
         dwstacksize,
@@ -96,8 +129,16 @@ unsafe extern "system" fn hook(
         dwcreationflags,
         lpthreadid,
       )
+    };
+
+    if handle.is_null() {
+      update_module_thread(module_handle, key, |threads, idx| {
+        threads.swap_remove(idx);
+      });
     }
 
+    handle
+
   // TODO: check if this branch is ever executed
   } else {
     unsafe {
@@ -117,17 +158,31 @@ const _TYPE_ASSERT: CreateThread = hook;
 
 unsafe extern "system" fn thread_start(payload: *mut c_void) -> u32 {
   // TODO: SAFETY
-  let (ret, module_handle) = unsafe {
-    let (f, value, module_handle) = *Box::from_raw(payload as *mut Payload);
+  let (f, value, module_handle, key) = unsafe { *Box::from_raw(payload as *mut Payload) };
 
-    (f(value), module_handle)
-  };
+  update_module_thread(module_handle, key, |threads, idx| {
+    threads[idx].os_id = unsafe { GetCurrentThreadId() } as u64;
+  });
 
-  lock_module_threads()
-    .entry(module_handle)
-    .and_modify(|count| {
-      *count -= 1;
-    });
+  let ret = unsafe { f(value) };
+
+  update_module_thread(module_handle, key, |threads, idx| {
+    threads.swap_remove(idx);
+  });
 
   ret
 }
+
+fn update_module_thread(
+  module_handle: WindowsLibraryHandle,
+  key: u64,
+  update: impl FnOnce(&mut Vec<SpawnedThread>, usize),
+) {
+  let mut module_threads = lock_module_threads();
+  let Some(threads) = module_threads.get_mut(&module_handle) else {
+    return;
+  };
+  if let Some(idx) = threads.iter().position(|thread| thread.key == key) {
+    update(threads, idx);
+  }
+}
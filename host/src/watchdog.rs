@@ -0,0 +1,157 @@
+use {
+  std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    sync::mpsc,
+    thread,
+    time::Duration,
+  },
+  thiserror::Error,
+};
+
+/// Returned when a module call didn't finish in time,
+/// the thread which executes it is still stuck in the module.
+#[derive(Error, Debug, Clone)]
+#[error(
+  "module call \"{call}\" did not finish in {timeout:?}\n\
+  stuck thread: {thread}"
+)]
+pub struct HungCall {
+  /// Name of the export
+  pub call: String,
+  pub timeout: Duration,
+  pub thread: StuckThread,
+}
+
+/// What is known about the thread which is stuck in the module.
+#[derive(Debug, Clone)]
+pub struct StuckThread {
+  /// Name of the thread (it's spawned by relib_host)
+  pub name: String,
+  /// Id of the thread given by operating system
+  pub os_id: Option<u64>,
+  /// Stack dump of the thread, if it's possible to get it
+  /// (on Linux it's read from `/proc/self/task/<tid>`, not available on Windows)
+  pub stack: Option<String>,
+}
+
+impl Display for StuckThread {
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    let Self { name, os_id, stack } = self;
+    write!(f, "\"{name}\"")?;
+
+    if let Some(os_id) = os_id {
+      write!(f, " (os id: {os_id})")?;
+    }
+
+    match stack {
+      Some(stack) => write!(f, "\nstack:\n{stack}"),
+      None => write!(f, "\nstack: unavailable"),
+    }
+  }
+}
+
+/// Runs `f` in a separate thread and waits for it no longer than `timeout`.
+/// If it takes longer, thread will be left running (there is no safe way to stop it)
+/// and `HungCall` will be returned.
+///
+/// If `f` finishes in time, thread is joined before returning, so when this function returns
+/// thread no longer runs anything (including thread-local destructors and unwinding out of module frames).
+pub fn call_with_timeout<R, F>(call: &str, timeout: Duration, f: F) -> Result<R, HungCall>
+where
+  R: Send + 'static,
+  F: FnOnce() -> R + Send + 'static,
+{
+  let thread_name = format!("relib watchdog: {call}");
+
+  let (os_id_sender, os_id_receiver) = mpsc::sync_channel::<Option<u64>>(1);
+  let (result_sender, result_receiver) = mpsc::sync_channel::<R>(1);
+
+  let handle = thread::Builder::new()
+    .name(thread_name.clone())
+    .spawn(move || {
+      let _ = os_id_sender.send(current_thread_os_id());
+
+      let result = f();
+
+      // host may have stopped waiting for it, so it's fine if receiver is gone
+      let _ = result_sender.send(result);
+    })
+    .unwrap_or_else(|e| {
+      panic!("Failed to spawn watchdog thread, reason: {e:#}");
+    });
+
+  // it's sent right after thread start so it should never take long
+  let os_id = os_id_receiver.recv().ok().flatten();
+
+  match result_receiver.recv_timeout(timeout) {
+    Ok(result) => {
+      // result is sent right before thread exits, so it shouldn't take long,
+      // sending can't panic so this can only fail if thread-local destructors panicked
+      // (which aborts the process anyway)
+      let _ = handle.join();
+      Ok(result)
+    }
+    Err(mpsc::RecvTimeoutError::Disconnected) => {
+      // thread can only drop the sender without sending when it panicked,
+      // relib exports catch panics, so it's a panic of the host code in `f`
+      panic!("watchdog thread of \"{call}\" panicked");
+    }
+    Err(mpsc::RecvTimeoutError::Timeout) => Err(HungCall {
+      call: call.to_owned(),
+      timeout,
+      thread: StuckThread {
+        name: thread_name,
+        os_id,
+        stack: os_id.and_then(stack_dump),
+      },
+    }),
+  }
+}
+
+#[cfg(target_os = "linux")]
+fn current_thread_os_id() -> Option<u64> {
+  let tid = unsafe { libc::gettid() };
+  Some(tid as u64)
+}
+
+#[cfg(target_os = "windows")]
+fn current_thread_os_id() -> Option<u64> {
+  use crate::windows::imports::GetCurrentThreadId;
+  Some(unsafe { GetCurrentThreadId() } as u64)
+}
+
+/// Kernel stack of the thread (usually requires root) and where it's waiting
+/// (`wchan` and current syscall with user-space stack and instruction pointers)
+#[cfg(target_os = "linux")]
+fn stack_dump(os_id: u64) -> Option<String> {
+  use std::fs;
+
+  let read = |file: &str| {
+    let content = fs::read_to_string(format!("/proc/self/task/{os_id}/{file}")).ok()?;
+    let content = content.trim();
+    if content.is_empty() {
+      None
+    } else {
+      Some(content.to_owned())
+    }
+  };
+
+  let mut dump = String::new();
+
+  if let Some(wchan) = read("wchan") {
+    dump += &format!("waiting in: {wchan}\n");
+  }
+  if let Some(syscall) = read("syscall") {
+    dump += &format!("syscall (number, args, sp, pc): {syscall}\n");
+  }
+  if let Some(stack) = read("stack") {
+    dump += &format!("kernel stack:\n{stack}\n");
+  }
+
+  if dump.is_empty() { None } else { Some(dump) }
+}
+
+#[cfg(target_os = "windows")]
+fn stack_dump(_os_id: u64) -> Option<String> {
+  None
+}
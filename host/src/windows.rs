@@ -32,6 +32,50 @@ pub mod imports {
 
   windows_targets::link!("kernel32.dll" "system" fn GetCurrentProcess() -> HANDLE);
   windows_targets::link!("kernel32.dll" "system" fn GetLastError() -> DWORD);
+  windows_targets::link!("kernel32.dll" "system" fn GetCurrentThreadId() -> DWORD);
+
+  #[cfg(feature = "unloading")]
+  pub const THREAD_QUERY_LIMITED_INFORMATION: u32 = 0x0800;
+  #[cfg(feature = "unloading")]
+  windows_targets::link!("kernel32.dll" "system" fn OpenThread(desired_access: u32, inherit_handle: BOOL, thread_id: u32) -> HANDLE);
+  #[cfg(feature = "unloading")]
+  windows_targets::link!("kernel32.dll" "system" fn GetThreadDescription(thread: HANDLE, description: *mut PWSTR) -> i32);
+  #[cfg(feature = "unloading")]
+  windows_targets::link!("kernel32.dll" "system" fn LocalFree(mem: *mut c_void) -> *mut c_void);
+  #[cfg(feature = "unloading")]
+  windows_targets::link!("kernel32.dll" "system" fn CloseHandle(object: HANDLE) -> BOOL);
+
+  #[cfg(feature = "unloading")]
+  #[repr(C)]
+  #[derive(Default)]
+  #[allow(non_snake_case)]
+  pub struct PROCESS_MEMORY_COUNTERS {
+    pub cb: u32,
+    pub PageFaultCount: u32,
+    pub PeakWorkingSetSize: usize,
+    pub WorkingSetSize: usize,
+    pub QuotaPeakPagedPoolUsage: usize,
+    pub QuotaPagedPoolUsage: usize,
+    pub QuotaPeakNonPagedPoolUsage: usize,
+    pub QuotaNonPagedPoolUsage: usize,
+    pub PagefileUsage: usize,
+    pub PeakPagefileUsage: usize,
+  }
+  #[cfg(feature = "unloading")]
+  windows_targets::link!("kernel32.dll" "system" fn K32GetProcessMemoryInfo(process: HANDLE, counters: *mut PROCESS_MEMORY_COUNTERS, cb: u32) -> BOOL);
+
+  #[cfg(feature = "unloading")]
+  pub const MEM_COMMIT: u32 = 0x1000;
+  #[cfg(feature = "unloading")]
+  pub const MEM_RESERVE: u32 = 0x2000;
+  #[cfg(feature = "unloading")]
+  pub const MEM_RELEASE: u32 = 0x8000;
+  #[cfg(feature = "unloading")]
+  pub const PAGE_READWRITE: u32 = 0x04;
+  #[cfg(feature = "unloading")]
+  windows_targets::link!("kernel32.dll" "system" fn VirtualAlloc(address: *const c_void, size: usize, allocation_type: u32, protect: u32) -> *mut c_void);
+  #[cfg(feature = "unloading")]
+  windows_targets::link!("kernel32.dll" "system" fn VirtualFree(address: *mut c_void, size: usize, free_type: u32) -> BOOL);
 
   #[cfg(feature = "unloading")]
   windows_targets::link!("kernel32.dll" "system" fn CreateThread(lpthreadattributes : *const c_void, dwstacksize : usize, lpstartaddress : unsafe extern "system" fn(main: *mut c_void) -> u32, lpparameter : *mut c_void, dwcreationflags : u32, lpthreadid : *mut u32) -> HANDLE);
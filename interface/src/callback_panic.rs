@@ -0,0 +1,19 @@
+use std::cell::Cell;
+
+thread_local! {
+  static CALLBACK_PANICKED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Used by generated closure thunk when the closure passed to the other side panics,
+/// so that generated import of the module can tell it apart from panic of the host
+/// (the host panics too when closure of the module panics)
+#[doc(hidden)]
+pub fn __set_callback_panicked() {
+  CALLBACK_PANICKED.set(true);
+}
+
+/// Used by generated imports of the module, resets the flag
+#[doc(hidden)]
+pub fn __take_callback_panicked() -> bool {
+  CALLBACK_PANICKED.replace(false)
+}
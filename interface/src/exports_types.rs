@@ -0,0 +1,147 @@
+// host side of the interface, it's here and not in relib_host so that code generated
+// by `#[relib_interface::exports]` and `#[relib_interface::imports]` doesn't require shared crate
+// to depend on relib_host (which would link it into modules too), relib_host enables "host" feature
+// which compiles it
+
+use std::{
+  cell::{Cell, RefCell},
+  collections::HashSet,
+  ffi::c_void,
+  ptr,
+};
+
+pub use libloading::Library;
+
+pub trait ModuleExportsForHost {
+  fn new(library: &Library) -> Self;
+}
+
+/// For cases when module doesn't export anything
+impl ModuleExportsForHost for () {
+  fn new(_library: &Library) {}
+}
+
+pub trait InitImports {
+  fn init(self, library: &Library);
+}
+
+impl<F> InitImports for F
+where
+  F: FnOnce(&Library),
+{
+  fn init(self, library: &Library) {
+    self(library)
+  }
+}
+
+/// For cases when module doesn't import anything
+impl InitImports for () {
+  fn init(self, _library: &Library) {}
+}
+
+#[derive(Clone, Copy)]
+struct Loading {
+  caller: *const c_void,
+  // null if all imports are granted
+  granted_imports: *const HashSet<String>,
+}
+
+/// Constant of the trait file which has different value in the module, see [`__check_consts`]
+#[doc(hidden)]
+#[derive(Clone, Copy)]
+pub struct __ConstMismatch {
+  pub trait_path: &'static str,
+  pub name: &'static str,
+}
+
+thread_local! {
+  static CONST_MISMATCH: Cell<Option<__ConstMismatch>> = const { Cell::new(None) };
+  static CHECKED_IMPORTS: RefCell<Vec<(&'static str, &'static str)>> = const { RefCell::new(Vec::new()) };
+  static CURRENT: Cell<*const c_void> = const { Cell::new(ptr::null()) };
+  static LOADING: Cell<Loading> = const {
+    Cell::new(Loading {
+      caller: ptr::null(),
+      granted_imports: ptr::null(),
+    })
+  };
+}
+
+/// Used by relib_host to make caller and its grants available in generated `init_imports`,
+/// also returns trait and function names of all imports which were checked by it
+#[doc(hidden)]
+pub fn __while_loading<R>(
+  caller: *const c_void,
+  granted_imports: Option<&HashSet<String>>,
+  f: impl FnOnce() -> R,
+) -> (R, Vec<(&'static str, &'static str)>) {
+  let previous = LOADING.replace(Loading {
+    caller,
+    granted_imports: granted_imports.map_or(ptr::null(), ptr::from_ref),
+  });
+  let _restore = Restore(&LOADING, previous);
+
+  CHECKED_IMPORTS.take();
+  let value = f();
+  (value, CHECKED_IMPORTS.take())
+}
+
+/// Used by relib_host to find out if generated code called [`__const_mismatch`] in `f`
+#[doc(hidden)]
+pub fn __check_consts<R>(f: impl FnOnce() -> R) -> (R, Option<__ConstMismatch>) {
+  CONST_MISMATCH.set(None);
+  let value = f();
+  (value, CONST_MISMATCH.take())
+}
+
+/// Used by generated code of exports and imports when value of the constant is different in the module
+#[doc(hidden)]
+pub fn __const_mismatch(trait_path: &'static str, name: &'static str) {
+  // only the first mismatch is reported
+  if CONST_MISMATCH.get().is_none() {
+    CONST_MISMATCH.set(Some(__ConstMismatch { trait_path, name }));
+  }
+}
+
+/// Used by generated `init_imports` to pass caller to the module
+#[doc(hidden)]
+pub fn __loading_caller() -> *const c_void {
+  LOADING.get().caller
+}
+
+/// Used by generated `init_imports` to bind only imports which are granted to the module
+#[doc(hidden)]
+pub fn __is_import_granted(trait_name: &'static str, fn_name: &'static str) -> bool {
+  CHECKED_IMPORTS.with_borrow_mut(|imports| imports.push((trait_name, fn_name)));
+
+  // SAFETY: pointer is only set while module is loaded
+  let Some(granted_imports) = (unsafe { LOADING.get().granted_imports.as_ref() }) else {
+    return true;
+  };
+
+  granted_imports.contains(trait_name)
+    || granted_imports.contains(&format!("{trait_name}::{fn_name}"))
+}
+
+/// Used by generated imports to make caller available in their implementations
+#[doc(hidden)]
+pub fn __with_caller<R>(caller: *const c_void, f: impl FnOnce() -> R) -> R {
+  let previous = CURRENT.replace(caller);
+  let _restore = Restore(&CURRENT, previous);
+  f()
+}
+
+/// Caller of currently executed import, used by relib_host
+#[doc(hidden)]
+pub fn __current_caller() -> *const c_void {
+  CURRENT.get()
+}
+
+/// Restores previous value even if import panics (imports can also be nested,
+/// for example if import calls export of another module which calls import)
+struct Restore<T: Copy + 'static>(&'static std::thread::LocalKey<Cell<T>>, T);
+
+impl<T: Copy + 'static> Drop for Restore<T> {
+  fn drop(&mut self) {
+    self.0.set(self.1);
+  }
+}
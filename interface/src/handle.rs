@@ -0,0 +1,119 @@
+use {
+  relib_internal_shared::ModuleId,
+  std::{
+    fmt::{self, Debug, Display, Formatter},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+  },
+};
+
+/// Opaque handle of the value owned by the module, which host can hold instead of raw pointer
+/// and pass back to module exports (see `relib_module::HandleTable`).
+///
+/// It contains only the id of the module and of the table which created it, index of the slot
+/// and its generation, so it's always safe to hold: after the value is removed, the module is unloaded or reloaded
+/// (every load has its own `ModuleId`) it resolves to [`StaleHandle`] instead of dangling pointer.
+#[repr(C)]
+pub struct Handle<T> {
+  module: ModuleId,
+  table: u32,
+  index: u32,
+  generation: u32,
+  // handle doesn't own the value, so it's Send and Sync regardless of T
+  _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+  #[doc(hidden)]
+  pub fn __new(module: ModuleId, table: u32, index: u32, generation: u32) -> Self {
+    Self {
+      module,
+      table,
+      index,
+      generation,
+      _marker: PhantomData,
+    }
+  }
+
+  /// Id of the module which created this handle (see `relib_host::Module::id`).
+  pub fn module_id(&self) -> ModuleId {
+    self.module
+  }
+
+  #[doc(hidden)]
+  pub fn __table(&self) -> u32 {
+    self.table
+  }
+
+  #[doc(hidden)]
+  pub fn __index(&self) -> u32 {
+    self.index
+  }
+
+  #[doc(hidden)]
+  pub fn __generation(&self) -> u32 {
+    self.generation
+  }
+}
+
+// implemented manually since derive would require T to implement these traits
+
+impl<T> Clone for Handle<T> {
+  fn clone(&self) -> Self {
+    *self
+  }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+  fn eq(&self, other: &Self) -> bool {
+    self.module == other.module
+      && self.table == other.table
+      && self.index == other.index
+      && self.generation == other.generation
+  }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.module.hash(state);
+    self.table.hash(state);
+    self.index.hash(state);
+    self.generation.hash(state);
+  }
+}
+
+impl<T> Debug for Handle<T> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Handle")
+      .field("module", &self.module)
+      .field("table", &self.table)
+      .field("index", &self.index)
+      .field("generation", &self.generation)
+      .finish()
+  }
+}
+
+/// Error returned when [`Handle`] no longer points to a value: it was removed from the table,
+/// or the handle was created by another table, another module or by previous load of this module.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StaleHandle {
+  /// Id of the module which created the handle
+  pub module: ModuleId,
+}
+
+impl Display for StaleHandle {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "stale handle (created by module with id: {})",
+      self.module
+    )
+  }
+}
+
+impl std::error::Error for StaleHandle {}
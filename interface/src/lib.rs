@@ -1,9 +1,48 @@
 #[cfg(feature = "build")]
-pub mod host;
-#[cfg(feature = "build")]
-pub mod module;
-#[cfg(feature = "build")]
-mod shared;
+pub use relib_interface_codegen::{GenerateOptions, host, module};
+
+#[cfg(feature = "macros")]
+pub use relib_interface_macro::{dyn_trait, exports, imports};
+
+#[cfg(feature = "host")]
+pub mod exports_types;
+
+/// Wraps host side of code generated by `#[relib_interface::exports]` and `#[relib_interface::imports]`,
+/// so that modules don't compile it (relib_host enables "host" feature)
+#[cfg(feature = "host")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __host_side {
+  ($($tokens:tt)*) => {
+    $($tokens)*
+  };
+}
+
+#[cfg(not(feature = "host"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __host_side {
+  ($($tokens:tt)*) => {};
+}
+
+mod transfer;
+pub use transfer::Transfer;
+
+mod module_box;
+pub use module_box::{DynTrait, ModuleBox, VTableFor};
+#[doc(hidden)]
+pub use module_box::{__DynData, __module_boxes_count};
+
+mod handle;
+pub use handle::{Handle, StaleHandle};
+
+mod callback_panic;
+#[doc(hidden)]
+pub use callback_panic::{__set_callback_panicked, __take_callback_panicked};
+
+// ABI-stable representations of `&str`, slices and closures, used by generated code
+#[doc(hidden)]
+pub use relib_internal_shared::{Callback as __Callback, RawSlice as __RawSlice, Str as __Str};
 
 #[cfg(feature = "include")]
 #[macro_export]
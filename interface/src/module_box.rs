@@ -0,0 +1,129 @@
+use std::{
+  ffi::c_void,
+  marker::PhantomData,
+  mem::MaybeUninit,
+  panic::{RefUnwindSafe, UnwindSafe},
+  sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Count of alive boxes created by this module (every module has its own copy of relib_interface),
+/// host refuses to unload module while it's not zero
+static ALIVE_BOXES: AtomicU64 = AtomicU64::new(0);
+
+/// Trait objects of traits marked with `#[relib_interface::dyn_trait]`.
+///
+/// # Safety
+/// Implemented by generated code, `__VTable` must be `#[repr(C)]` struct of `extern "C"` functions.
+pub unsafe trait DynTrait {
+  #[doc(hidden)]
+  type __VTable: 'static;
+}
+
+/// Implemented by generated code for every type which implements the trait marked with `#[relib_interface::dyn_trait]`.
+///
+/// # Safety
+/// Functions of `__VTABLE` must expect pointer to `T` as `data` parameter.
+pub unsafe trait VTableFor<T>: DynTrait {
+  #[doc(hidden)]
+  const __VTABLE: &'static Self::__VTable;
+}
+
+/// Owning handle of trait object which can be moved between host and module,
+/// for example, returned from module export (`fn create() -> ModuleBox<dyn Trait>`).
+///
+/// Value and vtable of it are in the memory of the module which created it,
+/// so host refuses to unload the module while any of its boxes is alive (see `relib_host::UnloadError::ModuleBoxesAlive`).
+/// Trait must be marked with `#[relib_interface::dyn_trait]`, which implements this trait for `ModuleBox<dyn Trait>`.
+#[repr(C)]
+pub struct ModuleBox<D: ?Sized + DynTrait> {
+  data: *mut c_void,
+  vtable: *const D::__VTable,
+  drop: extern "C" fn(data: *mut c_void, ____success____: *mut bool),
+  _marker: PhantomData<*mut D>,
+}
+
+impl<D: ?Sized + DynTrait> ModuleBox<D> {
+  pub fn new<T>(value: T) -> Self
+  where
+    D: VTableFor<T>,
+  {
+    ALIVE_BOXES.fetch_add(1, Ordering::SeqCst);
+
+    Self {
+      data: Box::into_raw(Box::new(value)).cast(),
+      vtable: D::__VTABLE,
+      drop: drop_value::<T>,
+      _marker: PhantomData,
+    }
+  }
+
+  #[doc(hidden)]
+  pub fn __data(&self) -> __DynData<'_> {
+    __DynData {
+      ptr: self.data,
+      _lifetime: PhantomData,
+    }
+  }
+
+  #[doc(hidden)]
+  pub fn __vtable(&self) -> &D::__VTable {
+    // SAFETY: module which created the box can't be unloaded while it's alive
+    unsafe { &*self.vtable }
+  }
+}
+
+// module catches panics of the value itself (see `#[relib_interface::dyn_trait]`),
+// so the box can be passed to exports and imports, which are called inside `catch_unwind`
+impl<D: ?Sized + DynTrait> UnwindSafe for ModuleBox<D> {}
+impl<D: ?Sized + DynTrait> RefUnwindSafe for ModuleBox<D> {}
+
+impl<D: ?Sized + DynTrait> Drop for ModuleBox<D> {
+  fn drop(&mut self) {
+    let mut ____success____ = MaybeUninit::<bool>::uninit();
+    (self.drop)(self.data, ____success____.as_mut_ptr());
+
+    // SAFETY: this bool is guaranteed to be initialized by the module
+    if !unsafe { ____success____.assume_init() } {
+      // panicking again while unwinding would abort the process
+      if std::thread::panicking() {
+        eprintln!("[relib] Drop of the value in ModuleBox panicked (while already panicking)");
+      } else {
+        panic!("Drop of the value in ModuleBox panicked");
+      }
+    }
+  }
+}
+
+// called in the module which created the box, so its own counter is decremented
+extern "C" fn drop_value<T>(data: *mut c_void, ____success____: *mut bool) {
+  let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+    // SAFETY: data was created from Box<T> in ModuleBox::new
+    drop(unsafe { Box::from_raw(data.cast::<T>()) });
+  }));
+  ALIVE_BOXES.fetch_sub(1, Ordering::SeqCst);
+
+  unsafe {
+    *____success____ = result.is_ok();
+  }
+}
+
+/// Pointer to the value of ModuleBox which is passed to functions of the vtable,
+/// lifetime is the lifetime of `&self` (return value can borrow from it)
+#[doc(hidden)]
+#[repr(transparent)]
+#[derive(Clone, Copy)]
+pub struct __DynData<'a> {
+  ptr: *mut c_void,
+  _lifetime: PhantomData<&'a ()>,
+}
+
+impl __DynData<'_> {
+  pub fn ptr(self) -> *mut c_void {
+    self.ptr
+  }
+}
+
+#[doc(hidden)]
+pub fn __module_boxes_count() -> u64 {
+  ALIVE_BOXES.load(Ordering::SeqCst)
+}
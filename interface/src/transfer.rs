@@ -0,0 +1,127 @@
+use std::alloc::Layout;
+
+/// Types which can be moved between host and module without cloning
+/// when interface is generated with shared allocator mode
+/// (see `relib_interface::GenerateOptions::shared_alloc`).
+///
+/// Memory of the moved value stays where it is, only its owner changes:
+/// allocations reported by [`Transfer::allocations`] are no longer deallocated when module is unloaded
+/// after value is moved to the host, and are deallocated on unload if value moved to the module leaks.
+///
+/// # Safety
+/// `allocations` must report every heap allocation owned by the value (including nested ones)
+/// with the layout it was allocated with, and the value must not own or reference anything else
+/// which can be gone after unloading (reference-counting pointers, `&'static T`, function pointers, vtables).
+pub unsafe trait Transfer {
+  fn allocations(&self, f: &mut dyn FnMut(*mut u8, Layout));
+}
+
+macro_rules! impl_without_allocations {
+  ($($type_:ty),* $(,)?) => {
+    $(
+      unsafe impl Transfer for $type_ {
+        fn allocations(&self, _: &mut dyn FnMut(*mut u8, Layout)) {}
+      }
+    )*
+  };
+}
+
+impl_without_allocations!(
+  (),
+  bool,
+  char,
+  u8,
+  i8,
+  u16,
+  i16,
+  u32,
+  i32,
+  u64,
+  i64,
+  u128,
+  i128,
+  usize,
+  isize,
+  f32,
+  f64,
+);
+
+unsafe impl Transfer for String {
+  fn allocations(&self, f: &mut dyn FnMut(*mut u8, Layout)) {
+    if self.capacity() != 0 {
+      // SAFETY: layout of the string buffer is the same as of `[u8; capacity]`
+      let layout = unsafe { Layout::from_size_align_unchecked(self.capacity(), 1) };
+      f(self.as_ptr().cast_mut(), layout);
+    }
+  }
+}
+
+unsafe impl<T: Transfer> Transfer for Vec<T> {
+  fn allocations(&self, f: &mut dyn FnMut(*mut u8, Layout)) {
+    // zero-sized types and empty vectors don't allocate
+    if size_of::<T>() != 0 && self.capacity() != 0 {
+      // SAFETY: vector was allocated with this layout so it can't overflow
+      let layout = unsafe {
+        Layout::from_size_align_unchecked(size_of::<T>() * self.capacity(), align_of::<T>())
+      };
+      f(self.as_ptr().cast_mut().cast(), layout);
+    }
+
+    for item in self {
+      item.allocations(f);
+    }
+  }
+}
+
+unsafe impl<T: Transfer> Transfer for Box<T> {
+  fn allocations(&self, f: &mut dyn FnMut(*mut u8, Layout)) {
+    let value: &T = self;
+    if size_of::<T>() != 0 {
+      f((value as *const T).cast_mut().cast(), Layout::new::<T>());
+    }
+
+    value.allocations(f);
+  }
+}
+
+unsafe impl<T: Transfer> Transfer for Option<T> {
+  fn allocations(&self, f: &mut dyn FnMut(*mut u8, Layout)) {
+    if let Some(value) = self {
+      value.allocations(f);
+    }
+  }
+}
+
+unsafe impl<T: Transfer, E: Transfer> Transfer for Result<T, E> {
+  fn allocations(&self, f: &mut dyn FnMut(*mut u8, Layout)) {
+    match self {
+      Ok(value) => value.allocations(f),
+      Err(error) => error.allocations(f),
+    }
+  }
+}
+
+unsafe impl<T: Transfer, const N: usize> Transfer for [T; N] {
+  fn allocations(&self, f: &mut dyn FnMut(*mut u8, Layout)) {
+    for item in self {
+      item.allocations(f);
+    }
+  }
+}
+
+macro_rules! impl_for_tuple {
+  ($($name:ident),+) => {
+    unsafe impl<$($name: Transfer),+> Transfer for ($($name,)+) {
+      #[allow(non_snake_case)]
+      fn allocations(&self, f: &mut dyn FnMut(*mut u8, Layout)) {
+        let ($($name,)+) = self;
+        $( $name.allocations(f); )+
+      }
+    }
+  };
+}
+
+impl_for_tuple!(A);
+impl_for_tuple!(A, B);
+impl_for_tuple!(A, B, C);
+impl_for_tuple!(A, B, C, D);
@@ -0,0 +1,305 @@
+use {
+  crate::marshal::Marshal,
+  proc_macro2::TokenStream as TokenStream2,
+  quote::{ToTokens, format_ident, quote},
+  syn::{PathArguments, ReturnType, TraitBound, Type, TypeGroup, TypeParamBound, TypeParen},
+};
+
+/// `&dyn Fn(A) -> R` or `&mut dyn FnMut(A) -> R` parameter, it's passed as `relib_interface::__Callback`:
+/// the calling side passes pointer to the closure with `extern "C"` thunk which calls it
+/// and the called side passes its own closure which calls the thunk to the implementation
+pub struct Callback {
+  mut_: bool,
+  inputs: Vec<(TokenStream2, Option<Marshal>)>,
+  /// `-> R` of the closure trait, it's empty if closure returns `()`
+  fn_output: TokenStream2,
+  output: TokenStream2,
+  output_marshal: Option<Marshal>,
+}
+
+impl Callback {
+  pub fn new(type_: &Type) -> Option<Self> {
+    let Type::Reference(reference) = type_ else {
+      return None;
+    };
+    // `&(dyn Fn() + 'a)` syntax is needed for lifetime bounds
+    let mut elem = &*reference.elem;
+    while let Type::Paren(TypeParen { elem: inner, .. })
+    | Type::Group(TypeGroup { elem: inner, .. }) = elem
+    {
+      elem = inner;
+    }
+    let Type::TraitObject(trait_object) = elem else {
+      return None;
+    };
+    let mut_ = reference.mutability.is_some();
+
+    let traits = trait_object
+      .bounds
+      .iter()
+      .filter_map(|bound| match bound {
+        TypeParamBound::Trait(trait_) => Some(trait_),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    let trait_ = traits.iter().find(|trait_| is_closure_trait(trait_))?;
+
+    let code = type_.to_token_stream();
+
+    // closure of the called side is not Send or Sync
+    if traits.len() != 1 {
+      panic!(
+        "Closure parameter `{code}` can't have other trait bounds (for example, `Send`), \
+        only lifetime bounds are allowed"
+      );
+    }
+
+    let segment = trait_.path.segments.last()?;
+    match (segment.ident.to_string().as_str(), mut_) {
+      ("Fn", false) | ("FnMut", true) => {}
+      _ => panic!(
+        "Closure parameter `{code}` is not supported, \
+        expected `&dyn Fn(A) -> R` or `&mut dyn FnMut(A) -> R`"
+      ),
+    }
+    let PathArguments::Parenthesized(arguments) = &segment.arguments else {
+      return None;
+    };
+
+    let inputs = arguments
+      .inputs
+      .iter()
+      .map(|input| (input.to_token_stream(), Marshal::new(input)))
+      .collect();
+    let (output, output_marshal) = match &arguments.output {
+      ReturnType::Default => (quote! { () }, None),
+      ReturnType::Type(_, type_) => (type_.to_token_stream(), Marshal::new(type_)),
+    };
+
+    Some(Self {
+      mut_,
+      inputs,
+      fn_output: arguments.output.to_token_stream(),
+      output,
+      output_marshal,
+    })
+  }
+
+  /// Type of the return value of `extern "C"` thunk
+  fn abi_output(&self) -> TokenStream2 {
+    match &self.output_marshal {
+      Some(marshal) => marshal.abi_type(),
+      None => self.output.clone(),
+    }
+  }
+
+  fn call_type(&self) -> TokenStream2 {
+    let Self { inputs, .. } = self;
+    let output = self.abi_output();
+    let abi_inputs = inputs.iter().map(|(type_, marshal)| match marshal {
+      Some(marshal) => marshal.abi_type(),
+      None => type_.clone(),
+    });
+
+    quote! {
+      extern "C" fn(
+        data: *mut std::ffi::c_void,
+        ____success____: *mut bool,
+        #( #abi_inputs ),*
+      ) -> std::mem::MaybeUninit<#output>
+    }
+  }
+
+  pub fn abi_type(&self) -> TokenStream2 {
+    let call_type = self.call_type();
+    quote! { relib_interface::__Callback<#call_type> }
+  }
+
+  /// Statement which must be executed before encoded value is passed (`value` is an identifier),
+  /// pointer to `&mut dyn FnMut` can only be taken from mutable variable
+  pub fn prelude(&self, value: &TokenStream2) -> TokenStream2 {
+    if self.mut_ {
+      quote! { let mut #value = #value; }
+    } else {
+      quote! {}
+    }
+  }
+
+  /// Closure of the calling side, it must be alive during the call (`value` is an identifier)
+  pub fn encode(&self, value: &TokenStream2) -> TokenStream2 {
+    let Self {
+      mut_,
+      inputs,
+      fn_output,
+      output_marshal,
+      ..
+    } = self;
+
+    let call_type = self.call_type();
+    let output = self.abi_output();
+    let encoded_return_value = match output_marshal {
+      Some(marshal) => marshal.encode(&quote! { return_value }),
+      None => quote! { return_value },
+    };
+    let arg_idents = arg_idents(inputs.len());
+    let arg_types = inputs.iter().map(|(type_, _)| type_);
+    let abi_args = inputs
+      .iter()
+      .zip(&arg_idents)
+      .map(|((type_, marshal), ident)| match marshal {
+        Some(marshal) => {
+          let abi_type = marshal.abi_type();
+          quote! { #ident: #abi_type }
+        }
+        None => quote! { #ident: #type_ },
+      });
+    let decoded_args = inputs
+      .iter()
+      .zip(&arg_idents)
+      .map(|((_, marshal), ident)| match marshal {
+        Some(marshal) => marshal.decode(&ident.to_token_stream()),
+        None => ident.to_token_stream(),
+      });
+
+    let (closure, data) = if *mut_ {
+      (
+        quote! {
+          unsafe { &mut *data.cast::<&mut dyn FnMut( #( #arg_types ),* ) #fn_output>() }
+        },
+        quote! { (&raw mut #value).cast() },
+      )
+    } else {
+      (
+        quote! {
+          unsafe { *data.cast::<&dyn Fn( #( #arg_types ),* ) #fn_output>() }
+        },
+        quote! { (&raw const #value).cast_mut().cast() },
+      )
+    };
+
+    quote! {{
+      extern "C" fn thunk(
+        data: *mut std::ffi::c_void,
+        ____success____: *mut bool,
+        #( #abi_args ),*
+      ) -> std::mem::MaybeUninit<#output> // will be initialized if closure won't panic
+      {
+        // SAFETY: data points to the closure which is alive during the call it's passed to
+        let closure = #closure;
+
+        // closure is not called again if it panicked, since the other side panics too
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || {
+          closure( #( #decoded_args ),* )
+        }));
+
+        match result {
+          Ok(return_value) => {
+            unsafe {
+              *____success____ = true;
+            }
+
+            #[allow(clippy::unit_arg)]
+            std::mem::MaybeUninit::new(#encoded_return_value)
+          }
+          // ignoring content since it's printed by std
+          Err(_) => {
+            relib_interface::__set_callback_panicked();
+            unsafe {
+              *____success____ = false;
+            }
+
+            std::mem::MaybeUninit::uninit()
+          }
+        }
+      }
+
+      relib_interface::__Callback::<#call_type> {
+        data: #data,
+        call: thunk,
+      }
+    }}
+  }
+
+  /// Closure of the called side which calls the closure of the calling side,
+  /// it panics if that closure panics (`value` is an identifier)
+  pub fn decode(&self, value: &TokenStream2) -> TokenStream2 {
+    let Self {
+      mut_,
+      inputs,
+      fn_output,
+      output_marshal,
+      ..
+    } = self;
+
+    let arg_idents = arg_idents(inputs.len());
+    let args = inputs
+      .iter()
+      .zip(&arg_idents)
+      .map(|((type_, _), ident)| quote! { #ident: #type_ });
+    let encoded_args = inputs
+      .iter()
+      .zip(&arg_idents)
+      .map(|((_, marshal), ident)| match marshal {
+        Some(marshal) => marshal.encode(&ident.to_token_stream()),
+        None => ident.to_token_stream(),
+      });
+    let unmarshalled_args = inputs
+      .iter()
+      .zip(&arg_idents)
+      .filter(|((_, marshal), _)| marshal.is_none())
+      .map(|(_, ident)| ident);
+
+    let decoded_return_value = quote! { unsafe { return_value.assume_init() } };
+    let decoded_return_value = match output_marshal {
+      Some(marshal) => marshal.decode(&decoded_return_value),
+      None => decoded_return_value,
+    };
+
+    let panic_message = format!("Closure passed as \"{value}\" panicked");
+    let reference = if *mut_ {
+      quote! { &mut }
+    } else {
+      quote! { & }
+    };
+
+    quote! {
+      #reference (move | #( #args ),* | #fn_output {
+        /// Arguments and return value must be Copy (except `&mut [T]`), see relib caveats in the readme for more info.
+        fn ____assert_type_is_copy____(_: impl Copy) {}
+        #( ____assert_type_is_copy____( #unmarshalled_args ); )*
+
+        let callback = #value;
+        let mut ____success____ = std::mem::MaybeUninit::<bool>::uninit();
+
+        let return_value = (callback.call)(
+          callback.data,
+          ____success____.as_mut_ptr(),
+          #( #encoded_args ),*
+        );
+
+        // SAFETY: this bool is guaranteed to be initialized by the thunk
+        if !unsafe { ____success____.assume_init() } {
+          panic!(#panic_message);
+        }
+
+        ____assert_type_is_copy____(return_value);
+
+        // SAFETY: thunk returned true so we are allowed to read the return value
+        #decoded_return_value
+      })
+    }
+  }
+}
+
+fn is_closure_trait(trait_: &TraitBound) -> bool {
+  trait_.path.segments.last().is_some_and(|segment| {
+    let ident = segment.ident.to_string();
+    ["Fn", "FnMut", "FnOnce"].contains(&ident.as_str())
+  })
+}
+
+fn arg_idents(len: usize) -> Vec<syn::Ident> {
+  (0..len)
+    .map(|idx| format_ident!("____arg{idx}____"))
+    .collect()
+}
@@ -0,0 +1,297 @@
+use {
+  crate::{
+    marshal::Params,
+    return_value::{ReturnValue, result_type_decl},
+    shared::{TraitFn, for_each_trait_item, take_receiver},
+  },
+  proc_macro2::TokenStream as TokenStream2,
+  quote::{format_ident, quote},
+  relib_internal_shared::output_to_return_type,
+  syn::{
+    ItemTrait, TraitBoundModifier, TraitItem, Type, TypeParamBound, WhereClause, WherePredicate,
+  },
+};
+
+/// Generates `#[repr(C)]` vtable of the trait, implementation of `relib_interface::VTableFor<T>` which creates it
+/// for every type which implements the trait (module side) and implementation of the trait
+/// for `relib_interface::ModuleBox<dyn Trait>` which calls functions of the vtable (host side)
+pub fn dyn_trait_code(trait_: &ItemTrait) -> syn::Result<TokenStream2> {
+  let trait_ident = &trait_.ident;
+  let trait_name = trait_ident.to_string();
+
+  if !trait_.generics.params.is_empty() {
+    return Err(syn::Error::new_spanned(
+      &trait_.generics,
+      "trait used as `ModuleBox<dyn Trait>` can't be generic",
+    ));
+  }
+
+  let mut vtable_decls = Vec::new();
+  let mut vtable_inits = Vec::new();
+  let mut thunks = Vec::new();
+  let mut proxy_fns = Vec::new();
+
+  for item in &trait_.items {
+    let TraitItem::Fn(fn_) = item else {
+      return Err(syn::Error::new_spanned(
+        item,
+        "only methods are supported in trait used as `ModuleBox<dyn Trait>`",
+      ));
+    };
+
+    // methods which are not available on trait objects (`where Self: Sized`) are not added to the vtable,
+    // ModuleBox uses their default implementation
+    if let Some(where_clause) = &fn_.sig.generics.where_clause {
+      if !requires_sized_self(where_clause) {
+        return Err(syn::Error::new_spanned(
+          where_clause,
+          "only methods with `where Self: Sized` can have where clause in trait used as `ModuleBox<dyn Trait>`",
+        ));
+      }
+      if fn_.default.is_none() {
+        return Err(syn::Error::new_spanned(
+          &fn_.sig,
+          "methods with `where Self: Sized` must have default implementation in trait used as `ModuleBox<dyn Trait>`",
+        ));
+      }
+      continue;
+    }
+
+    let mut stripped = fn_.clone();
+    let Some((receiver_lifetime, receiver_mut)) = take_receiver(&mut stripped.sig) else {
+      return Err(syn::Error::new_spanned(
+        &fn_.sig,
+        "methods of trait used as `ModuleBox<dyn Trait>` must take `&self` or `&mut self`",
+      ));
+    };
+    let stripped = TraitItem::Fn(stripped);
+
+    let TraitFn {
+      ident,
+      inputs,
+      output,
+      post_ident,
+      lifetimes_for,
+      lifetimes_full,
+      lifetimes_module,
+      cfg_attrs,
+      ..
+    } = for_each_trait_item(&trait_name, &stripped);
+
+    let return_type = output_to_return_type!(output);
+    let return_value = ReturnValue::new(&return_type, false);
+    let abi_return_type = &return_value.abi_type;
+
+    let Params {
+      abi_inputs,
+      prelude,
+      encoded,
+      decoded,
+      unmarshalled,
+    } = Params::new(&inputs);
+
+    let thunk_ident = format_ident!("__relib__{ident}");
+    let post_thunk_ident = format_ident!("__relib__post_{ident}");
+
+    vtable_decls.push(quote! {
+      #cfg_attrs
+      #ident: #lifetimes_for extern "C" fn(
+        data: relib_interface::__DynData<#receiver_lifetime>,
+        ____success____: *mut bool,
+        #abi_inputs
+      ) -> std::mem::MaybeUninit<#abi_return_type>,
+    });
+    vtable_inits.push(quote! {
+      #cfg_attrs
+      #ident: #thunk_ident::<T>,
+    });
+
+    // module side
+
+    let value = if receiver_mut {
+      quote! { unsafe { &mut *data.ptr().cast::<T>() } }
+    } else {
+      quote! { unsafe { &*data.ptr().cast::<T>() } }
+    };
+    let encoded_return_value = return_value.encode(quote! { return_value }, &quote! {});
+
+    thunks.push(quote! {
+      #cfg_attrs
+      // lifetimes of references are not used in their ABI-stable representations
+      // and elided lifetimes may be named, see `take_receiver`
+      #[allow(clippy::extra_unused_lifetimes, clippy::needless_lifetimes)]
+      extern "C" fn #thunk_ident <#lifetimes_module T: #trait_ident> (
+        data: relib_interface::__DynData<#receiver_lifetime>,
+        ____success____: *mut bool,
+        #abi_inputs
+      ) -> std::mem::MaybeUninit<#abi_return_type> // will be initialized if method won't panic
+      {
+        // references are converted back inside the closure since `&mut [T]` is not UnwindSafe
+        let result = std::panic::catch_unwind(move || {
+          // SAFETY: vtable is created for T in `VTableFor::<T>::__VTABLE`
+          let value = #value;
+          <T as #trait_ident>::#ident( value, #( #decoded ),* )
+        });
+
+        match result {
+          Ok(return_value) => {
+            unsafe {
+              *____success____ = true;
+            }
+
+            #[allow(unused_braces, clippy::unit_arg)]
+            std::mem::MaybeUninit::new({ #encoded_return_value })
+          }
+          // ignoring content since it's printed by std
+          Err(_) => {
+            unsafe {
+              *____success____ = false;
+            }
+
+            std::mem::MaybeUninit::uninit()
+          }
+        }
+      }
+    });
+
+    // module frees the box after host cloned the value
+    let read_return_value = quote! { unsafe { return_value.assume_init() } };
+    let read_return_value = if return_value.needs_post {
+      vtable_decls.push(quote! {
+        #cfg_attrs
+        #post_ident: #lifetimes_for extern "C" fn(#abi_return_type),
+      });
+      vtable_inits.push(quote! {
+        #cfg_attrs
+        #post_ident: #post_thunk_ident,
+      });
+
+      let free = return_value.free(quote! { return_value });
+      thunks.push(quote! {
+        #cfg_attrs
+        #[allow(clippy::extra_unused_lifetimes, clippy::needless_lifetimes)]
+        extern "C" fn #post_thunk_ident #lifetimes_full (return_value: #abi_return_type) {
+          #free
+        }
+      });
+
+      let decoded = return_value.decode(quote! { return_value }, &quote! {});
+      quote! {
+        let return_value = #read_return_value;
+        let decoded = #decoded;
+        (vtable.#post_ident)(return_value);
+
+        decoded
+      }
+    } else {
+      return_value.decode(read_return_value, &quote! {})
+    };
+
+    // host side
+
+    let sig = &fn_.sig;
+    let panic_message = format!(
+      "Module panicked in \"{trait_name}::{ident}\" method called through ModuleBox\n\
+      note: consider unloading module if it panicked, as it is unsafe to call it again"
+    );
+
+    proxy_fns.push(quote! {
+      #cfg_attrs
+      #sig {
+        /// All parameters must be Copy (except `&mut [T]` and closures), see relib caveats in the readme for more info.
+        fn ____assert_type_is_copy____(_: impl Copy) {}
+        #( ____assert_type_is_copy____( #unmarshalled ); )*
+
+        let vtable = relib_interface::ModuleBox::__vtable(self);
+
+        #( #prelude )*
+        let mut ____success____ = std::mem::MaybeUninit::<bool>::uninit();
+
+        let return_value = (vtable.#ident)(
+          relib_interface::ModuleBox::__data(self),
+          ____success____.as_mut_ptr(),
+          #( #encoded ),*
+        );
+
+        // SAFETY: this bool is guaranteed to be initialized by the module
+        if !unsafe { ____success____.assume_init() } {
+          panic!("{}", #panic_message);
+        }
+
+        #[allow(unused_braces, clippy::unit_arg)]
+        { #read_return_value }
+      }
+    });
+  }
+
+  let result_type_decl = result_type_decl();
+
+  Ok(quote! {
+    const _: () = {
+      #result_type_decl
+
+      #[doc(hidden)]
+      #[repr(C)]
+      #[allow(non_snake_case, non_camel_case_types)]
+      pub struct ____VTable____ {
+        #( #vtable_decls )*
+      }
+
+      unsafe impl relib_interface::DynTrait for dyn #trait_ident {
+        type __VTable = ____VTable____;
+      }
+
+      unsafe impl<T: #trait_ident + 'static> relib_interface::VTableFor<T> for dyn #trait_ident {
+        const __VTABLE: &'static ____VTable____ = &____VTable____ {
+          #( #vtable_inits )*
+        };
+      }
+
+      #( #thunks )*
+
+      impl #trait_ident for relib_interface::ModuleBox<dyn #trait_ident> {
+        #( #proxy_fns )*
+      }
+    };
+  })
+}
+
+/// `ModuleBox<dyn Trait>` is moved between host and module as is (it's `#[repr(C)]`),
+/// path of the type doesn't matter
+pub fn is_module_box(type_: &Type) -> bool {
+  let Type::Path(path) = type_ else {
+    return false;
+  };
+  path.qself.is_none()
+    && path
+      .path
+      .segments
+      .last()
+      .is_some_and(|segment| segment.ident == "ModuleBox")
+}
+
+/// Whether where clause has `Self: Sized` predicate, which makes the method unavailable on trait objects
+fn requires_sized_self(where_clause: &WhereClause) -> bool {
+  where_clause.predicates.iter().any(|predicate| {
+    let WherePredicate::Type(predicate) = predicate else {
+      return false;
+    };
+    let Type::Path(bounded) = &predicate.bounded_ty else {
+      return false;
+    };
+
+    bounded.qself.is_none()
+      && bounded.path.is_ident("Self")
+      && predicate.bounds.iter().any(|bound| match bound {
+        TypeParamBound::Trait(trait_) => {
+          matches!(trait_.modifier, TraitBoundModifier::None)
+            && trait_
+              .path
+              .segments
+              .last()
+              .is_some_and(|segment| segment.ident == "Sized")
+        }
+        _ => false,
+      })
+  })
+}
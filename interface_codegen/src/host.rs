@@ -0,0 +1,834 @@
+use {
+  crate::{
+    marshal::Params,
+    return_value::{ReturnValue, result_type_decl},
+    shared::{
+      Mode, SAFETY_DOC, TraitFile, TraitFn, caller_symbol_name, cfg_attrs, const_symbol_name,
+      extract_trait_name_from_path, for_each_trait_item, out_dir_file_name, parse_trait_file,
+      pass_out_dir_file_name_to_crate_code, shared_alloc_marker_name, table_symbol_name,
+      write_code_to_file,
+    },
+  },
+  proc_macro2::TokenStream as TokenStream2,
+  quote::quote,
+  relib_internal_shared::output_to_return_type,
+  syn::{ItemConst, ItemTrait},
+};
+
+#[cfg(feature = "internal")]
+pub fn generate_internal(
+  exports_file_content: &'static str,
+  exports_trait_path: &str,
+  imports_file_content: &'static str,
+  imports_trait_path: &str,
+) {
+  generate_exports_(
+    exports_file_content,
+    exports_trait_path,
+    false,
+    "internal_generated_module",
+    false,
+  );
+  generate_imports_(
+    imports_file_content,
+    imports_trait_path,
+    false,
+    "internal_generated_module",
+    false,
+  );
+}
+
+/// Will generate `generated_module_exports.rs` and `generated_module_imports.rs`
+/// in the OUT_DIR which you can include using
+/// `relib_interface::include_exports!();` and `relib_interface::include_exports!();`
+/// in your `lib.rs` or `main.rs` and then use `ModuleExports` struct:
+/// ```
+/// let exports = ModuleExports::new(library);
+/// exports.something();
+/// ```
+#[cfg(feature = "public")]
+pub fn generate(
+  exports_file_content: &'static str,
+  exports_trait_path: &str,
+  imports_file_content: &'static str,
+  imports_trait_path: &str,
+) {
+  generate_exports_(
+    exports_file_content,
+    exports_trait_path,
+    true,
+    "generated_module",
+    false,
+  );
+  generate_imports_(
+    imports_file_content,
+    imports_trait_path,
+    true,
+    "generated_module",
+    false,
+  );
+}
+
+/// Will generate `{prefix}_exports.rs` and `{prefix}_imports.rs` in the OUT_DIR which you can include
+/// using `relib_interface::include_exports!(gen_exports, <prefix>);` and `relib_interface::include_imports!(gen_imports, <prefix>);`
+/// in your `lib.rs` or `main.rs` and then use `ModuleExports` struct:
+/// ```
+/// let exports = ModuleExports::new(library);
+/// exports.something();
+/// ```
+#[cfg(feature = "public")]
+pub fn generate_with_prefix(
+  prefix: &str,
+  exports_file_content: &'static str,
+  exports_trait_path: &str,
+  imports_file_content: &'static str,
+  imports_trait_path: &str,
+) {
+  generate_exports_(
+    exports_file_content,
+    exports_trait_path,
+    true,
+    prefix,
+    false,
+  );
+  generate_imports_(
+    imports_file_content,
+    imports_trait_path,
+    true,
+    prefix,
+    false,
+  );
+}
+
+/// Same as [`generate_with_prefix`] but also allows to configure code generation,
+/// for example, to enable shared allocator mode (see [`GenerateOptions`](crate::GenerateOptions)).
+#[cfg(feature = "public")]
+pub fn generate_with_options(
+  options: &crate::GenerateOptions,
+  exports_file_content: &'static str,
+  exports_trait_path: &str,
+  imports_file_content: &'static str,
+  imports_trait_path: &str,
+) {
+  let crate::GenerateOptions {
+    prefix,
+    shared_alloc,
+  } = options;
+
+  generate_exports_(
+    exports_file_content,
+    exports_trait_path,
+    true,
+    prefix,
+    *shared_alloc,
+  );
+  generate_imports_(
+    imports_file_content,
+    imports_trait_path,
+    true,
+    prefix,
+    *shared_alloc,
+  );
+}
+
+#[cfg(feature = "public")]
+pub fn generate_exports(exports_file_content: &'static str, exports_trait_path: &str) {
+  generate_exports_(
+    exports_file_content,
+    exports_trait_path,
+    true,
+    "generated_module",
+    false,
+  );
+}
+
+#[cfg(feature = "public")]
+pub fn generate_imports(imports_file_content: &'static str, imports_trait_path: &str) {
+  generate_imports_(
+    imports_file_content,
+    imports_trait_path,
+    true,
+    "generated_module",
+    false,
+  );
+}
+
+#[cfg(feature = "public")]
+pub fn generate_exports_with_prefix(
+  prefix: &str,
+  exports_file_content: &'static str,
+  exports_trait_path: &str,
+) {
+  generate_exports_(
+    exports_file_content,
+    exports_trait_path,
+    true,
+    prefix,
+    false,
+  );
+}
+
+#[cfg(feature = "public")]
+pub fn generate_imports_with_prefix(
+  prefix: &str,
+  imports_file_content: &'static str,
+  imports_trait_path: &str,
+) {
+  generate_imports_(
+    imports_file_content,
+    imports_trait_path,
+    true,
+    prefix,
+    false,
+  );
+}
+
+fn generate_exports_(
+  exports_file_content: &'static str,
+  exports_trait_path: &str,
+  pub_exports: bool,
+  prefix: &str,
+  shared_alloc: bool,
+) {
+  pass_out_dir_file_name_to_crate_code(prefix, "exports");
+
+  let trait_name = extract_trait_name_from_path(exports_trait_path);
+  let TraitFile {
+    trait_: exports_trait,
+    items,
+    consts,
+  } = parse_trait_file(trait_name, exports_file_content, exports_trait_path);
+
+  let code = exports_code(
+    &exports_trait,
+    exports_trait_path,
+    &consts,
+    pub_exports,
+    shared_alloc,
+    Mode::BuildScript,
+  );
+
+  let result_type_decl = result_type_decl();
+
+  write_code_to_file(
+    &out_dir_file_name(prefix, "exports"),
+    quote! {
+      #items
+
+      #result_type_decl
+
+      #code
+    },
+  );
+}
+
+/// `ModuleExports` struct for calling exports of the module
+pub(crate) fn exports_code(
+  exports_trait: &ItemTrait,
+  exports_trait_path: &str,
+  consts: &[ItemConst],
+  pub_exports: bool,
+  shared_alloc: bool,
+  mode: Mode,
+) -> TokenStream2 {
+  let trait_name = &exports_trait.ident.to_string();
+
+  let mut export_decls = Vec::<TokenStream2>::new();
+  let mut export_inits = Vec::<TokenStream2>::new();
+  let mut export_impls = Vec::<TokenStream2>::new();
+
+  for item in &exports_trait.items {
+    let TraitFn {
+      ident,
+      inputs,
+      inputs_without_types,
+      output,
+      mangled_name,
+      mangled_ident: _,
+      post_ident,
+      post_mangled_name,
+      post_mangled_ident: _,
+      lifetimes_for,
+      lifetimes_full: _,
+      lifetimes_where_module,
+      lifetimes_module,
+      cfg_attrs,
+    } = for_each_trait_item(trait_name, item);
+
+    let pub_return_type = output_to_return_type!(output);
+
+    let panic_message = format!(
+      "Couldn't find \"{ident}\" export\n\
+      note: make sure this module implements {exports_trait_path} trait\n\
+      note: if `relib_interface::include_exports!()` is used not in module crate, \
+      make sure the crate (that invokes `include_exports`) is used in module crate, for example you can do so by \
+      using this syntax: `use <crate> as _;`"
+    );
+
+    let post_panic_message = format!(
+      r#"Failed to get "{post_ident}" fn symbol from module (mangled name: "{post_mangled_name}")"#
+    );
+
+    let import_init = match mode {
+      Mode::BuildScript => quote! {
+        #cfg_attrs
+        #ident: unsafe {
+          *library.get(concat!(#mangled_name, "\0").as_bytes()).expect(#panic_message)
+        },
+      },
+      Mode::Macro => quote! {
+        #cfg_attrs
+        #ident: table.#ident,
+      },
+    };
+
+    let ignore_code_style_warns = quote! {
+      #[allow(clippy::needless_lifetimes, clippy::unused_unit)]
+    };
+
+    // !!! keep in sync with main and before_unload calls in relib_host crate !!!
+    let (decl, init, impl_) = if pub_exports {
+      let return_value = ReturnValue::new(&pub_return_type, shared_alloc);
+      let return_type = &return_value.abi_type;
+
+      // SAFETY: function returned true so we are allowed to read the return value
+      let read_return_value = quote! { unsafe { return_value.assume_init() } };
+
+      // module moves the ownership of boxes to the host with shared allocator, there is no need in post function
+      let (post_decl, post_init, read_return_value) = if return_value.needs_post {
+        let decoded = return_value.decode(quote! { return_value }, &quote! {});
+        (
+          quote! {
+            #cfg_attrs
+            #post_ident: #lifetimes_for extern "C" fn( #return_type ),
+          },
+          match mode {
+            Mode::BuildScript => quote! {
+              #cfg_attrs
+              #post_ident: unsafe {
+                *library.get(concat!(#post_mangled_name, "\0").as_bytes()).expect(#post_panic_message)
+              },
+            },
+            Mode::Macro => quote! {
+              #cfg_attrs
+              #post_ident: table.#post_ident,
+            },
+          },
+          quote! {
+            let return_value = #read_return_value;
+            let decoded = #decoded;
+            (self.#post_ident)(return_value);
+
+            decoded
+          },
+        )
+      } else {
+        (
+          quote! {},
+          quote! {},
+          return_value.decode(read_return_value, &quote! {}),
+        )
+      };
+
+      let Params {
+        abi_inputs,
+        prelude,
+        encoded,
+        unmarshalled,
+        ..
+      } = Params::new(&inputs);
+
+      (
+        quote! {
+          #cfg_attrs
+          #ident: #lifetimes_for extern "C" fn(
+            ____success____: *mut bool,
+            #abi_inputs
+          ) -> std::mem::MaybeUninit<#return_type>,
+          #post_decl
+        },
+        quote! {
+          #import_init
+          #post_init
+        },
+        quote! {
+          /// Returns `None` if module panics.
+          /// Consider unloading module if it panicked, as it is unsafe to call it again.
+          /// Note: not all panics are handled, see a ["double panic"](https://doc.rust-lang.org/std/ops/trait.Drop.html#panics).
+          /// ```no_run
+          /// struct Bomb;
+          /// impl Drop for Bomb {
+          ///   fn drop(&mut self) {
+          ///     panic!("boom"); // will abort the program
+          ///   }
+          /// }
+          /// let _bomb = Bomb;
+          /// panic!();
+          /// ```
+          #[doc = #SAFETY_DOC]
+          #[must_use = "returns None if module panics, consider unloading module if it panicked, as it is unsafe to call it again"]
+          #ignore_code_style_warns
+          #cfg_attrs
+          pub unsafe fn #ident <'module, #lifetimes_module> (
+            &'module self,
+            #inputs
+          ) -> Option<#pub_return_type>
+          #lifetimes_where_module
+          {
+            /// All parameters must be Copy (except `&mut [T]` and closures), see relib caveats in the readme for more info.
+            fn ____assert_type_is_copy____(_: impl Copy) {}
+            #( ____assert_type_is_copy____( #unmarshalled ); )*
+
+            #( #prelude )*
+            let mut ____success____ = std::mem::MaybeUninit::<bool>::uninit();
+
+            let return_value = (self.#ident)(
+              ____success____.as_mut_ptr(),
+              #( #encoded ),*
+            );
+
+            // SAFETY: this bool is guaranteed to be initialized by the module
+            if !unsafe { ____success____.assume_init() } {
+              return None;
+            }
+
+            #[allow(unused_braces, clippy::unit_arg)]
+            Some({ #read_return_value })
+          }
+        },
+      )
+    } else {
+      (
+        quote! {
+          #cfg_attrs
+          #ident: #lifetimes_for extern "C" fn( #inputs ) #output,
+        },
+        import_init,
+        quote! {
+          #[doc = #SAFETY_DOC]
+          #ignore_code_style_warns
+          #cfg_attrs
+          pub unsafe fn #ident #lifetimes_module ( &self, #inputs ) -> #pub_return_type
+          #lifetimes_where_module
+          {
+            #[allow(clippy::let_unit_value)]
+            let return_value = (self.#ident)( #( #inputs_without_types )* );
+            return_value
+          }
+        },
+      )
+    };
+
+    export_decls.push(decl);
+    export_inits.push(init);
+    export_impls.push(impl_);
+  }
+
+  let shared_alloc_check = if pub_exports && shared_alloc {
+    let marker_name = shared_alloc_marker_name(trait_name);
+    let panic_message = "Module exports are not generated with shared allocator mode\n\
+      note: make sure that module build script uses `relib_interface::module::generate_with_options` \
+      with `shared_alloc` enabled";
+
+    quote! {
+      unsafe {
+        library.get::<*const u8>(concat!(#marker_name, "\0").as_bytes()).expect(#panic_message);
+      }
+    }
+  } else {
+    quote! {}
+  };
+
+  let exports_types = if pub_exports {
+    exports_types_path(mode)
+  } else {
+    quote! { crate::exports_types }
+  };
+
+  let consts_check = consts_check(trait_name, exports_trait_path, consts, &exports_types);
+
+  let (library_type, read_table) = match mode {
+    Mode::BuildScript => (quote! { libloading::Library }, quote! {}),
+    Mode::Macro => {
+      let table_symbol = table_symbol_name(trait_name);
+      let panic_message = format!(
+        "Couldn't find exports of {exports_trait_path} trait\n\
+        note: make sure this module implements it using `#[relib_interface::exports]` on impl block"
+      );
+
+      (
+        quote! { relib_interface::exports_types::Library },
+        quote! {
+          let table: extern "C" fn() -> *const std::ffi::c_void = unsafe {
+            *library.get(concat!(#table_symbol, "\0").as_bytes()).expect(#panic_message)
+          };
+          // SAFETY: host and module are compiled with the same shared crate code
+          let table = unsafe { *table().cast::<ModuleExportsTable>() };
+        },
+      )
+    }
+  };
+
+  quote! {
+    use #exports_types::ModuleExportsForHost;
+
+    #[allow(non_snake_case)]
+    #[derive(Clone)]
+    pub struct ModuleExports {
+      #( #export_decls )*
+    }
+
+    impl ModuleExports {
+      pub fn new(library: &#library_type) -> Self {
+        #shared_alloc_check
+        #consts_check
+        #read_table
+
+        Self {
+          #( #export_inits )*
+        }
+      }
+
+      #( #export_impls )*
+    }
+
+    impl ModuleExportsForHost for ModuleExports {
+      fn new(library: &#library_type) -> Self {
+        Self::new(library)
+      }
+    }
+  }
+}
+
+fn generate_imports_(
+  imports_file_content: &'static str,
+  imports_trait_path: &str,
+  pub_imports: bool,
+  prefix: &str,
+  shared_alloc: bool,
+) {
+  pass_out_dir_file_name_to_crate_code(prefix, "imports");
+
+  let trait_name = extract_trait_name_from_path(imports_trait_path);
+  let TraitFile {
+    trait_: imports_trait,
+    items,
+    consts,
+  } = parse_trait_file(trait_name, imports_file_content, imports_trait_path);
+
+  let imports_trait_path_syn: syn::Path =
+    syn::parse_str(imports_trait_path).expect("Failed to parse imports_trait_path as syn::Path");
+
+  let code = imports_code(
+    &imports_trait,
+    imports_trait_path,
+    &consts,
+    pub_imports,
+    shared_alloc,
+    Mode::BuildScript,
+  );
+
+  let result_type_decl = result_type_decl();
+
+  write_code_to_file(
+    &out_dir_file_name(prefix, "imports"),
+    quote! {
+      #items
+
+      #result_type_decl
+
+      use #imports_trait_path_syn as Imports;
+
+      /// Struct for implementing your `Imports` trait
+      pub struct ModuleImportsImpl;
+
+      #code
+    },
+  );
+}
+
+/// `init_imports` function which passes implementation of imports to the module,
+/// expects `Imports` trait to be in scope
+pub(crate) fn imports_code(
+  imports_trait: &ItemTrait,
+  imports_trait_path: &str,
+  consts: &[ItemConst],
+  pub_imports: bool,
+  shared_alloc: bool,
+  mode: Mode,
+) -> TokenStream2 {
+  let trait_name = &imports_trait.ident.to_string();
+  let exports_types = if pub_imports {
+    exports_types_path(mode)
+  } else {
+    quote! { crate::exports_types }
+  };
+
+  let mut imports = Vec::<TokenStream2>::new();
+
+  for item in &imports_trait.items {
+    let TraitFn {
+      ident,
+      inputs,
+      inputs_without_types,
+      output,
+      mangled_name,
+      mangled_ident: _,
+      post_ident: _,
+      post_mangled_name,
+      post_mangled_ident: _,
+      lifetimes_for,
+      lifetimes_full,
+      lifetimes_where_module: _,
+      lifetimes_module,
+      cfg_attrs,
+    } = for_each_trait_item(trait_name, item);
+
+    let panic_message = match mode {
+      Mode::BuildScript => format!(
+        "Couldn't find \"{ident}\" import\n\
+        note: make sure this module expects {imports_trait_path} trait\n\
+        note: if `relib_interface::include_imports!()` is used not in module crate, \
+        make sure (that invokes `include_imports`) is used in module crate, for example you can do so by \
+        using this syntax: `use <crate> as _;`"
+      ),
+      Mode::Macro => format!(
+        "Couldn't find \"{ident}\" import\n\
+        note: make sure this module expects {imports_trait_path} trait"
+      ),
+    };
+
+    // implementation of imports is a generic parameter when it's not defined in generated code
+    let (impl_generics, impl_fn) = match mode {
+      Mode::BuildScript => (lifetimes_full.clone(), quote! { impl_ }),
+      Mode::Macro => (
+        quote! { <#lifetimes_module ModuleImportsImpl: Imports> },
+        quote! { impl_::<ModuleImportsImpl> },
+      ),
+    };
+
+    let post_panic_message = format!(
+      r#"Failed to get "{post_mangled_name}" symbol of static function pointer from module"#
+    );
+
+    // !!! keep in sync with main and before_unload calls in relib_host crate !!!
+    let impl_code = if pub_imports {
+      let return_type = output_to_return_type!(output);
+      let return_value = ReturnValue::new(&return_type, shared_alloc);
+      let return_type = &return_value.abi_type;
+
+      // module takes the ownership of boxes with shared allocator, there is no need in post function
+      let post_init = if return_value.needs_post {
+        let free = return_value.free(quote! { return_value });
+        quote! {
+          let post_ptr: *mut #lifetimes_for extern "C" fn(return_value: #return_type)
+            = *library.get(concat!(#post_mangled_name, "\0").as_bytes()).expect(#post_panic_message);
+
+          *post_ptr = post_impl;
+
+          #[allow(clippy::extra_unused_lifetimes, clippy::needless_lifetimes)]
+          extern "C" fn post_impl #lifetimes_full (return_value: #return_type) {
+            #free
+          }
+        }
+      } else {
+        quote! {}
+      };
+
+      let return_value = return_value.encode(quote! { return_value }, &quote! {});
+
+      let Params {
+        abi_inputs,
+        decoded,
+        unmarshalled,
+        ..
+      } = Params::new(&inputs);
+
+      let ident_str = ident.to_string();
+
+      quote! {
+        // if import is not granted it's not set, so module will panic when it's called
+        if #exports_types::__is_import_granted(#trait_name, #ident_str) { unsafe {
+          #[allow(clippy::type_complexity)]
+          let ptr: *mut Option<#lifetimes_for extern "C" fn(
+            ____caller____: *const std::ffi::c_void,
+            ____success____: *mut bool,
+            #abi_inputs
+          ) -> std::mem::MaybeUninit<#return_type>>
+            = *library.get(concat!(#mangled_name, "\0").as_bytes()).expect(#panic_message);
+
+          *ptr = Some(#impl_fn);
+
+          // lifetimes of references are not used in their ABI-stable representations
+          // and elided lifetimes may be named, see `name_elided_lifetimes`
+          #[allow(clippy::extra_unused_lifetimes, clippy::needless_lifetimes)]
+          extern "C" fn impl_ #impl_generics (
+            ____caller____: *const std::ffi::c_void,
+            ____success____: *mut bool,
+            #abi_inputs
+          ) -> std::mem::MaybeUninit<#return_type> // will be initialized if function won't panic
+          {
+            /// All parameters must be Copy (except `&mut [T]` and closures), see relib caveats in the readme for more info.
+            fn ____assert_type_is_copy____(_: impl Copy) {}
+            #( ____assert_type_is_copy____( #unmarshalled ); )*
+
+            // references are converted back inside the closure since `&mut [T]` is not UnwindSafe
+            let result = std::panic::catch_unwind(move || {
+              #exports_types::__with_caller(____caller____, move || {
+                <ModuleImportsImpl as Imports>::#ident( #( #decoded ),* )
+              })
+            });
+
+            match result {
+              Ok(return_value) => {
+                unsafe {
+                  *____success____ = true;
+                }
+
+                #[allow(unused_braces, clippy::unit_arg)]
+                std::mem::MaybeUninit::new({ #return_value })
+              }
+              // ignoring content since it's handled in default panic hook of std
+              Err(_) => {
+                unsafe {
+                  *____success____ = false;
+                }
+
+                std::mem::MaybeUninit::uninit()
+              }
+            }
+          }
+
+          #post_init
+        } }
+      }
+    } else {
+      quote! {
+        unsafe {
+          let ptr: *mut #lifetimes_for extern "C" fn( #inputs ) #output
+            = *library.get(concat!(#mangled_name, "\0").as_bytes()).expect(#panic_message);
+
+          *ptr = #impl_fn;
+
+          extern "C" fn impl_ #impl_generics ( #inputs ) #output {
+            <ModuleImportsImpl as Imports>::#ident( #( #inputs_without_types )* )
+          }
+        }
+      }
+    };
+
+    // trailing semicolon makes it a statement, attributes are not allowed on expressions
+    imports.push(quote! {
+      #cfg_attrs
+      #impl_code;
+    });
+  }
+
+  let shared_alloc_check = if pub_imports && shared_alloc {
+    let marker_name = shared_alloc_marker_name(trait_name);
+    let panic_message = "Module imports are not generated with shared allocator mode\n\
+      note: make sure that module build script uses `relib_interface::module::generate_with_options` \
+      with `shared_alloc` enabled";
+
+    quote! {
+      unsafe {
+        library.get::<*const u8>(concat!(#marker_name, "\0").as_bytes()).expect(#panic_message);
+      }
+    }
+  } else {
+    quote! {}
+  };
+
+  let consts_check = consts_check(trait_name, imports_trait_path, consts, &exports_types);
+
+  // internal imports pass module id explicitly
+  let init_caller = if pub_imports {
+    let caller_symbol = caller_symbol_name(trait_name);
+    let panic_message = format!(
+      "Couldn't find caller of {imports_trait_path} imports\n\
+      note: make sure that host and module are compiled with the same relib version"
+    );
+
+    quote! {
+      unsafe {
+        let caller: *mut *const std::ffi::c_void =
+          *library.get(concat!(#caller_symbol, "\0").as_bytes()).expect(#panic_message);
+        *caller = #exports_types::__loading_caller();
+      }
+    }
+  } else {
+    quote! {}
+  };
+
+  match mode {
+    Mode::BuildScript => quote! {
+      pub fn init_imports(library: &libloading::Library) {
+        #shared_alloc_check
+        #consts_check
+        #init_caller
+
+        #( #imports )*
+      }
+    },
+    Mode::Macro => quote! {
+      /// Pass it to `relib_host::load_module` with your implementation of imports,
+      /// for example: `init_imports::<MyImports>`
+      pub fn init_imports<ModuleImportsImpl: Imports>(library: &relib_interface::exports_types::Library) {
+        #shared_alloc_check
+        #consts_check
+        #init_caller
+
+        #( #imports )*
+      }
+    },
+  }
+}
+
+/// Compares values of constants from the trait file with the values module was compiled with,
+/// mismatch is returned from `load_module` as an error
+fn consts_check(
+  trait_name: &str,
+  trait_path: &str,
+  consts: &[ItemConst],
+  exports_types: &TokenStream2,
+) -> TokenStream2 {
+  consts
+    .iter()
+    .map(|item_const| {
+      let ItemConst {
+        attrs, ident, ty, ..
+      } = item_const;
+      let cfg_attrs = cfg_attrs(attrs);
+      let symbol = const_symbol_name(trait_name, ident);
+
+      let panic_message = format!(
+        "Couldn't find \"{ident}\" constant of {trait_path} trait file in the module\n\
+        note: make sure that host and module are compiled with the same shared crate code"
+      );
+      let ident_str = ident.to_string();
+
+      quote! {
+        #cfg_attrs
+        unsafe {
+          let value: extern "C" fn() -> *const std::ffi::c_void =
+            *library.get(concat!(#symbol, "\0").as_bytes()).expect(#panic_message);
+          // SAFETY: module returns pointer to the value of the same constant
+          let value = &*value().cast::<#ty>();
+          if *value != #ident {
+            #exports_types::__const_mismatch(#trait_path, #ident_str);
+          }
+        }
+      }
+    })
+    .collect()
+}
+
+/// Host side of code generated by `#[relib_interface::exports]` and `#[relib_interface::imports]`
+/// is placed in the crate of the trait, which doesn't depend on relib_host,
+/// so it uses the same types from relib_interface
+fn exports_types_path(mode: Mode) -> TokenStream2 {
+  match mode {
+    Mode::BuildScript => quote! { relib_host::exports_types },
+    Mode::Macro => quote! { relib_interface::exports_types },
+  }
+}
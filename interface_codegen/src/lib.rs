@@ -0,0 +1,17 @@
+//! Code generation of relib_interface crate, see its documentation.
+
+// build script part is only used with "internal" or "public" features,
+// relib_interface_macro only needs the part for proc-macros
+#![cfg_attr(not(any(feature = "internal", feature = "public")), allow(dead_code))]
+
+pub mod host;
+pub mod module;
+mod shared;
+mod return_value;
+mod marshal;
+mod callback;
+mod dyn_trait;
+mod options;
+pub use options::GenerateOptions;
+mod macros;
+pub use macros::{dyn_trait, exports, imports};
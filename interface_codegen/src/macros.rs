@@ -0,0 +1,277 @@
+use {
+  crate::{
+    dyn_trait::dyn_trait_code,
+    host, module,
+    return_value::result_type_decl,
+    shared::{Mode, table_symbol_name},
+  },
+  proc_macro2::TokenStream as TokenStream2,
+  quote::{format_ident, quote},
+  syn::{Ident, Item, ItemImpl, ItemTrait, parse_quote},
+};
+
+/// Takes exports trait and generates code for both host and module next to it,
+/// or takes implementation of exports trait in the module and exports it.
+/// See `relib_interface_macro` for proc-macro.
+///
+/// # Example
+/// ```ignore
+/// // input:
+/// pub trait Exports {
+///   fn foo() -> i32;
+/// }
+///
+/// // output:
+/// pub trait Exports {
+///   fn foo() -> i32;
+///
+///   #[doc(hidden)]
+///   fn __relib_exports_table() -> *const std::ffi::c_void where Self: Sized {
+///     // ...
+///   }
+/// }
+///
+/// pub mod gen_exports {
+///   use super::*;
+///
+///   // module side, exported by `#[relib_interface::exports]` on impl block
+///   pub struct ModuleExportsTable {
+///     // ...
+///   }
+///
+///   // host side
+///   pub struct ModuleExports {
+///     // ...
+///   }
+/// }
+/// ```
+pub fn exports(args: TokenStream2, input: TokenStream2) -> TokenStream2 {
+  let input: Item = match syn::parse2(input) {
+    Ok(input) => input,
+    Err(e) => return e.to_compile_error(),
+  };
+
+  match input {
+    Item::Trait(trait_) => match mod_name(args, "gen_exports") {
+      Ok(mod_name) => exports_trait(trait_, mod_name),
+      Err(e) => e.to_compile_error(),
+    },
+    Item::Impl(impl_) => {
+      if !args.is_empty() {
+        return syn::Error::new_spanned(args, "arguments are only supported on exports trait")
+          .to_compile_error();
+      }
+      exports_impl(impl_)
+    }
+    input => syn::Error::new_spanned(input, "expected exports trait or implementation of it")
+      .to_compile_error(),
+  }
+}
+
+/// Takes imports trait and generates code for both host and module next to it.
+/// See `relib_interface_macro` for proc-macro.
+///
+/// # Example
+/// ```ignore
+/// // input:
+/// pub trait Imports {
+///   fn foo() -> i32;
+/// }
+///
+/// // output:
+/// pub trait Imports {
+///   fn foo() -> i32;
+/// }
+///
+/// pub mod gen_imports {
+///   use super::*;
+///
+///   // module side
+///   pub unsafe fn foo() -> i32 {
+///     // ...
+///   }
+///
+///   // host side
+///   pub fn init_imports<ModuleImportsImpl: Imports>(library: &relib_interface::exports_types::Library) {
+///     // ...
+///   }
+/// }
+/// ```
+pub fn imports(args: TokenStream2, input: TokenStream2) -> TokenStream2 {
+  let trait_: ItemTrait = match syn::parse2(input) {
+    Ok(input) => input,
+    Err(e) => return e.to_compile_error(),
+  };
+
+  let mod_name = match mod_name(args, "gen_imports") {
+    Ok(mod_name) => mod_name,
+    Err(e) => return e.to_compile_error(),
+  };
+
+  let trait_ident = &trait_.ident;
+  let trait_name = trait_ident.to_string();
+  let vis = &trait_.vis;
+
+  let module_code = module::imports_code(&trait_, true, false);
+  let host_code = host::imports_code(&trait_, &trait_name, &[], true, false, Mode::Macro);
+
+  let result_type_decl = result_type_decl();
+
+  let doc = format!("Code generated by `#[relib_interface::imports]` for [`{trait_name}`] trait");
+
+  quote! {
+    #trait_
+
+    #[doc = #doc]
+    #vis mod #mod_name {
+      #[allow(unused_imports)]
+      use super::*;
+      #[allow(unused_imports)]
+      use super::#trait_ident as Imports;
+
+      #result_type_decl
+
+      #module_code
+
+      relib_interface::__host_side! {
+        #host_code
+      }
+    }
+  }
+}
+
+/// Takes a trait and generates code which allows to use it as `relib_interface::ModuleBox<dyn Trait>`
+/// (see `dyn_trait_code`).
+/// See `relib_interface_macro` for proc-macro.
+///
+/// # Example
+/// ```ignore
+/// // input:
+/// pub trait System {
+///   fn update(&mut self, dt: f32);
+/// }
+///
+/// // output:
+/// pub trait System {
+///   fn update(&mut self, dt: f32);
+/// }
+///
+/// const _: () = {
+///   // module side
+///   unsafe impl<T: System + 'static> relib_interface::VTableFor<T> for dyn System {
+///     // ...
+///   }
+///
+///   // host side
+///   impl System for relib_interface::ModuleBox<dyn System> {
+///     // ...
+///   }
+/// };
+/// ```
+pub fn dyn_trait(args: TokenStream2, input: TokenStream2) -> TokenStream2 {
+  if !args.is_empty() {
+    return syn::Error::new_spanned(
+      args,
+      "`#[relib_interface::dyn_trait]` doesn't take arguments",
+    )
+    .to_compile_error();
+  }
+
+  let trait_: ItemTrait = match syn::parse2(input) {
+    Ok(input) => input,
+    Err(e) => return e.to_compile_error(),
+  };
+
+  match dyn_trait_code(&trait_) {
+    Ok(code) => quote! {
+      #trait_
+
+      #code
+    },
+    Err(e) => e.to_compile_error(),
+  }
+}
+
+fn exports_trait(mut trait_: ItemTrait, mod_name: Ident) -> TokenStream2 {
+  let trait_ident = trait_.ident.clone();
+  let trait_name = trait_ident.to_string();
+  let vis = trait_.vis.clone();
+
+  let module_code = module::exports_code(&trait_, true, false, Mode::Macro);
+  let host_code = host::exports_code(&trait_, &trait_name, &[], true, false, Mode::Macro);
+
+  // added after the code is generated since it must not be exported itself
+  trait_.items.push(parse_quote! {
+    #[doc(hidden)]
+    fn __relib_exports_table() -> *const ::std::ffi::c_void
+    where
+      Self: Sized,
+    {
+      #mod_name::module_exports_table::<Self>()
+    }
+  });
+
+  let result_type_decl = result_type_decl();
+
+  let doc = format!("Code generated by `#[relib_interface::exports]` for [`{trait_name}`] trait");
+
+  quote! {
+    #trait_
+
+    #[doc = #doc]
+    #vis mod #mod_name {
+      #[allow(unused_imports)]
+      use super::*;
+      use super::#trait_ident as Exports;
+
+      #result_type_decl
+
+      #module_code
+
+      relib_interface::__host_side! {
+        #host_code
+      }
+    }
+  }
+}
+
+fn exports_impl(impl_: ItemImpl) -> TokenStream2 {
+  let Some((None, trait_path, _)) = &impl_.trait_ else {
+    return syn::Error::new_spanned(&impl_.self_ty, "expected implementation of exports trait")
+      .to_compile_error();
+  };
+
+  if !impl_.generics.params.is_empty() {
+    return syn::Error::new_spanned(
+      &impl_.generics,
+      "implementation of exports can't be generic",
+    )
+    .to_compile_error();
+  }
+
+  let Some(trait_name) = trait_path.segments.last() else {
+    return syn::Error::new_spanned(trait_path, "expected trait path").to_compile_error();
+  };
+  // trait must be referenced by its original name since symbol of exports is based on it
+  let symbol = table_symbol_name(&trait_name.ident.to_string());
+  let self_ty = &impl_.self_ty;
+
+  quote! {
+    #impl_
+
+    const _: () = {
+      #[unsafe(export_name = #symbol)]
+      extern "C" fn module_exports_table() -> *const ::std::ffi::c_void {
+        <#self_ty as #trait_path>::__relib_exports_table()
+      }
+    };
+  }
+}
+
+fn mod_name(args: TokenStream2, default: &str) -> syn::Result<Ident> {
+  if args.is_empty() {
+    Ok(format_ident!("{default}"))
+  } else {
+    syn::parse2(args)
+  }
+}
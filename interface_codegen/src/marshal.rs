@@ -0,0 +1,151 @@
+use {
+  crate::{callback::Callback, dyn_trait::is_module_box},
+  proc_macro2::TokenStream as TokenStream2,
+  quote::{ToTokens, quote},
+  syn::{FnArg, GenericArgument, PathArguments, Token, Type, punctuated::Punctuated},
+};
+
+/// References which are not ABI-stable (`&str` and slices), generated code converts them
+/// to `relib_interface::__Str` and `relib_interface::__RawSlice` on one side and back on the other
+pub enum Marshal {
+  Str,
+  Slice(TokenStream2),
+  SliceMut(TokenStream2),
+}
+
+impl Marshal {
+  pub fn new(type_: &Type) -> Option<Self> {
+    let Type::Reference(reference) = type_ else {
+      return None;
+    };
+
+    match (&*reference.elem, reference.mutability.is_some()) {
+      (Type::Path(path), false) if path.qself.is_none() && path.path.is_ident("str") => {
+        Some(Self::Str)
+      }
+      (Type::Slice(slice), false) => Some(Self::Slice(slice.elem.to_token_stream())),
+      (Type::Slice(slice), true) => Some(Self::SliceMut(slice.elem.to_token_stream())),
+      _ => None,
+    }
+  }
+
+  pub fn abi_type(&self) -> TokenStream2 {
+    match self {
+      Self::Str => quote! { relib_interface::__Str },
+      Self::Slice(elem) | Self::SliceMut(elem) => quote! { relib_interface::__RawSlice<#elem> },
+    }
+  }
+
+  pub fn encode(&self, value: &TokenStream2) -> TokenStream2 {
+    let abi_type = self.abi_type();
+    quote! { <#abi_type>::from(#value) }
+  }
+
+  pub fn decode(&self, value: &TokenStream2) -> TokenStream2 {
+    let into_ref = match self {
+      Self::Str => quote! { into_str_unchecked },
+      Self::Slice(_) => quote! { into_slice },
+      Self::SliceMut(_) => quote! { into_slice_mut },
+    };
+
+    // value is bound to a variable first since it can be an expression with its own unsafe block
+    quote! {{
+      let abi_value = #value;
+      // SAFETY: the other side created it from the reference of the same type
+      unsafe { abi_value.#into_ref() }
+    }}
+  }
+}
+
+/// `Option<&T>` and `Option<&mut T>` (where `T` is sized) are ABI-stable thanks to null pointer optimization,
+/// so they are passed as is (`&str` and slices are not sized, so they are not)
+pub fn is_option_ref(type_: &Type) -> bool {
+  let Type::Path(path) = type_ else {
+    return false;
+  };
+  let Some(segment) = path.path.segments.last() else {
+    return false;
+  };
+  if segment.ident != "Option" {
+    return false;
+  }
+  let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+    return false;
+  };
+
+  match arguments.args.first() {
+    Some(GenericArgument::Type(Type::Reference(reference))) if arguments.args.len() == 1 => {
+      let reference = Type::Reference(reference.clone());
+      Marshal::new(&reference).is_none()
+    }
+    _ => false,
+  }
+}
+
+/// Parameters of the function in generated `extern "C"` functions
+pub struct Params {
+  /// Parameters with ABI-stable types
+  pub abi_inputs: TokenStream2,
+  /// Statements which must be executed by the calling side before arguments are encoded
+  pub prelude: Vec<TokenStream2>,
+  /// Arguments which are passed to `extern "C"` function by the calling side
+  pub encoded: Vec<TokenStream2>,
+  /// Arguments which are passed to the implementation by the called side
+  pub decoded: Vec<TokenStream2>,
+  /// Arguments which are passed as is (they must be Copy, see relib caveats in the readme),
+  /// `&mut [T]`, closures and `ModuleBox` are not
+  pub unmarshalled: Vec<TokenStream2>,
+}
+
+impl Params {
+  pub fn new(inputs: &Punctuated<FnArg, Token![,]>) -> Self {
+    let mut abi_inputs = TokenStream2::new();
+    let mut prelude = Vec::new();
+    let mut encoded = Vec::new();
+    let mut decoded = Vec::new();
+    let mut unmarshalled = Vec::new();
+
+    for arg in inputs {
+      let FnArg::Typed(arg) = arg else {
+        unreachable!();
+      };
+      let pat = arg.pat.to_token_stream();
+
+      if let Some(callback) = Callback::new(&arg.ty) {
+        let abi_type = callback.abi_type();
+        abi_inputs.extend(quote! { #pat: #abi_type, });
+        prelude.push(callback.prelude(&pat));
+        encoded.push(callback.encode(&pat));
+        decoded.push(callback.decode(&pat));
+        continue;
+      }
+
+      match Marshal::new(&arg.ty) {
+        Some(marshal) => {
+          let abi_type = marshal.abi_type();
+          abi_inputs.extend(quote! { #pat: #abi_type, });
+          encoded.push(marshal.encode(&pat));
+          decoded.push(marshal.decode(&pat));
+        }
+        None => {
+          let type_ = &arg.ty;
+          abi_inputs.extend(quote! { #pat: #type_, });
+          encoded.push(pat.clone());
+          decoded.push(pat.clone());
+          // ownership of ModuleBox is moved to the other side
+          if !is_module_box(type_) {
+            unmarshalled.push(pat);
+          }
+        }
+      }
+    }
+
+    Self {
+      abi_inputs,
+      prelude,
+      encoded,
+      decoded,
+      unmarshalled,
+    }
+  }
+}
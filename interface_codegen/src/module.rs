@@ -0,0 +1,794 @@
+use {
+  crate::{
+    marshal::Params,
+    return_value::{ReturnValue, result_type_decl},
+    shared::{
+      Mode, SAFETY_DOC, TraitFile, TraitFn, caller_symbol_name, cfg_attrs, const_symbol_name,
+      extract_trait_name_from_path, for_each_trait_item, out_dir_file_name, parse_trait_file,
+      pass_out_dir_file_name_to_crate_code, shared_alloc_marker_name, signature_symbol_name,
+      write_code_to_file,
+    },
+  },
+  proc_macro2::TokenStream as TokenStream2,
+  quote::{format_ident, quote},
+  relib_internal_shared::output_to_return_type,
+  syn::{FnArg, ItemConst, ItemTrait, TraitItem},
+};
+
+#[cfg(feature = "internal")]
+pub fn generate_internal(
+  exports_file_content: &'static str,
+  exports_trait_path: &str,
+  imports_file_content: &'static str,
+  imports_trait_path: &str,
+) {
+  generate_exports_(
+    exports_file_content,
+    exports_trait_path,
+    false,
+    "internal_generated_module",
+    false,
+  );
+  generate_imports_(
+    imports_file_content,
+    imports_trait_path,
+    false,
+    "internal_generated_module",
+    false,
+  );
+}
+
+/// in the OUT_DIR which you can include using
+/// `relib_interface::include_exports!();` and `relib_interface::include_exports!();`
+/// in your `lib.rs` or `main.rs`
+/// and then use `ModuleExportsImpl` struct to implement your `Exports` trait:
+/// ```
+/// impl Exports for ModuleExportsImpl {
+///   // ...
+/// }
+/// ```
+#[cfg(feature = "public")]
+pub fn generate(
+  exports_file_content: &'static str,
+  exports_trait_path: &str,
+  imports_file_content: &'static str,
+  imports_trait_path: &str,
+) {
+  generate_exports_(
+    exports_file_content,
+    exports_trait_path,
+    true,
+    "generated_module",
+    false,
+  );
+  generate_imports_(
+    imports_file_content,
+    imports_trait_path,
+    true,
+    "generated_module",
+    false,
+  );
+}
+
+/// in the OUT_DIR which you can include using
+/// `relib_interface::include_exports!(gen_exports, <prefix>);` and `relib_interface::include_imports!(gen_imports, <prefix>);`
+/// in your `lib.rs` or `main.rs`
+/// and then use `ModuleExportsImpl` struct to implement your `Exports` trait:
+/// ```
+/// impl Exports for ModuleExportsImpl {
+///   // ...
+/// }
+/// ```
+#[cfg(feature = "public")]
+pub fn generate_with_prefix(
+  prefix: &str,
+  exports_file_content: &'static str,
+  exports_trait_path: &str,
+  imports_file_content: &'static str,
+  imports_trait_path: &str,
+) {
+  generate_exports_(
+    exports_file_content,
+    exports_trait_path,
+    true,
+    prefix,
+    false,
+  );
+  generate_imports_(
+    imports_file_content,
+    imports_trait_path,
+    true,
+    prefix,
+    false,
+  );
+}
+
+/// Same as [`generate_with_prefix`] but also allows to configure code generation,
+/// for example, to enable shared allocator mode (see [`GenerateOptions`](crate::GenerateOptions)).
+#[cfg(feature = "public")]
+pub fn generate_with_options(
+  options: &crate::GenerateOptions,
+  exports_file_content: &'static str,
+  exports_trait_path: &str,
+  imports_file_content: &'static str,
+  imports_trait_path: &str,
+) {
+  let crate::GenerateOptions {
+    prefix,
+    shared_alloc,
+  } = options;
+
+  generate_exports_(
+    exports_file_content,
+    exports_trait_path,
+    true,
+    prefix,
+    *shared_alloc,
+  );
+  generate_imports_(
+    imports_file_content,
+    imports_trait_path,
+    true,
+    prefix,
+    *shared_alloc,
+  );
+}
+
+#[cfg(feature = "public")]
+pub fn generate_exports(exports_file_content: &'static str, exports_trait_path: &str) {
+  generate_exports_(
+    exports_file_content,
+    exports_trait_path,
+    true,
+    "generated_module",
+    false,
+  );
+}
+
+#[cfg(feature = "public")]
+pub fn generate_imports(imports_file_content: &'static str, imports_trait_path: &str) {
+  generate_imports_(
+    imports_file_content,
+    imports_trait_path,
+    true,
+    "generated_module",
+    false,
+  );
+}
+
+#[cfg(feature = "public")]
+pub fn generate_exports_with_prefix(
+  prefix: &str,
+  exports_file_content: &'static str,
+  exports_trait_path: &str,
+) {
+  generate_exports_(
+    exports_file_content,
+    exports_trait_path,
+    true,
+    prefix,
+    false,
+  );
+}
+
+#[cfg(feature = "public")]
+pub fn generate_imports_with_prefix(
+  prefix: &str,
+  imports_file_content: &'static str,
+  imports_trait_path: &str,
+) {
+  generate_imports_(
+    imports_file_content,
+    imports_trait_path,
+    true,
+    prefix,
+    false,
+  );
+}
+
+fn generate_exports_(
+  exports_file_content: &'static str,
+  exports_trait_path: &str,
+  pub_exports: bool,
+  prefix: &str,
+  shared_alloc: bool,
+) {
+  pass_out_dir_file_name_to_crate_code(prefix, "exports");
+
+  let trait_name = extract_trait_name_from_path(exports_trait_path);
+
+  let TraitFile {
+    trait_: exports_trait,
+    items,
+    consts,
+  } = parse_trait_file(trait_name, exports_file_content, exports_trait_path);
+
+  let exports_trait_path: syn::Path =
+    syn::parse_str(exports_trait_path).expect("Failed to parse exports_trait_path as syn::Path");
+
+  let code = exports_code(&exports_trait, pub_exports, shared_alloc, Mode::BuildScript);
+  let consts_code = consts_code(trait_name, &consts);
+  let result_type_decl = result_type_decl();
+
+  write_code_to_file(
+    &out_dir_file_name(prefix, "exports"),
+    quote! {
+      #items
+
+      #consts_code
+
+      #result_type_decl
+
+      use #exports_trait_path as Exports;
+
+      /// Struct for implementing your `Exports` trait
+      pub struct ModuleExportsImpl;
+
+      #code
+    },
+  );
+}
+
+/// Exported functions which call implementation of `Exports` trait (it must be in scope),
+/// with [`Mode::Macro`] they are collected into `ModuleExportsTable` instead of being exported one by one
+pub(crate) fn exports_code(
+  exports_trait: &ItemTrait,
+  pub_exports: bool,
+  shared_alloc: bool,
+  mode: Mode,
+) -> TokenStream2 {
+  let trait_name = &exports_trait.ident.to_string();
+
+  let mut exports = Vec::<TokenStream2>::new();
+  let mut table_decls = Vec::<TokenStream2>::new();
+  let mut table_inits = Vec::<TokenStream2>::new();
+
+  for item in &exports_trait.items {
+    let TraitFn {
+      ident,
+      inputs,
+      inputs_without_types,
+      output,
+      mangled_name,
+      mangled_ident,
+      post_ident,
+      post_mangled_name: _,
+      post_mangled_ident,
+      lifetimes_for,
+      lifetimes_full,
+      lifetimes_where_module: _,
+      lifetimes_module,
+      cfg_attrs,
+    } = for_each_trait_item(trait_name, item);
+
+    // with Mode::Macro this code is placed in the crate of the trait, so implementation
+    // of exports is a generic parameter and functions are exported through the table
+    let (export_attrs, export_generics) = match mode {
+      Mode::BuildScript => (
+        quote! {
+          #[unsafe(no_mangle)]
+          pub
+        },
+        lifetimes_full.clone(),
+      ),
+      Mode::Macro => (
+        quote! { #[allow(non_snake_case)] },
+        quote! { <#lifetimes_module ModuleExportsImpl: Exports> },
+      ),
+    };
+
+    // !!! keep in sync with main and before_unload calls in relib_host crate !!!
+    let code = if pub_exports {
+      let return_type = output_to_return_type!(output);
+      let return_value = ReturnValue::new(&return_type, shared_alloc);
+      let return_type = &return_value.abi_type;
+
+      // host takes the ownership of boxes with shared allocator, there is no need in post function
+      let post_export = if return_value.needs_post {
+        table_decls.push(quote! {
+          #cfg_attrs
+          #post_ident: #lifetimes_for extern "C" fn(#return_type),
+        });
+        table_inits.push(quote! {
+          #cfg_attrs
+          #post_ident: #post_mangled_ident,
+        });
+
+        let free = return_value.free(quote! { return_value });
+        quote! {
+          #cfg_attrs
+          #[allow(clippy::extra_unused_lifetimes, clippy::needless_lifetimes)]
+          #export_attrs
+          extern "C" fn #post_mangled_ident #lifetimes_full (
+            return_value: #return_type
+          ) {
+            #free
+          }
+        }
+      } else {
+        quote! {}
+      };
+
+      let return_value = return_value.encode(
+        quote! { return_value },
+        &quote! { relib_module::__internal::release_transferred(&boxed); },
+      );
+
+      let Params {
+        abi_inputs,
+        decoded,
+        ..
+      } = Params::new(&inputs);
+
+      table_decls.push(quote! {
+        #cfg_attrs
+        #ident: #lifetimes_for extern "C" fn(
+          ____success____: *mut bool,
+          #abi_inputs
+        ) -> std::mem::MaybeUninit<#return_type>,
+      });
+      table_inits.push(quote! {
+        #cfg_attrs
+        #ident: #mangled_ident::<ModuleExportsImpl>,
+      });
+
+      quote! {
+        #cfg_attrs
+        // lifetimes of references are not used in their ABI-stable representations
+        // and elided lifetimes may be named, see `name_elided_lifetimes`
+        #[allow(clippy::extra_unused_lifetimes, clippy::needless_lifetimes)]
+        #export_attrs
+        extern "C" fn #mangled_ident #export_generics (
+          ____success____: *mut bool,
+          #abi_inputs
+        ) -> std::mem::MaybeUninit<#return_type> // will be initialized if function won't panic
+        {
+          // references are converted back inside the closure since `&mut [T]` is not UnwindSafe
+          let result = std::panic::catch_unwind(move || {
+            <ModuleExportsImpl as Exports>::#ident( #( #decoded ),* )
+          });
+
+          match result {
+            Ok(return_value) => {
+              unsafe {
+                *____success____ = true;
+              }
+
+              #[allow(unused_braces, clippy::unit_arg)]
+              std::mem::MaybeUninit::new({ #return_value })
+            }
+            // ignoring content since it's printed by std
+            Err(_) => {
+              unsafe {
+                *____success____ = false;
+              }
+
+              std::mem::MaybeUninit::uninit()
+            }
+          }
+        }
+
+        #post_export
+      }
+    } else {
+      quote! {
+        #cfg_attrs
+        #[unsafe(export_name = #mangled_name)]
+        pub extern "C" fn #ident #lifetimes_full ( #inputs ) #output {
+          <ModuleExportsImpl as Exports>::#ident( #( #inputs_without_types )* )
+        }
+      }
+    };
+
+    exports.push(code);
+  }
+
+  let shared_alloc_marker = shared_alloc_marker(trait_name, pub_exports && shared_alloc);
+  let signature = signature(exports_trait, pub_exports);
+
+  let table = match mode {
+    Mode::BuildScript => quote! {},
+    Mode::Macro => quote! {
+      /// Exports of the module, host gets it using the symbol exported by `#[relib_interface::exports]` impl
+      #[doc(hidden)]
+      #[repr(C)]
+      #[derive(Clone, Copy)]
+      #[allow(non_snake_case)]
+      pub struct ModuleExportsTable {
+        #( #table_decls )*
+      }
+
+      #[doc(hidden)]
+      pub fn module_exports_table<ModuleExportsImpl: Exports>() -> *const std::ffi::c_void {
+        let table: &'static ModuleExportsTable = const {
+          &ModuleExportsTable {
+            #( #table_inits )*
+          }
+        };
+        (table as *const ModuleExportsTable).cast()
+      }
+    },
+  };
+
+  quote! {
+    #shared_alloc_marker
+
+    #signature
+
+    #( #exports )*
+
+    #table
+  }
+}
+
+fn generate_imports_(
+  imports_file_content: &'static str,
+  imports_trait_path: &str,
+  pub_imports: bool,
+  prefix: &str,
+  shared_alloc: bool,
+) {
+  pass_out_dir_file_name_to_crate_code(prefix, "imports");
+
+  let trait_name = extract_trait_name_from_path(imports_trait_path);
+  let TraitFile {
+    trait_: imports_trait,
+    items,
+    consts,
+  } = parse_trait_file(trait_name, imports_file_content, imports_trait_path);
+
+  let code = imports_code(&imports_trait, pub_imports, shared_alloc);
+  let consts_code = consts_code(trait_name, &consts);
+  let result_type_decl = result_type_decl();
+
+  write_code_to_file(
+    &out_dir_file_name(prefix, "imports"),
+    quote! {
+      #items
+
+      #consts_code
+
+      #result_type_decl
+
+      #code
+    },
+  );
+}
+
+/// Exported functions which return pointers to values of constants from the trait file,
+/// host checks that they are equal to its own values when module is loaded
+fn consts_code(trait_name: &str, consts: &[ItemConst]) -> TokenStream2 {
+  consts
+    .iter()
+    .map(|item_const| {
+      let ItemConst {
+        attrs, ident, ty, ..
+      } = item_const;
+      let cfg_attrs = cfg_attrs(attrs);
+      let symbol_ident = format_ident!("{}", const_symbol_name(trait_name, ident));
+
+      quote! {
+        #cfg_attrs
+        #[unsafe(no_mangle)]
+        pub extern "C" fn #symbol_ident() -> *const std::ffi::c_void {
+          let value: &'static #ty = const { &#ident };
+          (value as *const #ty).cast()
+        }
+      }
+    })
+    .collect()
+}
+
+/// Functions for calling imports, which are implemented by the host
+pub(crate) fn imports_code(
+  imports_trait: &ItemTrait,
+  pub_imports: bool,
+  shared_alloc: bool,
+) -> TokenStream2 {
+  let trait_name = &imports_trait.ident.to_string();
+  let caller_ident = format_ident!("{}", caller_symbol_name(trait_name));
+
+  let mut imports = Vec::<TokenStream2>::new();
+
+  for item in &imports_trait.items {
+    let TraitFn {
+      ident,
+      inputs,
+      inputs_without_types,
+      output,
+      mangled_name: _,
+      mangled_ident,
+      post_ident: _,
+      post_mangled_name: _,
+      post_mangled_ident,
+      lifetimes_for,
+      lifetimes_full,
+      lifetimes_where_module: _,
+      lifetimes_module: _,
+      cfg_attrs,
+    } = for_each_trait_item(trait_name, item);
+
+    let placeholder_inputs: TokenStream2 = inputs
+      .iter()
+      .map(|arg| {
+        let FnArg::Typed(arg) = arg else {
+          unreachable!();
+        };
+
+        let ty = &arg.ty;
+        quote! { _: #ty , }
+      })
+      .collect();
+
+    // some internal imports are only used with some features of relib_module
+    // (cfg with features can't be used for them since it would be evaluated in relib_host too)
+    let allow_unused = if pub_imports {
+      quote! {}
+    } else {
+      quote! { #[allow(dead_code)] }
+    };
+
+    let function_sig = quote! {
+      #cfg_attrs
+      #allow_unused
+      // elided lifetimes may be named, see `name_elided_lifetimes`
+      #[allow(clippy::needless_lifetimes)]
+      #[doc = #SAFETY_DOC]
+      pub unsafe fn #ident #lifetimes_full ( #inputs ) #output
+    };
+
+    let function_static_decl = quote! {
+      #[allow(non_upper_case_globals, clippy::type_complexity)]
+      #[unsafe(no_mangle)]
+      static mut #mangled_ident
+    };
+
+    let suppress_lints_for_return_value = quote! {
+      #[allow(unused_variables, clippy::let_unit_value, clippy::diverging_sub_expression)]
+    };
+
+    // !!! keep in sync with main and before_unload calls in relib_host crate !!!
+    let function_body = if pub_imports {
+      let return_type = output_to_return_type!(output);
+      let return_value = ReturnValue::new(&return_type, shared_alloc);
+      let return_type = &return_value.abi_type;
+
+      // SAFETY: function returned true so we are allowed to read the return value
+      let read_return_value = quote! { unsafe { return_value.assume_init() } };
+
+      // host moves the ownership of boxes to this module with shared allocator (its global allocator
+      // is also global allocator of this module), there is no need in post function
+      let (post_fn_decl, read_return_value) = if return_value.needs_post {
+        let decoded = return_value.decode(quote! { return_value }, &quote! {});
+        (
+          quote! {
+            #[allow(non_upper_case_globals)]
+            #[unsafe(no_mangle)]
+            static mut #post_mangled_ident: #lifetimes_for extern "C" fn(return_value: #return_type) = ____post_placeholder____;
+
+            #[allow(clippy::extra_unused_lifetimes, clippy::needless_lifetimes)]
+            extern "C" fn ____post_placeholder____ #lifetimes_full (_: #return_type) {
+              unreachable!();
+            }
+          },
+          quote! {
+            let return_value = #read_return_value;
+            let decoded = #decoded;
+
+            // SAFETY: user of relib_interface crate should guarantee that
+            // module and host binaries are compiled with the same shared crate code
+            unsafe {
+              #post_mangled_ident(return_value);
+            }
+
+            decoded
+          },
+        )
+      } else {
+        (
+          quote! {},
+          return_value.decode(
+            read_return_value,
+            &quote! { relib_module::__internal::adopt_transferred(unsafe { &*ptr }); },
+          ),
+        )
+      };
+
+      let not_granted_message = format!(
+        "Import \"{ident}\" is not available in this module\n\
+        note: it's not granted by the host (see `relib_host::LoadOptions::grant_imports`) \
+        or imports are not initialized"
+      );
+
+      let Params {
+        abi_inputs,
+        prelude,
+        encoded,
+        ..
+      } = Params::new(&inputs);
+
+      quote! {
+        // host doesn't set it if import is not granted to this module
+        #function_static_decl: Option<#lifetimes_for extern "C" fn(
+          ____caller____: *const std::ffi::c_void,
+          ____success____: *mut bool,
+          #abi_inputs
+        ) -> std::mem::MaybeUninit<#return_type>> = None;
+
+        #post_fn_decl
+
+        // panic unwinds to the export which called this import, so host will get None from it
+        let Some(import) = (unsafe { #mangled_ident }) else {
+          panic!("{}", #not_granted_message);
+        };
+
+        #( #prelude )*
+        let mut ____success____ = std::mem::MaybeUninit::<bool>::uninit();
+
+        // can be left set if host caught panic of the closure itself
+        relib_interface::__take_callback_panicked();
+
+        #suppress_lints_for_return_value
+        let return_value = import(
+          unsafe { #caller_ident },
+          ____success____.as_mut_ptr(),
+          #( #encoded ),*
+        );
+
+        // SAFETY: this bool is guaranteed to be initialized by the host
+        if !unsafe{ ____success____.assume_init() } {
+          // host panicked because closure of the module passed to it panicked,
+          // so the panic is raised again and unwinds to the export which called this import
+          if relib_interface::__take_callback_panicked() {
+            panic!("Closure passed to import {:?} panicked", stringify!(#ident));
+          }
+
+          // TODO: expose unrecoverable helper in relib_module::__internal and use it here?
+          eprintln!("[relib] host panicked while executing import {:?} of module, aborting", stringify!(#ident));
+          std::process::abort();
+        }
+
+        #read_return_value
+      }
+    } else {
+      quote! {
+        #function_static_decl: #lifetimes_for extern "C" fn( #inputs ) #output = placeholder;
+
+        extern "C" fn placeholder #lifetimes_full ( #placeholder_inputs ) #output {
+          unreachable!();
+        }
+
+        unsafe {
+          #mangled_ident( #( #inputs_without_types )* )
+        }
+      }
+    };
+
+    let full_function = quote! {
+      #function_sig {
+        #function_body
+      }
+    };
+
+    imports.push(full_function);
+  }
+
+  let shared_alloc_marker = shared_alloc_marker(trait_name, pub_imports && shared_alloc);
+  let signature = signature(imports_trait, pub_imports);
+
+  // internal imports pass module id explicitly
+  let caller_decl = if pub_imports {
+    quote! {
+      #[allow(non_upper_case_globals)]
+      #[unsafe(no_mangle)]
+      static mut #caller_ident: *const std::ffi::c_void = std::ptr::null();
+    }
+  } else {
+    quote! {}
+  };
+
+  quote! {
+    #shared_alloc_marker
+
+    #signature
+
+    #caller_decl
+
+    #( #imports )*
+  }
+}
+
+fn shared_alloc_marker(trait_name: &str, shared_alloc: bool) -> TokenStream2 {
+  if !shared_alloc {
+    return quote! {};
+  }
+
+  let marker_ident = format_ident!("{}", shared_alloc_marker_name(trait_name));
+  quote! {
+    #[allow(non_upper_case_globals)]
+    #[unsafe(no_mangle)]
+    pub static #marker_ident: u8 = 0;
+  }
+}
+
+/// Signatures of the trait functions (one per line, with cfg attributes), see `signature_symbol_name`.
+/// Internal interfaces are always the same for the same relib version, so they don't need it
+fn signature(trait_: &ItemTrait, pub_interface: bool) -> TokenStream2 {
+  if !pub_interface {
+    return quote! {};
+  }
+
+  let trait_name = trait_.ident.to_string();
+  let signature = trait_
+    .items
+    .iter()
+    .filter_map(|item| {
+      let TraitItem::Fn(fn_) = item else {
+        return None;
+      };
+      let cfg_attrs = cfg_attrs(&fn_.attrs);
+      let sig = &fn_.sig;
+      Some(normalize_signature(&quote! { #cfg_attrs #sig }.to_string()))
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+
+  let mut bytes = signature.into_bytes();
+  bytes.push(0);
+  let len = bytes.len();
+  let bytes = proc_macro2::Literal::byte_string(&bytes);
+  let signature_ident = format_ident!("{}", signature_symbol_name(&trait_name));
+
+  quote! {
+    #[allow(non_upper_case_globals)]
+    #[unsafe(no_mangle)]
+    pub static #signature_ident: [u8; #len] = *#bytes;
+  }
+}
+
+/// Spacing of stringified tokens depends on whether they come from the proc macro or the build script,
+/// so it's normalized to make signatures comparable: `fn f<'a>(a: &'a str, b: Vec<u8>) -> u8`
+fn normalize_signature(signature: &str) -> String {
+  let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+  let mut normalized = String::with_capacity(signature.len());
+  let mut chars = signature.chars().peekable();
+  while let Some(c) = chars.next() {
+    if c.is_whitespace() {
+      while chars.next_if(|c| c.is_whitespace()).is_some() {}
+      let next = chars.peek().copied().unwrap_or(' ');
+      // `mut u8`, `mut [u8]` and `#[cfg(..)] fn`
+      let separates_words = (normalized.ends_with(is_word) && (is_word(next) || next == '['))
+        || (normalized.ends_with(']') && is_word(next));
+      if separates_words {
+        normalized.push(' ');
+      }
+      continue;
+    }
+
+    match c {
+      ',' | ';' => {
+        normalized.push(c);
+        normalized.push(' ');
+      }
+      '+' | '=' => {
+        normalized.push(' ');
+        normalized.push(c);
+        normalized.push(' ');
+      }
+      ':' if chars.peek() == Some(&':') => {
+        chars.next();
+        normalized.push_str("::");
+      }
+      ':' => normalized.push_str(": "),
+      '-' if chars.peek() == Some(&'>') => {
+        chars.next();
+        normalized.push_str(" -> ");
+      }
+      _ => normalized.push(c),
+    }
+  }
+
+  // separators pushed with trailing space may be followed by skipped whitespace
+  normalized.replace("  ", " ")
+}
@@ -0,0 +1,39 @@
+/// Options of `relib_interface::host::generate_with_options` and `relib_interface::module::generate_with_options`.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct GenerateOptions {
+  /// Prefix of generated files, see `generate_with_prefix`.
+  ///
+  /// Default is `"generated_module"`.
+  pub prefix: String,
+
+  /// Move non-`Copy` return values between host and module without cloning them.
+  ///
+  /// Requires module to use global allocator of the host ("global_host_alloc" feature of relib_module)
+  /// and return types to implement `relib_interface::Transfer`,
+  /// must be enabled in both host and module build scripts.
+  ///
+  /// Default is `false`.
+  pub shared_alloc: bool,
+}
+
+impl Default for GenerateOptions {
+  fn default() -> Self {
+    Self {
+      prefix: "generated_module".to_owned(),
+      shared_alloc: false,
+    }
+  }
+}
+
+impl GenerateOptions {
+  pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+    self.prefix = prefix.into();
+    self
+  }
+
+  pub fn shared_alloc(mut self, enable: bool) -> Self {
+    self.shared_alloc = enable;
+    self
+  }
+}
@@ -0,0 +1,283 @@
+use {
+  crate::{
+    dyn_trait::is_module_box,
+    marshal::{Marshal, is_option_ref},
+    shared::type_needs_box,
+  },
+  proc_macro2::TokenStream as TokenStream2,
+  quote::{ToTokens, quote},
+  syn::{GenericArgument, PathArguments, Type},
+};
+
+/// How return value of public exports and imports is passed between host and module:
+/// values which are not ABI-stable primitives are boxed (see `type_needs_box`) except `ModuleBox` (it's moved as is),
+/// `&str` and slices are converted to ABI-stable representations (see [`Marshal`]),
+/// `Result` is passed as `#[repr(C)]` enum (see [`result_type_decl`]) with its `Ok` and `Err` values boxed separately
+pub struct ReturnValue {
+  /// Type which is returned by `extern "C"` function
+  pub abi_type: TokenStream2,
+  /// Side which returned boxed value must free it in post function after the other side cloned it,
+  /// with shared allocator the ownership of the box is moved instead
+  pub needs_post: bool,
+  kind: Kind,
+  shared_alloc: bool,
+}
+
+enum Kind {
+  Value(Part),
+  Result { ok: Part, err: Part },
+}
+
+struct Part {
+  type_: TokenStream2,
+  boxed: bool,
+  /// `ModuleBox` is not Copy, so it must not be dropped by the post function after it's moved out
+  moved: bool,
+  marshal: Option<Marshal>,
+}
+
+impl Part {
+  fn new(type_: TokenStream2) -> Self {
+    let parsed = syn::parse2::<Type>(type_.clone()).ok();
+    let marshal = parsed.as_ref().and_then(Marshal::new);
+    let moved = parsed.as_ref().is_some_and(is_module_box);
+    let boxed = marshal.is_none()
+      && !moved
+      && !parsed.as_ref().is_some_and(is_option_ref)
+      && type_needs_box(&type_);
+
+    Self {
+      type_,
+      boxed,
+      moved,
+      marshal,
+    }
+  }
+
+  fn abi_type(&self) -> TokenStream2 {
+    let type_ = &self.type_;
+    if let Some(marshal) = &self.marshal {
+      marshal.abi_type()
+    } else if self.boxed {
+      quote! { *mut #type_ }
+    } else {
+      type_.clone()
+    }
+  }
+}
+
+impl ReturnValue {
+  pub fn new(return_type: &TokenStream2, shared_alloc: bool) -> Self {
+    let kind = match result_types(return_type) {
+      Some((ok, err)) => Kind::Result {
+        ok: Part::new(ok),
+        err: Part::new(err),
+      },
+      None => Kind::Value(Part::new(return_type.clone())),
+    };
+
+    let (abi_type, boxed) = match &kind {
+      Kind::Value(part) => (part.abi_type(), part.boxed),
+      Kind::Result { ok, err } => {
+        let ok_type = ok.abi_type();
+        let err_type = err.abi_type();
+        (
+          quote! { ____Result____<#ok_type, #err_type> },
+          ok.boxed || err.boxed,
+        )
+      }
+    };
+
+    Self {
+      abi_type,
+      needs_post: boxed && !shared_alloc,
+      kind,
+      shared_alloc,
+    }
+  }
+
+  /// Converts `value` returned by implementation to `abi_type`,
+  /// with shared allocator `release` statement is called with `boxed` variable before the box is leaked
+  pub fn encode(&self, value: TokenStream2, release: &TokenStream2) -> TokenStream2 {
+    match &self.kind {
+      Kind::Value(part) => self.encode_part(part, value, release),
+      Kind::Result { ok, err } => {
+        let ok = self.encode_part(ok, quote! { value }, release);
+        let err = self.encode_part(err, quote! { value }, release);
+        quote! {
+          match #value {
+            std::result::Result::Ok(value) => ____Result____::Ok(#ok),
+            std::result::Result::Err(value) => ____Result____::Err(#err),
+          }
+        }
+      }
+    }
+  }
+
+  fn encode_part(&self, part: &Part, value: TokenStream2, release: &TokenStream2) -> TokenStream2 {
+    if let Some(marshal) = &part.marshal {
+      return marshal.encode(&value);
+    }
+    if !part.boxed {
+      return value;
+    }
+
+    if self.shared_alloc {
+      quote! {{
+        let boxed = std::boxed::Box::new(#value);
+        #release
+        std::boxed::Box::into_raw(boxed)
+      }}
+    } else {
+      quote! { std::boxed::Box::into_raw(std::boxed::Box::new(#value)) }
+    }
+  }
+
+  /// Converts `value` of `abi_type` back to the return type of the function (`value` must be evaluated only once),
+  /// with shared allocator `adopt` statement is called with `ptr` variable before the box is taken
+  pub fn decode(&self, value: TokenStream2, adopt: &TokenStream2) -> TokenStream2 {
+    match &self.kind {
+      Kind::Value(part) => self.decode_part(part, value, adopt),
+      Kind::Result { ok, err } => {
+        let (ok_pat, ok) = self.decode_result_part(ok, adopt);
+        let (err_pat, err) = self.decode_result_part(err, adopt);
+        quote! {
+          match #value {
+            ____Result____::Ok(#ok_pat) => std::result::Result::Ok(#ok),
+            ____Result____::Err(#err_pat) => std::result::Result::Err(#err),
+          }
+        }
+      }
+    }
+  }
+
+  /// Value passed to the post function must stay whole, so moved parts are copied out of it
+  /// (post function forgets them, see `free`)
+  fn decode_result_part(&self, part: &Part, adopt: &TokenStream2) -> (TokenStream2, TokenStream2) {
+    if part.moved && self.needs_post {
+      (
+        quote! { ref value },
+        // SAFETY: post function doesn't drop it
+        quote! { unsafe { std::ptr::read(value) } },
+      )
+    } else {
+      (
+        quote! { value },
+        self.decode_part(part, quote! { value }, adopt),
+      )
+    }
+  }
+
+  fn decode_part(&self, part: &Part, value: TokenStream2, adopt: &TokenStream2) -> TokenStream2 {
+    if let Some(marshal) = &part.marshal {
+      return marshal.decode(&value);
+    }
+    if !part.boxed {
+      return value;
+    }
+
+    let type_ = &part.type_;
+    if self.shared_alloc {
+      quote! {{
+        let ptr = #value;
+        #adopt
+
+        // SAFETY: the other side released the value and the box (see relib_interface::Transfer),
+        // so they are now owned by this side and allocated by the global allocator of the host
+        let boxed = unsafe {
+          std::boxed::Box::from_raw(ptr.cast::<std::mem::ManuallyDrop<#type_>>())
+        };
+        std::mem::ManuallyDrop::into_inner(*boxed)
+      }}
+    } else {
+      quote! {
+        unsafe { Clone::clone(&*#value) }
+      }
+    }
+  }
+
+  /// Body of post function which frees boxes of `value` of `abi_type`
+  pub fn free(&self, value: TokenStream2) -> TokenStream2 {
+    let free_part = |part: &Part| {
+      if part.boxed {
+        quote! {
+          unsafe {
+            drop(std::boxed::Box::from_raw(value));
+          }
+        }
+      } else if part.moved {
+        // the other side took the ownership of it
+        quote! {
+          std::mem::forget(value);
+        }
+      } else {
+        quote! {
+          let _ = value;
+        }
+      }
+    };
+
+    match &self.kind {
+      Kind::Value(part) => {
+        let free = free_part(part);
+        quote! {
+          let value = #value;
+          #free
+        }
+      }
+      Kind::Result { ok, err } => {
+        let ok = free_part(ok);
+        let err = free_part(err);
+        quote! {
+          match #value {
+            ____Result____::Ok(value) => { #ok }
+            ____Result____::Err(value) => { #err }
+          }
+        }
+      }
+    }
+  }
+}
+
+/// `#[repr(C)]` representation of `Result` which is returned by `extern "C"` functions,
+/// must be added once to every module of generated code
+pub fn result_type_decl() -> TokenStream2 {
+  quote! {
+    #[doc(hidden)]
+    #[repr(C, u8)]
+    #[derive(Clone, Copy)]
+    #[allow(dead_code, non_camel_case_types)]
+    pub enum ____Result____<T, E> {
+      Ok(T),
+      Err(E),
+    }
+  }
+}
+
+/// Returns `Ok` and `Err` types if it's a `Result<T, E>` (path of the type doesn't matter, but it must have two generic arguments)
+fn result_types(return_type: &TokenStream2) -> Option<(TokenStream2, TokenStream2)> {
+  let Ok(Type::Path(type_path)) = syn::parse2::<Type>(return_type.clone()) else {
+    return None;
+  };
+  if type_path.qself.is_some() {
+    return None;
+  }
+
+  let segment = type_path.path.segments.last()?;
+  if segment.ident != "Result" {
+    return None;
+  }
+
+  let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+    return None;
+  };
+  let mut types = arguments.args.iter().map(|argument| match argument {
+    GenericArgument::Type(type_) => Some(type_.to_token_stream()),
+    _ => None,
+  });
+
+  match (types.next(), types.next(), types.next()) {
+    (Some(Some(ok)), Some(Some(err)), None) => Some((ok, err)),
+    _ => None,
+  }
+}
@@ -0,0 +1,509 @@
+use {
+  proc_macro2::TokenStream as TokenStream2,
+  quote::{ToTokens, format_ident, quote},
+  relib_internal_shared::fn_inputs_without_types,
+  std::{fs, path::Path},
+  syn::{
+    Attribute, FnArg, GenericParam, Ident, Item, ItemConst, ItemTrait, Lifetime, LifetimeParam,
+    ParenthesizedGenericArguments, ReturnType, Signature, Token, TraitItem, TypeBareFn,
+    TypeReference, UseTree,
+    punctuated::Punctuated,
+    visit_mut::{self, VisitMut},
+  },
+};
+
+/// How generated code is added to the crate
+#[derive(Clone, Copy)]
+pub enum Mode {
+  /// Written to OUT_DIR by build script and included using `include_exports!`/`include_imports!`
+  BuildScript,
+  /// Expanded by `#[relib_interface::exports]`/`#[relib_interface::imports]` next to the trait
+  Macro,
+}
+
+pub fn format_code(code: &str, file_name: &str) -> String {
+  match syn::parse_file(code) {
+    Ok(file) => prettyplease::unparse(&file),
+    Err(e) => {
+      eprintln!("Failed to parse Rust code for formatting of file: {file_name}, reason: {e:#}");
+      code.to_owned()
+    }
+  }
+}
+
+/// Parsed interface trait file
+pub struct TraitFile {
+  pub trait_: ItemTrait,
+  /// `use` items (with paths relative to the crate root or parent module replaced by absolute ones),
+  /// type aliases and constants which are copied to generated code
+  pub items: TokenStream2,
+  /// Constants which must have the same value in host and module, see [`const_symbol_name`]
+  pub consts: Vec<ItemConst>,
+}
+
+pub fn parse_trait_file(
+  trait_name: &str,
+  file_content: &'static str,
+  trait_path: &str,
+) -> TraitFile {
+  let trait_path_segments = trait_path
+    .split("::")
+    .map(|segment| format_ident!("{segment}"))
+    .collect::<Vec<_>>();
+  let [crate_name, trait_module_path @ .., _] = trait_path_segments.as_slice() else {
+    panic!("Failed to extract crate name from trait path: {trait_path}");
+  };
+
+  let ast = syn::parse_file(file_content).unwrap_or_else(|e| {
+    panic!("Failed to parse Rust code of trait file: {trait_name}: {e:#?}");
+  });
+
+  let mut trait_ = None;
+  let mut items = TokenStream2::new();
+  let mut consts = Vec::new();
+
+  for item in ast.items {
+    match item {
+      Item::Use(item_use) => {
+        let patched_use_tree =
+          patch_use_tree_if_needed(&item_use.tree, crate_name, trait_module_path);
+        let cfg_attrs = cfg_attrs(&item_use.attrs);
+        items.extend(quote! { #cfg_attrs use #patched_use_tree; });
+      }
+      Item::Type(item_type) => {
+        items.extend(quote! {
+          #[allow(dead_code)]
+          #item_type
+        });
+      }
+      Item::Const(item_const) => {
+        items.extend(item_const.to_token_stream());
+        consts.push(item_const);
+      }
+      // other traits are allowed so that, for example, exports and imports can be declared in one file
+      Item::Trait(item_trait) => {
+        if item_trait.ident == trait_name {
+          trait_ = Some(item_trait);
+        }
+      }
+      item => {
+        let code = item.to_token_stream();
+        panic!(
+          "Unexpected item in the {trait_name} trait file: `{code}`\n\
+          note: only `use`, `type`, `const` and `trait` items are allowed"
+        );
+      }
+    }
+  }
+
+  let Some(trait_) = trait_ else {
+    panic!(
+      r#"Expected "{trait_name}" trait in the trait file since this name was passed in build.rs relib_interface::<...>::generate"#
+    );
+  };
+
+  TraitFile {
+    trait_,
+    items,
+    consts,
+  }
+}
+
+pub fn write_code_to_file(file: &str, code: TokenStream2) {
+  let code = code.to_string();
+  let code = format_code(&code, file);
+  let out = format!(
+    "// This file is generated, DO NOT edit manually\n\
+    // ---------------------------------------------\n\n\
+    {code}"
+  );
+
+  let out_dir = std::env::var("OUT_DIR").unwrap();
+  let path = Path::new(&out_dir).join(file);
+
+  fs::write(&path, out).unwrap_or_else(|e| {
+    panic!("Failed to write {}, reason: {e:#}", path.display());
+  });
+}
+
+pub struct TraitFn<'a> {
+  pub ident: &'a Ident,
+  /// Inputs and output with named elided lifetimes, see [`name_elided_lifetimes`]
+  pub inputs: Punctuated<FnArg, Token![,]>,
+  pub inputs_without_types: Vec<TokenStream2>,
+  pub output: ReturnType,
+  pub mangled_name: String,
+  pub mangled_ident: Ident,
+
+  pub post_ident: Ident,
+  pub post_mangled_name: String,
+  pub post_mangled_ident: Ident,
+
+  pub lifetimes_for: TokenStream2,
+  pub lifetimes_full: TokenStream2,
+  pub lifetimes_where_module: TokenStream2,
+  pub lifetimes_module: TokenStream2,
+
+  /// `#[cfg(...)]` attributes of the function, they must be added to every generated item of it
+  pub cfg_attrs: TokenStream2,
+}
+
+pub fn for_each_trait_item<'trait_>(
+  trait_name: &str,
+  trait_item: &'trait_ TraitItem,
+) -> TraitFn<'trait_> {
+  let TraitItem::Fn(fn_) = trait_item else {
+    panic!("All trait items must be functions");
+  };
+  let cfg_attrs = cfg_attrs(&fn_.attrs);
+  let ident = &fn_.sig.ident;
+  let mut fn_ = fn_.sig.clone();
+  assert!(
+    fn_.receiver().is_none(),
+    "Functions in {trait_name} trait must not have `self` receiver"
+  );
+
+  let lifetimes = fn_.generics.params.iter().map(|p| {
+    let GenericParam::Lifetime(lt) = p else {
+      panic!(
+        "Functions in {trait_name} trait must not have generic types and const generics since they are not ABI-stable,\
+        it's only possible to use lifetime generics\n\
+        found in \"{}\" function",
+        fn_.ident
+      );
+    };
+
+    if !lt.bounds.is_empty() {
+      panic!(
+        "Functions in {trait_name} trait can't have lifetime bounds (`'a: 'b` syntax)\n\
+        found in \"{}\" function",
+        fn_.ident
+      );
+    }
+
+    lt.clone()
+  }).collect::<Vec<_>>();
+
+  // lifetime bounds are not allowed and it doesn't make sense without generics anyway
+  if fn_.generics.where_clause.is_some() {
+    panic!(
+      "Functions in {trait_name} trait can't have where clause\n\
+      found in \"{}\" function",
+      fn_.ident
+    );
+  }
+
+  let mut lifetimes = lifetimes;
+  lifetimes.extend(name_elided_lifetimes(&mut fn_));
+
+  let (lifetimes_for, lifetimes_full, lifetimes_where_module, lifetimes_module) =
+    if !lifetimes.is_empty() {
+      let lifetimes_where_module = if lifetimes.is_empty() {
+        quote! {}
+      } else {
+        quote! {
+          where #( 'module: #lifetimes, )*
+        }
+      };
+
+      let lifetimes = quote! { #( #lifetimes, )* };
+
+      (
+        quote! { for<#lifetimes> },
+        quote! { <#lifetimes> },
+        quote! { #lifetimes_where_module },
+        quote! { #lifetimes },
+      )
+    } else {
+      (quote! {}, quote! {}, quote! {}, quote! {})
+    };
+
+  let inputs_without_types = fn_inputs_without_types!(fn_.inputs);
+
+  // !!! keep in sync with main and before_unload calls in relib_host crate !!!
+  let mangled_name = format!("__relib__{trait_name}_{ident}");
+  let mangled_ident = format_ident!("{mangled_name}");
+  let post_mangled_name = format!("__post{mangled_name}");
+  let post_mangled_ident = format_ident!("{post_mangled_name}");
+
+  TraitFn {
+    ident,
+    inputs: fn_.inputs,
+    inputs_without_types,
+    output: fn_.output,
+    mangled_name,
+    mangled_ident,
+    post_ident: format_ident!("post_{ident}"),
+    post_mangled_name,
+    post_mangled_ident,
+    lifetimes_for,
+    lifetimes_full,
+    lifetimes_where_module,
+    lifetimes_module,
+    cfg_attrs,
+  }
+}
+
+/// Elided lifetimes of the output are replaced by the lifetime of the input (`fn f(s: &str) -> &str`
+/// becomes `fn f<'elided>(s: &'elided str) -> &'elided str`), since generated methods of `ModuleExports`
+/// also take `&self`, which would change lifetime elision rules.
+/// Returns lifetime which must be added to generics of the function.
+fn name_elided_lifetimes(fn_: &mut Signature) -> Option<LifetimeParam> {
+  let ReturnType::Type(_, output) = &mut fn_.output else {
+    return None;
+  };
+
+  let mut output_lifetimes = Lifetimes::default();
+  output_lifetimes.visit_type_mut(output);
+  if output_lifetimes.elided == 0 {
+    return None;
+  }
+
+  let mut input_lifetimes = Lifetimes::default();
+  for input in &mut fn_.inputs {
+    input_lifetimes.visit_fn_arg_mut(input);
+  }
+
+  // otherwise lifetime of the output is ambiguous and compiler will report it in the trait itself
+  match (input_lifetimes.elided, input_lifetimes.named.as_slice()) {
+    (0, [named]) => {
+      let named = named.clone();
+      Lifetimes::replace_elided(named).visit_type_mut(output);
+      None
+    }
+    (1, []) => {
+      let lifetime = Lifetime::new("'elided", proc_macro2::Span::call_site());
+      for input in &mut fn_.inputs {
+        Lifetimes::replace_elided(lifetime.clone()).visit_fn_arg_mut(input);
+      }
+      Lifetimes::replace_elided(lifetime.clone()).visit_type_mut(output);
+      Some(LifetimeParam::new(lifetime))
+    }
+    _ => None,
+  }
+}
+
+/// Removes `&self` or `&mut self` receiver of the method of `#[relib_interface::dyn_trait]` trait,
+/// elided lifetimes of the output are replaced by the lifetime of the receiver (as lifetime elision rules say),
+/// which is named `'elided` and added to generics of the method if needed.
+/// Returns lifetime of the receiver and whether it's mutable, or `None` if there is no such receiver.
+pub fn take_receiver(fn_: &mut Signature) -> Option<(Lifetime, bool)> {
+  let Some(FnArg::Receiver(receiver)) = fn_.inputs.first() else {
+    return None;
+  };
+  // typed receivers such as `self: Box<Self>` are not supported
+  if receiver.colon_token.is_some() {
+    return None;
+  }
+  let (_, lifetime) = receiver.reference.as_ref()?;
+  let mutable = receiver.mutability.is_some();
+
+  let lifetime = match lifetime {
+    Some(lifetime) => lifetime.clone(),
+    None => {
+      let mut output_lifetimes = Lifetimes::default();
+      if let ReturnType::Type(_, output) = &mut fn_.output {
+        output_lifetimes.visit_type_mut(output);
+      }
+
+      if output_lifetimes.elided == 0 {
+        Lifetime::new("'_", proc_macro2::Span::call_site())
+      } else {
+        let lifetime = Lifetime::new("'elided", proc_macro2::Span::call_site());
+        if let ReturnType::Type(_, output) = &mut fn_.output {
+          Lifetimes::replace_elided(lifetime.clone()).visit_type_mut(output);
+        }
+        fn_
+          .generics
+          .params
+          .push(GenericParam::Lifetime(LifetimeParam::new(lifetime.clone())));
+        lifetime
+      }
+    }
+  };
+
+  fn_.inputs = fn_.inputs.iter().skip(1).cloned().collect();
+  Some((lifetime, mutable))
+}
+
+/// Counts lifetimes of references and generic arguments (such as `RStr<'a>`), or replaces elided ones
+#[derive(Default)]
+struct Lifetimes {
+  elided: usize,
+  named: Vec<Lifetime>,
+  replace_with: Option<Lifetime>,
+}
+
+impl Lifetimes {
+  fn replace_elided(lifetime: Lifetime) -> Self {
+    Self {
+      replace_with: Some(lifetime),
+      ..Default::default()
+    }
+  }
+
+  fn elided(&mut self) -> Option<Lifetime> {
+    self.elided += 1;
+    self.replace_with.clone()
+  }
+}
+
+impl VisitMut for Lifetimes {
+  fn visit_type_reference_mut(&mut self, reference: &mut TypeReference) {
+    if reference.lifetime.is_none() {
+      reference.lifetime = self.elided();
+    }
+    visit_mut::visit_type_reference_mut(self, reference);
+  }
+
+  fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+    if lifetime.ident == "_" {
+      if let Some(replace_with) = self.elided() {
+        *lifetime = replace_with;
+      }
+    } else if !self.named.contains(lifetime) {
+      self.named.push(lifetime.clone());
+    }
+  }
+
+  // lifetimes of function pointers and closures (`dyn Fn(&str)`) are not elided to the lifetimes of the function
+  fn visit_type_bare_fn_mut(&mut self, _: &mut TypeBareFn) {}
+  fn visit_parenthesized_generic_arguments_mut(&mut self, _: &mut ParenthesizedGenericArguments) {}
+}
+
+/// Conditional compilation attributes are copied as is, so they are evaluated
+/// in the crate which includes generated code (and in the crate of the trait file)
+pub fn cfg_attrs(attrs: &[Attribute]) -> TokenStream2 {
+  attrs
+    .iter()
+    .filter(|attr| attr.path().is_ident("cfg"))
+    .map(ToTokens::to_token_stream)
+    .collect()
+}
+
+/// Symbol which module exports when its code is generated with shared allocator mode,
+/// host checks it to make sure that both sides use the same mode
+pub fn shared_alloc_marker_name(trait_name: &str) -> String {
+  format!("__relib__{trait_name}__shared_alloc")
+}
+
+/// Null-terminated byte string static which module exports with signatures of the trait functions,
+/// `relib_host::inspect` reads it to compare interfaces of modules without loading them
+pub fn signature_symbol_name(trait_name: &str) -> String {
+  format!("__relib_signature__{trait_name}")
+}
+
+/// Symbol of the function which returns `ModuleExportsTable`,
+/// module exports it when exports trait is implemented using `#[relib_interface::exports]`
+pub fn table_symbol_name(trait_name: &str) -> String {
+  format!("__relib__{trait_name}__table")
+}
+
+/// Symbol of the static in which host writes pointer to `relib_host::Caller` of the module,
+/// module passes it to every import so that host knows which module calls it
+pub fn caller_symbol_name(trait_name: &str) -> String {
+  format!("__relib__{trait_name}__caller")
+}
+
+/// Symbol of the function which returns pointer to the value of constant from the trait file,
+/// host compares it with its own value when module is loaded
+pub fn const_symbol_name(trait_name: &str, const_ident: &Ident) -> String {
+  format!("__relib__{trait_name}__const__{const_ident}")
+}
+
+pub fn extract_trait_name_from_path(trait_path: &str) -> &str {
+  trait_path.split("::").last().unwrap_or_else(|| {
+    panic!("Failed to extract trait name from path: {trait_path}");
+  })
+}
+
+/// Paths relative to the crate root and to the parent module of the trait file
+/// are replaced by absolute ones since generated code is included in another crate
+fn patch_use_tree_if_needed(
+  use_tree: &UseTree,
+  crate_name: &Ident,
+  trait_module_path: &[Ident],
+) -> TokenStream2 {
+  match use_tree {
+    UseTree::Path(path) => {
+      let ident = path.ident.to_string();
+      let ident = ident.as_str();
+
+      match ident {
+        "crate" => {
+          let tree = &path.tree;
+          quote! { #crate_name::#tree }
+        }
+        // `self` paths are left as is since items of the trait file are copied next to generated code
+        "self" | "super" if uses_super(use_tree) => {
+          super_use_tree(use_tree, crate_name, trait_module_path)
+        }
+        _ => use_tree.to_token_stream(),
+      }
+    }
+    UseTree::Group(group) => {
+      let use_items = group
+        .items
+        .iter()
+        .map(|use_tree| patch_use_tree_if_needed(use_tree, crate_name, trait_module_path))
+        .collect::<Vec<_>>();
+
+      quote! { { #( #use_items ),* } }
+    }
+    UseTree::Name(..) | UseTree::Rename(..) | UseTree::Glob(..) => use_tree.to_token_stream(),
+  }
+}
+
+fn uses_super(use_tree: &UseTree) -> bool {
+  match use_tree {
+    UseTree::Path(path) if path.ident == "super" => true,
+    UseTree::Path(path) if path.ident == "self" => uses_super(&path.tree),
+    _ => false,
+  }
+}
+
+/// Resolves `super` against module path of the trait
+/// (for example, `use super::types::*` in `crate::a::b::Trait` is `crate::a::types::*`)
+fn super_use_tree(use_tree: &UseTree, crate_name: &Ident, module_path: &[Ident]) -> TokenStream2 {
+  match use_tree {
+    UseTree::Path(path) if path.ident == "self" => {
+      super_use_tree(&path.tree, crate_name, module_path)
+    }
+    UseTree::Path(path) if path.ident == "super" => {
+      let Some((_, parent_module_path)) = module_path.split_last() else {
+        let code = use_tree.to_token_stream();
+        panic!(
+          "Failed to copy `{code}`: there is no parent module of the crate root\n\
+          note: `super` is resolved against the trait path passed to `generate`, \
+          it must be the path of the module where the trait is declared (not a re-export)"
+        );
+      };
+      super_use_tree(&path.tree, crate_name, parent_module_path)
+    }
+    _ => quote! { #crate_name #( ::#module_path )* :: #use_tree },
+  }
+}
+
+pub const SAFETY_DOC: &str = "# Safety\n\
+  Behavior is undefined if any of the following conditions are violated:\n\
+  1. Types of arguments and return value must be ABI-stable.\n\
+  2. Host and module crates must be compiled with same shared crate code (which contains exports and imports traits).\n\
+  3. Returned value must not be a reference-counting pointer or &'static T (see [caveats](https://docs.rs/relib/latest/relib/#moving-non-copy-types-between-host-and-module)).";
+
+pub fn type_needs_box(type_: &TokenStream2) -> bool {
+  relib_internal_shared::type_needs_box(&type_.to_string())
+}
+
+pub fn pass_out_dir_file_name_to_crate_code(prefix: &str, name: &str) {
+  // prefix is not converted to uppercase because it's part of public api and
+  // needs to be passed by library user to include_exports/imports macro
+  // name is also not converted to uppercase for consistency
+  let key = format!("__RELIB_OUT_DIR_{prefix}_{name}__");
+  let file_name = out_dir_file_name(prefix, name);
+  let value = format!("/{file_name}");
+  println!("cargo:rustc-env={key}={value}");
+}
+
+pub fn out_dir_file_name(prefix: &str, name: &str) -> String {
+  format!("{prefix}_{name}.rs")
+}
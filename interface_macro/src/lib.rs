@@ -0,0 +1,112 @@
+use proc_macro::TokenStream;
+
+/// Generates bindings for exports trait right next to it, without build script.
+///
+/// On a trait in shared crate it generates `gen_exports` module (name can be changed: `#[relib_interface::exports(my_mod)]`)
+/// with `ModuleExports` struct for the host.
+/// On implementation of the trait in module crate it exports this implementation to the host.
+///
+/// See [docs](https://docs.rs/relib/latest/relib/docs/index.html#interface-without-build-scripts) for more info.
+///
+/// # Examples
+/// ```ignore
+/// // in shared crate:
+/// #[relib_interface::exports]
+/// pub trait Exports {
+///   fn foo() -> u8;
+/// }
+///
+/// // in module crate:
+/// struct ModuleExports;
+///
+/// #[relib_interface::exports]
+/// impl shared::Exports for ModuleExports {
+///   fn foo() -> u8 {
+///     10
+///   }
+/// }
+///
+/// // in host crate:
+/// let module = relib_host::load_module::<shared::gen_exports::ModuleExports>(/* ... */);
+/// ```
+#[proc_macro_attribute]
+pub fn exports(args: TokenStream, input: TokenStream) -> TokenStream {
+  relib_interface_codegen::exports(args.into(), input.into()).into()
+}
+
+/// Generates bindings for imports trait right next to it, without build script.
+///
+/// It generates `gen_imports` module (name can be changed: `#[relib_interface::imports(my_mod)]`)
+/// with functions for calling imports in the module, and `init_imports` function for the host.
+///
+/// See [docs](https://docs.rs/relib/latest/relib/docs/index.html#interface-without-build-scripts) for more info.
+///
+/// # Examples
+/// ```ignore
+/// // in shared crate:
+/// #[relib_interface::imports]
+/// pub trait Imports {
+///   fn foo() -> u8;
+/// }
+///
+/// // in host crate:
+/// struct ModuleImports;
+///
+/// impl shared::Imports for ModuleImports {
+///   fn foo() -> u8 {
+///     10
+///   }
+/// }
+///
+/// let module = relib_host::load_module::<()>(path, shared::gen_imports::init_imports::<ModuleImports>);
+///
+/// // in module crate:
+/// let value = unsafe { shared::gen_imports::foo() };
+/// ```
+#[proc_macro_attribute]
+pub fn imports(args: TokenStream, input: TokenStream) -> TokenStream {
+  relib_interface_codegen::imports(args.into(), input.into()).into()
+}
+
+/// Allows to move trait objects of the trait between host and module as `relib_interface::ModuleBox<dyn Trait>`.
+///
+/// It generates `#[repr(C)]` vtable of the trait, which module creates in `ModuleBox::new`,
+/// and implementation of the trait for `ModuleBox<dyn Trait>`, which calls functions of the vtable.
+/// Methods must take `&self` or `&mut self`, their parameters and return values follow the same rules
+/// as parameters and return values of exports and imports. Methods with `where Self: Sized`
+/// are not added to the vtable, other where clauses are not supported.
+///
+/// See [docs](https://docs.rs/relib/latest/relib/docs/index.html#trait-objects) for more info.
+///
+/// # Examples
+/// ```ignore
+/// // in shared crate:
+/// #[relib_interface::dyn_trait]
+/// pub trait System {
+///   fn update(&mut self, dt: f32);
+/// }
+///
+/// #[relib_interface::exports]
+/// pub trait Exports {
+///   fn create_system() -> relib_interface::ModuleBox<dyn System>;
+/// }
+///
+/// // in module crate:
+/// struct Physics;
+///
+/// impl shared::System for Physics {
+///   fn update(&mut self, dt: f32) {}
+/// }
+///
+/// fn create_system() -> relib_interface::ModuleBox<dyn shared::System> {
+///   relib_interface::ModuleBox::new(Physics)
+/// }
+///
+/// // in host crate:
+/// let mut system = unsafe { module.exports().create_system() }.unwrap();
+/// system.update(0.016);
+/// ```
+#[proc_macro_attribute]
+pub fn dyn_trait(args: TokenStream, input: TokenStream) -> TokenStream {
+  relib_interface_codegen::dyn_trait(args.into(), input.into()).into()
+}
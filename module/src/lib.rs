@@ -15,7 +15,12 @@ compile_error!(
   disable \"global_alloc_tracker\" and enable \"unloading_core\""
 );
 
-// TODO: add support for conditional compilation in relib_interface
-#[doc(hidden)]
-#[cfg(all(feature = "unloading_core", not(feature = "dealloc_validation")))]
-pub use unloading_core::_suppress_warn;
+#[cfg(all(feature = "global_arena_alloc", not(feature = "unloading_core")))]
+compile_error!(
+  "\"global_arena_alloc\" feature cannot be enabled without \"unloading_core\" feature"
+);
+
+#[cfg(all(feature = "global_host_alloc", not(feature = "unloading_core")))]
+compile_error!(
+  "\"global_host_alloc\" feature cannot be enabled without \"unloading_core\" feature"
+);
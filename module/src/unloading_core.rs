@@ -17,13 +17,32 @@ mod thread_spawn_hook;
 mod mmap_hooks;
 #[cfg(target_os = "linux")]
 mod pthread_key_hooks;
+#[cfg(target_os = "linux")]
+mod untracked_threads;
 mod helpers;
 mod exports_impl;
+mod pre_init_alloc;
+
+mod shutdown;
+pub use shutdown::{Cancelled, ShutdownToken, shutdown_token};
 
 mod alloc_tracker;
 pub use alloc_tracker::AllocTracker;
-#[cfg(all(feature = "unloading_core", not(feature = "dealloc_validation")))]
-pub use alloc_tracker::_suppress_warn;
+
+mod arena_alloc;
+pub use arena_alloc::ArenaAlloc;
+
+mod host_alloc;
+pub use host_alloc::HostAlloc;
+
+mod handle_table;
+pub use handle_table::HandleTable;
+
+/// Used in code generated by relib_interface
+#[doc(hidden)]
+pub mod __internal {
+  pub use super::host_alloc::{adopt_transferred, release_transferred};
+}
 
 #[cfg(target_os = "windows")]
 mod windows_dll_main;
@@ -34,7 +53,14 @@ mod windows_dealloc;
 /// (for example `std::mem:forget`, static items) on module unload.
 /// It sends all allocations and deallocations to host because to
 /// store allocations we need to allocate unknown amount of memory.
-#[cfg(feature = "global_alloc_tracker")]
+///
+/// If "global_arena_alloc" or "global_host_alloc" feature is enabled too, that allocator is used instead
+/// (so `--all-features` builds have only one global allocator).
+#[cfg(all(
+  feature = "global_alloc_tracker",
+  not(feature = "global_arena_alloc"),
+  not(feature = "global_host_alloc")
+))]
 mod __alloc_tracker {
   use {super::AllocTracker, std::alloc::System};
 
@@ -42,6 +68,29 @@ mod __alloc_tracker {
   static ALLOC_TRACKER: AllocTracker<System> = AllocTracker::new(System);
 }
 
+/// Alternative to alloc tracker: all memory of the module is allocated in regions
+/// mapped by host, so on unload host doesn't need to deallocate every leaked allocation,
+/// it simply unmaps the regions (useful for modules with millions of allocations).
+///
+/// If "global_host_alloc" feature is enabled too, host allocator is used instead.
+#[cfg(all(feature = "global_arena_alloc", not(feature = "global_host_alloc")))]
+mod __arena_alloc {
+  use super::ArenaAlloc;
+
+  #[global_allocator]
+  static ARENA_ALLOC: ArenaAlloc = ArenaAlloc::new();
+}
+
+/// Alternative to alloc tracker: all memory of the module is allocated by global allocator of the host,
+/// which allows to move values between host and module without cloning.
+#[cfg(feature = "global_host_alloc")]
+mod __host_alloc {
+  use super::HostAlloc;
+
+  #[global_allocator]
+  static HOST_ALLOC: HostAlloc = HostAlloc::new();
+}
+
 static ALLOCATOR_LOCK: AtomicBool = AtomicBool::new(false);
 fn allocator_lock() -> bool {
   ALLOCATOR_LOCK.load(Ordering::SeqCst)
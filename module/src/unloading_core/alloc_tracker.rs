@@ -3,13 +3,16 @@ use {
     MODULE_ID, gen_imports,
     helpers::{assert_allocator_is_still_accessible, unrecoverable},
   },
-  relib_internal_shared::{Allocation, AllocatorOp, AllocatorPtr, StableLayout},
+  relib_internal_shared::{
+    ALLOC_SHARDS, ALLOC_SIZE_CLASSES, AllocStats, Allocation, AllocatorOp, AllocatorPtr,
+    BudgetPolicy, PtrBuildHasher, alloc_size_class,
+  },
   std::{
     alloc::{GlobalAlloc, Layout},
     collections::HashMap,
     sync::{
-      LazyLock, Mutex, MutexGuard,
-      atomic::{AtomicBool, Ordering},
+      Mutex, MutexGuard,
+      atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering},
     },
   },
 };
@@ -31,7 +34,17 @@ unsafe impl<A: GlobalAlloc> GlobalAlloc for AllocTracker<A> {
   unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
     assert_allocator_is_still_accessible();
 
+    if !is_within_budget(layout.size()) {
+      return std::ptr::null_mut();
+    }
+
     let ptr = unsafe { self.allocator.alloc(layout) };
+    let tracked_ptr = AllocatorPtr(ptr);
+
+    let mut shard = lock_shard(tracked_ptr.shard());
+    if !ptr.is_null() {
+      shard.stats.on_alloc(layout.size());
+    }
 
     if ALLOC_INIT.load(Ordering::SeqCst) {
       // TODO: SAFETY
@@ -39,7 +52,10 @@ unsafe impl<A: GlobalAlloc> GlobalAlloc for AllocTracker<A> {
         gen_imports::on_alloc(MODULE_ID, ptr, layout.into());
       }
     } else {
-      save_alloc_in_cache(ptr, layout.into());
+      shard.push(
+        tracked_ptr,
+        AllocatorOp::Alloc(Allocation(tracked_ptr, layout.into())),
+      );
     }
 
     ptr
@@ -48,74 +64,207 @@ unsafe impl<A: GlobalAlloc> GlobalAlloc for AllocTracker<A> {
   unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
     assert_allocator_is_still_accessible();
 
-    #[cfg(feature = "dealloc_validation")]
-    if !UNLOAD_DEALLOCATION.load(Ordering::SeqCst) && !is_ptr_valid(ptr) {
-      unrecoverable("invalid pointer was passed to dealloc of global allocator");
+    // cache may be already deallocated at this point
+    if !UNLOAD_DEALLOCATION.load(Ordering::SeqCst) {
+      let tracked_ptr = AllocatorPtr(ptr);
+      let mut shard = lock_shard(tracked_ptr.shard());
+
+      #[cfg(feature = "dealloc_validation")]
+      if !shard.is_ptr_valid(tracked_ptr) {
+        unrecoverable("invalid pointer was passed to dealloc of global allocator");
+      }
+
+      // it's fine to save it before actual deallocation: allocator can't return this pointer
+      // to another thread until it's deallocated, so ops of it will be saved in the right order
+      shard.stats.on_dealloc(layout.size());
+      shard.push(
+        tracked_ptr,
+        AllocatorOp::Dealloc(Allocation(tracked_ptr, layout.into())),
+      );
     }
 
     // TODO: SAFETY
     unsafe {
       self.allocator.dealloc(ptr, layout);
     }
+  }
+}
 
-    if !UNLOAD_DEALLOCATION.load(Ordering::SeqCst) {
-      save_dealloc_in_cache(ptr, layout.into());
+/// Counted here and not in the host since alloc and dealloc of the same pointer
+/// are merged into one op in the cache, so host doesn't see every allocation.
+/// Counters are stored in cache shards since they are already locked in alloc and dealloc,
+/// only live bytes are global since they are needed for memory budget check
+struct ShardStats {
+  total_allocations: u64,
+  total_deallocations: u64,
+  live_by_size_class: [u64; ALLOC_SIZE_CLASSES],
+}
+
+impl ShardStats {
+  const fn new() -> Self {
+    Self {
+      total_allocations: 0,
+      total_deallocations: 0,
+      live_by_size_class: [0; ALLOC_SIZE_CLASSES],
     }
   }
-}
 
-const CACHE_SIZE: usize = 20_000;
+  fn on_alloc(&mut self, size: usize) {
+    self.total_allocations += 1;
+    self.live_by_size_class[alloc_size_class(size)] += 1;
 
-type AllocsCache = HashMap<AllocatorPtr, AllocatorOp>;
+    let size = size as u64;
+    let live_bytes = LIVE_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+    if live_bytes > PEAK_BYTES.load(Ordering::Relaxed) {
+      PEAK_BYTES.fetch_max(live_bytes, Ordering::Relaxed);
+    }
+  }
 
-static ALLOCS_CACHE: LazyLock<Mutex<AllocsCache>> = LazyLock::new(|| Mutex::new(HashMap::new()));
-static ALLOC_INIT: AtomicBool = AtomicBool::new(false);
+  fn on_dealloc(&mut self, size: usize) {
+    self.total_deallocations += 1;
+    // alloc and dealloc of the same pointer are always counted in the same shard
+    let class = &mut self.live_by_size_class[alloc_size_class(size)];
+    *class = class.saturating_sub(1);
+    LIVE_BYTES.fetch_sub(size as u64, Ordering::Relaxed);
+  }
+}
 
-static TRANSPORT_BUFFER: Mutex<Vec<AllocatorOp>> = Mutex::new(Vec::new());
+static LIVE_BYTES: AtomicU64 = AtomicU64::new(0);
+static PEAK_BYTES: AtomicU64 = AtomicU64::new(0);
 
-fn lock_allocs_cache() -> MutexGuard<'static, AllocsCache> {
-  ALLOCS_CACHE.lock().unwrap_or_else(|_| {
-    unrecoverable("failed to lock ALLOCS_CACHE");
-  })
+const NO_BUDGET: u64 = u64::MAX;
+
+static BUDGET_LIMIT: AtomicU64 = AtomicU64::new(NO_BUDGET);
+static BUDGET_POLICY: AtomicU8 = AtomicU8::new(BudgetPolicy::Fail as u8);
+static OVER_BUDGET: AtomicBool = AtomicBool::new(false);
+
+pub fn set_budget(limit: u64, policy: BudgetPolicy) {
+  BUDGET_POLICY.store(policy as u8, Ordering::SeqCst);
+  BUDGET_LIMIT.store(limit, Ordering::SeqCst);
+  OVER_BUDGET.store(false, Ordering::SeqCst);
 }
 
-fn lock_transport_buffer() -> MutexGuard<'static, Vec<AllocatorOp>> {
-  TRANSPORT_BUFFER.lock().unwrap_or_else(|_| {
-    unrecoverable("failed to lock TRANSPORT_BUFFER");
-  })
+pub fn is_over_budget() -> bool {
+  OVER_BUDGET.load(Ordering::SeqCst)
 }
 
-fn push_to_allocs_cache(op: AllocatorOp, cache: Option<&mut AllocsCache>) {
-  let cache = if let Some(cache) = cache {
-    cache
+/// Check is approximate: concurrent allocations may exceed the budget a bit
+fn is_within_budget(size: usize) -> bool {
+  let limit = BUDGET_LIMIT.load(Ordering::Relaxed);
+  if limit == NO_BUDGET {
+    return true;
+  }
+
+  let live_bytes = LIVE_BYTES.load(Ordering::Relaxed);
+  if live_bytes.saturating_add(size as u64) <= limit {
+    return true;
+  }
+
+  OVER_BUDGET.store(true, Ordering::SeqCst);
+
+  let policy = BUDGET_POLICY.load(Ordering::SeqCst);
+  if policy == BudgetPolicy::Fail as u8 {
+    false
+  } else if policy == BudgetPolicy::Allow as u8 {
+    true
   } else {
-    &mut lock_allocs_cache()
-  };
+    unsafe { gen_imports::on_memory_budget_exceeded(MODULE_ID, size, live_bytes) }
+  }
+}
 
-  let ptr = match op {
-    AllocatorOp::Alloc(Allocation(ptr, ..)) => ptr,
-    AllocatorOp::Dealloc(Allocation(ptr, ..)) => ptr,
+pub fn stats() -> AllocStats {
+  let mut stats = AllocStats {
+    live_allocations: 0,
+    live_bytes: LIVE_BYTES.load(Ordering::Relaxed),
+    peak_bytes: PEAK_BYTES.load(Ordering::Relaxed),
+    total_allocations: 0,
+    total_deallocations: 0,
+    live_by_size_class: [0; ALLOC_SIZE_CLASSES],
   };
 
-  cache.insert(ptr, op);
-
-  if cache.len() == CACHE_SIZE {
-    send_cached_allocs(Some(cache));
+  for idx in 0..ALLOC_SHARDS {
+    let shard = lock_shard(idx);
+    stats.total_allocations += shard.stats.total_allocations;
+    stats.total_deallocations += shard.stats.total_deallocations;
+    for (total, shard_class) in stats
+      .live_by_size_class
+      .iter_mut()
+      .zip(shard.stats.live_by_size_class)
+    {
+      *total += shard_class;
+    }
   }
+
+  stats.live_allocations = stats.live_by_size_class.iter().sum();
+  stats
 }
 
-fn save_alloc_in_cache(ptr: *mut u8, layout: StableLayout) {
-  push_to_allocs_cache(
-    AllocatorOp::Alloc(Allocation(AllocatorPtr(ptr), layout)),
-    None,
-  );
+/// Max number of cached allocation ops of all shards
+const CACHE_SIZE: usize = 20_000;
+const SHARD_CACHE_SIZE: usize = CACHE_SIZE / ALLOC_SHARDS;
+
+type AllocsCache = HashMap<AllocatorPtr, AllocatorOp, PtrBuildHasher>;
+
+/// Allocation ops are cached in shards (see `ALLOC_SHARDS`) so that threads allocating at the same time
+/// rarely wait for each other, and sent to the host in batches when the shard is full.
+/// Per-thread caches are not used since alloc and dealloc of the same pointer can happen
+/// in different threads and host must receive them in the right order.
+struct Shard {
+  cache: AllocsCache,
+  /// Buffer for sending cached ops to the host,
+  /// preallocated like `cache` because allocator must not allocate while it's locked
+  transport: Vec<AllocatorOp>,
+  stats: ShardStats,
 }
 
-fn save_dealloc_in_cache(ptr: *mut u8, layout: StableLayout) {
-  let cache = &mut lock_allocs_cache();
+impl Shard {
+  const fn new() -> Self {
+    Self {
+      cache: HashMap::with_hasher(PtrBuildHasher::new()),
+      transport: Vec::new(),
+      stats: ShardStats::new(),
+    }
+  }
+
+  fn push(&mut self, ptr: AllocatorPtr, op: AllocatorOp) {
+    self.cache.insert(ptr, op);
+
+    if self.cache.len() == SHARD_CACHE_SIZE {
+      self.send();
+    }
+  }
+
+  fn send(&mut self) {
+    self
+      .transport
+      .extend(self.cache.drain().map(|(_, allocation)| allocation));
 
-  let ptr = AllocatorPtr(ptr);
-  push_to_allocs_cache(AllocatorOp::Dealloc(Allocation(ptr, layout)), Some(cache));
+    unsafe {
+      let slice: &[AllocatorOp] = &self.transport;
+      gen_imports::on_cached_allocs(MODULE_ID, slice.into());
+    }
+
+    self.transport.clear();
+  }
+
+  #[cfg(feature = "dealloc_validation")]
+  /// is this pointer allocated by this allocator and is still alive?
+  fn is_ptr_valid(&self, ptr: AllocatorPtr) -> bool {
+    match self.cache.get(&ptr) {
+      Some(AllocatorOp::Alloc(..)) => true,
+      Some(AllocatorOp::Dealloc(..)) => false,
+      None => unsafe { gen_imports::is_ptr_allocated(MODULE_ID, ptr.0) },
+    }
+  }
+}
+
+static SHARDS: [Mutex<Shard>; ALLOC_SHARDS] = [const { Mutex::new(Shard::new()) }; ALLOC_SHARDS];
+static ALLOC_INIT: AtomicBool = AtomicBool::new(false);
+
+fn lock_shard(idx: usize) -> MutexGuard<'static, Shard> {
+  SHARDS[idx].lock().unwrap_or_else(|_| {
+    unrecoverable("failed to lock allocs cache shard");
+  })
 }
 
 pub unsafe fn init() {
@@ -123,32 +272,23 @@ pub unsafe fn init() {
 
   // !!! keep in mind that at least one of these allocations is needed for AllocTracker check in host !!!
 
-  let mut cache = lock_allocs_cache();
-  cache.reserve(CACHE_SIZE);
+  for idx in 0..ALLOC_SHARDS {
+    // allocated before locking the shard since allocator locks the shard of allocated pointer
+    let cache = AllocsCache::with_capacity_and_hasher(SHARD_CACHE_SIZE, Default::default());
+    let transport = Vec::with_capacity(SHARD_CACHE_SIZE);
 
-  let mut transport = lock_transport_buffer();
-  transport.reserve(CACHE_SIZE);
+    let mut shard = lock_shard(idx);
+    shard.cache = cache;
+    shard.transport = transport;
+  }
 
   ALLOC_INIT.swap(false, Ordering::SeqCst);
 }
 
-pub fn send_cached_allocs(cache: Option<&mut AllocsCache>) {
-  let cache = if let Some(cache) = cache {
-    cache
-  } else {
-    &mut lock_allocs_cache()
-  };
-
-  let mut transport = lock_transport_buffer();
-
-  transport.extend(cache.drain().map(|(_, allocation)| allocation));
-
-  unsafe {
-    let slice: &[AllocatorOp] = &transport;
-    gen_imports::on_cached_allocs(MODULE_ID, slice.into());
+pub fn send_cached_allocs() {
+  for idx in 0..ALLOC_SHARDS {
+    lock_shard(idx).send();
   }
-
-  transport.clear();
 }
 
 pub unsafe fn dealloc(allocs: &[Allocation]) {
@@ -162,22 +302,3 @@ pub unsafe fn dealloc(allocs: &[Allocation]) {
 
   UNLOAD_DEALLOCATION.swap(false, Ordering::SeqCst);
 }
-
-#[cfg(feature = "dealloc_validation")]
-/// is this pointer allocated by this allocator and is still alive?
-fn is_ptr_valid(ptr: *mut u8) -> bool {
-  let cache_contains_ptr = {
-    let cache = lock_allocs_cache();
-    cache.contains_key(&AllocatorPtr(ptr))
-  };
-
-  cache_contains_ptr || unsafe { gen_imports::is_ptr_allocated(MODULE_ID, ptr) }
-}
-
-#[cfg(not(feature = "dealloc_validation"))]
-#[expect(unreachable_code)]
-pub fn _suppress_warn() {
-  unsafe {
-    gen_imports::is_ptr_allocated(unreachable!(), unreachable!());
-  }
-}
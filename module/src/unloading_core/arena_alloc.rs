@@ -0,0 +1,178 @@
+use {
+  super::{
+    MODULE_ID, gen_imports,
+    helpers::{assert_allocator_is_still_accessible, unrecoverable},
+    pre_init_alloc,
+  },
+  std::{
+    alloc::{GlobalAlloc, Layout},
+    sync::{Mutex, MutexGuard},
+  },
+};
+
+/// Size of the region which is split into blocks of one size class
+const RUN_SIZE: usize = 256 * 1024;
+const MIN_BLOCK_SIZE: usize = 16;
+/// Bigger allocations get their own region
+const MAX_BLOCK_SIZE: usize = 32 * 1024;
+const SIZE_CLASSES: usize = (MAX_BLOCK_SIZE / MIN_BLOCK_SIZE).ilog2() as usize + 1;
+/// Regions are at least page-aligned, so blocks of a run are aligned to their size up to page size
+const PAGE_SIZE: usize = 4096;
+
+/// Global allocator which carves all memory of the module out of regions mapped by the host,
+/// so on unload host simply unmaps them instead of deallocating every leaked allocation one by one.
+///
+/// Small allocations are served from regions of 256 KiB (runs) split into blocks of power of two size,
+/// freed blocks are reused, but runs are only returned to the system on unload.
+/// Allocations bigger than 32 KiB get their own region which is unmapped on deallocation.
+/// Allocations made before module is initialized by host are made by `System`.
+///
+/// **note:** [`Module::memory_stats`](https://docs.rs/relib_host/latest/relib_host/struct.Module.html#method.memory_stats),
+/// memory budget and "dealloc_validation" feature are only supported by [`AllocTracker`](crate::AllocTracker)
+/// (host prints a warning if stats or budget are requested).
+#[derive(Default, Debug)]
+pub struct ArenaAlloc;
+
+impl ArenaAlloc {
+  pub const fn new() -> Self {
+    ArenaAlloc
+  }
+}
+
+unsafe impl GlobalAlloc for ArenaAlloc {
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    if !pre_init_alloc::is_initialized() {
+      return pre_init_alloc::alloc(layout);
+    }
+    assert_allocator_is_still_accessible();
+
+    match size_class(layout) {
+      Some(class) => lock_size_class(class).alloc(block_size(class)),
+      None => map_region(layout.size(), layout.align()),
+    }
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    if unsafe { pre_init_alloc::dealloc(ptr, layout) } {
+      return;
+    }
+    assert_allocator_is_still_accessible();
+
+    match size_class(layout) {
+      Some(class) => unsafe { lock_size_class(class).dealloc(ptr) },
+      None => unsafe { gen_imports::arena_unmap(MODULE_ID, ptr) },
+    }
+  }
+
+  unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+    // SAFETY: caller guarantees that new_size rounded up to align doesn't overflow isize
+    let new_layout = unsafe { Layout::from_size_align_unchecked(new_size, layout.align()) };
+
+    // block is big enough (allocations made before initialization are not blocks)
+    let class = size_class(layout);
+    if class.is_some() && class == size_class(new_layout) && !pre_init_alloc::contains(ptr) {
+      return ptr;
+    }
+
+    let new_ptr = unsafe { self.alloc(new_layout) };
+    if !new_ptr.is_null() {
+      unsafe {
+        std::ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(new_size));
+        self.dealloc(ptr, layout);
+      }
+    }
+    new_ptr
+  }
+}
+
+/// `None` if allocation doesn't fit in a block and needs its own region
+fn size_class(layout: Layout) -> Option<usize> {
+  if layout.align() > PAGE_SIZE {
+    return None;
+  }
+
+  let size = layout
+    .size()
+    .max(layout.align())
+    .max(MIN_BLOCK_SIZE)
+    .next_power_of_two();
+  if size > MAX_BLOCK_SIZE {
+    return None;
+  }
+
+  Some((size / MIN_BLOCK_SIZE).trailing_zeros() as usize)
+}
+
+fn block_size(class: usize) -> usize {
+  MIN_BLOCK_SIZE << class
+}
+
+fn map_region(size: usize, align: usize) -> *mut u8 {
+  unsafe { gen_imports::arena_map(MODULE_ID, size, align) }
+}
+
+struct FreeBlock {
+  next: *mut FreeBlock,
+}
+
+struct SizeClass {
+  /// Blocks which were deallocated
+  free: *mut FreeBlock,
+  /// Unused part of the current run
+  bump: *mut u8,
+  end: *mut u8,
+}
+
+// SAFETY: pointers are only accessed while size class is locked
+unsafe impl Send for SizeClass {}
+
+static SIZE_CLASS_STATES: [Mutex<SizeClass>; SIZE_CLASSES] =
+  [const { Mutex::new(SizeClass::new()) }; SIZE_CLASSES];
+
+fn lock_size_class(class: usize) -> MutexGuard<'static, SizeClass> {
+  SIZE_CLASS_STATES[class].lock().unwrap_or_else(|_| {
+    unrecoverable("failed to lock arena size class");
+  })
+}
+
+impl SizeClass {
+  const fn new() -> Self {
+    Self {
+      free: std::ptr::null_mut(),
+      bump: std::ptr::null_mut(),
+      end: std::ptr::null_mut(),
+    }
+  }
+
+  fn alloc(&mut self, block_size: usize) -> *mut u8 {
+    if !self.free.is_null() {
+      let block = self.free;
+      self.free = unsafe { (*block).next };
+      return block.cast();
+    }
+
+    // run size is a multiple of every block size, so it's always exactly exhausted
+    if self.bump == self.end {
+      let run = map_region(RUN_SIZE, PAGE_SIZE);
+      if run.is_null() {
+        return run;
+      }
+      self.bump = run;
+      self.end = run.wrapping_add(RUN_SIZE);
+    }
+
+    let block = self.bump;
+    self.bump = self.bump.wrapping_add(block_size);
+    block
+  }
+
+  /// # Safety
+  /// `ptr` must be a block of this size class
+  unsafe fn dealloc(&mut self, ptr: *mut u8) {
+    let block = ptr.cast::<FreeBlock>();
+    unsafe {
+      block.write(FreeBlock { next: self.free });
+    }
+    self.free = block;
+  }
+}
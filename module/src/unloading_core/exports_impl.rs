@@ -1,21 +1,32 @@
 use {
   super::{
     ALLOCATOR_LOCK, HOST_OWNER_THREAD, MODULE_ID, alloc_tracker, gen_exports::ModuleExportsImpl,
+    pre_init_alloc,
+  },
+  relib_internal_shared::{
+    AllocStats, BudgetPolicy, ModuleId, exports::___Internal___Exports___ as Exports,
   },
-  relib_internal_shared::{ModuleId, exports::___Internal___Exports___ as Exports},
   std::{ffi::c_void, sync::atomic::Ordering},
 };
 
+#[cfg(target_os = "linux")]
+use relib_internal_shared::{VisitThread, VisitThreadId, VisitUntrackedThread};
+
 impl Exports for ModuleExportsImpl {
   fn init(host_owner_thread: usize, module: ModuleId, enable_alloc_tracker: bool) {
     unsafe {
       HOST_OWNER_THREAD = host_owner_thread;
       MODULE_ID = module;
 
-      if enable_alloc_tracker {
+      // with arena and host allocators there is nothing to track
+      if enable_alloc_tracker
+        && !cfg!(feature = "global_arena_alloc")
+        && !cfg!(feature = "global_host_alloc")
+      {
         alloc_tracker::init();
       }
     }
+    pre_init_alloc::mark_initialized();
   }
 
   fn exit(allocs: relib_internal_shared::SliceAllocation) {
@@ -26,55 +37,76 @@ impl Exports for ModuleExportsImpl {
   }
 
   fn take_cached_allocs_before_exit() {
-    alloc_tracker::send_cached_allocs(None);
+    alloc_tracker::send_cached_allocs();
+  }
+
+  fn cancel_shutdown_token() {
+    super::shutdown::cancel();
   }
 
   fn lock_module_allocator() {
     ALLOCATOR_LOCK.store(true, Ordering::SeqCst);
   }
 
+  fn alloc_stats() -> AllocStats {
+    alloc_tracker::stats()
+  }
+
+  fn alloc_tracker_is_used() -> bool {
+    !cfg!(feature = "global_arena_alloc") && !cfg!(feature = "global_host_alloc")
+  }
+
+  fn set_memory_budget(limit: u64, policy: BudgetPolicy) {
+    alloc_tracker::set_budget(limit, policy);
+  }
+
+  fn is_over_budget() -> bool {
+    alloc_tracker::is_over_budget()
+  }
+
+  fn module_boxes_count() -> u64 {
+    relib_interface::__module_boxes_count()
+  }
+
+  #[cfg(target_os = "linux")]
   fn run_thread_local_dtors() {
-    #[cfg(target_os = "linux")]
-    {
-      use super::thread_locals;
-      unsafe {
-        thread_locals::dtors::run();
-      }
+    unsafe {
+      super::thread_locals::dtors::run();
     }
   }
 
+  #[cfg(target_os = "linux")]
   fn spawned_threads_count() -> u64 {
-    #[cfg(target_os = "linux")]
-    {
-      super::thread_spawn_hook::spawned_threads_count()
-    }
-    #[cfg(target_os = "windows")]
-    {
-      super::helpers::unrecoverable("spawned_threads_count called on windows")
+    super::thread_spawn_hook::spawned_threads_count()
+  }
+
+  #[cfg(target_os = "linux")]
+  fn visit_spawned_threads(visit: VisitThread, ctx: *mut c_void) {
+    super::thread_spawn_hook::visit_spawned_threads(visit, ctx);
+  }
+
+  #[cfg(target_os = "linux")]
+  fn visit_untracked_threads(visit: VisitUntrackedThread, ctx: *mut c_void) {
+    super::untracked_threads::visit(visit, ctx);
+  }
+
+  #[cfg(target_os = "linux")]
+  fn visit_foreign_tls_dtor_threads(visit: VisitThreadId, ctx: *mut c_void) {
+    for thread in super::thread_locals::hook::foreign_dtor_threads() {
+      visit(ctx, thread);
     }
   }
 
+  #[cfg(target_os = "linux")]
   fn misc_cleanup() {
-    #[cfg(target_os = "linux")]
-    {
-      super::mmap_hooks::cleanup();
-      super::pthread_key_hooks::cleanup();
-    }
-    #[cfg(target_os = "windows")]
-    {
-      super::helpers::unrecoverable("unmap_all_mmaps called on windows")
-    }
+    super::mmap_hooks::cleanup();
+    super::pthread_key_hooks::cleanup();
   }
 
+  #[cfg(target_os = "windows")]
   fn set_dealloc_callback(callback: *const c_void) {
-    #[cfg(target_os = "windows")]
     unsafe {
       super::windows_dealloc::set_dealloc_callback(callback);
     }
-    #[cfg(target_os = "linux")]
-    {
-      let _ = callback;
-      super::helpers::unrecoverable("set_dealloc_callback called on linux")
-    }
   }
 }
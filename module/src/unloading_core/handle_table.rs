@@ -0,0 +1,190 @@
+use {
+  super::{MODULE_ID, helpers::unrecoverable},
+  relib_interface::{Handle, StaleHandle},
+  std::sync::{
+    Mutex, MutexGuard, PoisonError,
+    atomic::{AtomicU32, Ordering},
+  },
+};
+
+/// Id of the next table which inserts a value, 0 is reserved for tables without values
+static NEXT_TABLE_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Generational table of values owned by the module, the host holds [`Handle`]s of them
+/// instead of raw pointers and passes them back to module exports.
+///
+/// Handle is only resolved by the table (and load of the module) which created it,
+/// and only while its slot has the same generation, otherwise [`StaleHandle`] is returned,
+/// so there can be any number of tables with the same `T`.
+///
+/// # Example
+/// ```
+/// use relib_interface::{Handle, StaleHandle};
+///
+/// pub struct Widget {
+///   size: u32,
+/// }
+///
+/// static WIDGETS: relib_module::HandleTable<Widget> = relib_module::HandleTable::new();
+///
+/// // implementation of exports
+/// fn create_widget(size: u32) -> Handle<Widget> {
+///   WIDGETS.insert(Widget { size })
+/// }
+///
+/// fn widget_size(widget: Handle<Widget>) -> Result<u32, StaleHandle> {
+///   WIDGETS.with(widget, |widget| widget.size)
+/// }
+/// ```
+pub struct HandleTable<T> {
+  slots: Mutex<Slots<T>>,
+}
+
+struct Slots<T> {
+  /// Assigned on the first insert
+  table_id: u32,
+  entries: Vec<Slot<T>>,
+  /// Indexes of empty slots which can be reused
+  free: Vec<u32>,
+  len: usize,
+}
+
+struct Slot<T> {
+  generation: u32,
+  value: Option<T>,
+}
+
+impl<T> HandleTable<T> {
+  pub const fn new() -> Self {
+    Self {
+      slots: Mutex::new(Slots {
+        table_id: 0,
+        entries: Vec::new(),
+        free: Vec::new(),
+        len: 0,
+      }),
+    }
+  }
+
+  /// Moves the value into the table and returns handle of it.
+  pub fn insert(&self, value: T) -> Handle<T> {
+    let mut slots = self.lock();
+
+    if slots.table_id == 0 {
+      let table_id = NEXT_TABLE_ID.fetch_add(1, Ordering::Relaxed);
+      if table_id == 0 {
+        unrecoverable("too many HandleTables");
+      }
+      slots.table_id = table_id;
+    }
+
+    let index = match slots.free.pop() {
+      Some(index) => index,
+      None => {
+        let Ok(index) = u32::try_from(slots.entries.len()) else {
+          unrecoverable("too many values in HandleTable");
+        };
+        slots.entries.push(Slot {
+          generation: 0,
+          value: None,
+        });
+        index
+      }
+    };
+
+    slots.len += 1;
+    let table_id = slots.table_id;
+    let slot = &mut slots.entries[index as usize];
+    slot.value = Some(value);
+    Handle::__new(module_id(), table_id, index, slot.generation)
+  }
+
+  /// Removes the value from the table, after that the handle (and all copies of it) is stale.
+  pub fn remove(&self, handle: Handle<T>) -> Result<T, StaleHandle> {
+    let mut slots = self.lock();
+    let slot = slots.slot(handle)?;
+
+    let Some(value) = slot.value.take() else {
+      return Err(stale(handle));
+    };
+    slot.generation = slot.generation.wrapping_add(1);
+
+    // slot is retired when its generation is exhausted so old handles can't become valid again
+    let retired = slot.generation == 0;
+    if !retired {
+      slots.free.push(handle.__index());
+    }
+    slots.len -= 1;
+
+    Ok(value)
+  }
+
+  /// Calls `f` with the value of the handle.
+  ///
+  /// Table is locked while `f` is running, so it must not access the same table.
+  /// If `f` panics, the table remains usable.
+  pub fn with<R>(&self, handle: Handle<T>, f: impl FnOnce(&mut T) -> R) -> Result<R, StaleHandle> {
+    let mut slots = self.lock();
+    let slot = slots.slot(handle)?;
+
+    let Some(value) = &mut slot.value else {
+      return Err(stale(handle));
+    };
+    Ok(f(value))
+  }
+
+  /// Returns `true` if the handle is not stale.
+  pub fn contains(&self, handle: Handle<T>) -> bool {
+    self
+      .lock()
+      .slot(handle)
+      .is_ok_and(|slot| slot.value.is_some())
+  }
+
+  /// Returns count of values in the table.
+  pub fn len(&self) -> usize {
+    self.lock().len
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  fn lock(&self) -> MutexGuard<'_, Slots<T>> {
+    // mutex can only be poisoned by panic in `with` callback,
+    // which doesn't leave the slots in inconsistent state
+    self.slots.lock().unwrap_or_else(PoisonError::into_inner)
+  }
+}
+
+impl<T> Default for HandleTable<T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<T> Slots<T> {
+  fn slot(&mut self, handle: Handle<T>) -> Result<&mut Slot<T>, StaleHandle> {
+    // module id is unique for every load, so handles of another module
+    // or previous load of this module are rejected, and table id is unique
+    // for every table of the module (0 only if this table has never created any handles)
+    if handle.module_id() != module_id() || handle.__table() != self.table_id {
+      return Err(stale(handle));
+    }
+
+    match self.entries.get_mut(handle.__index() as usize) {
+      Some(slot) if slot.generation == handle.__generation() => Ok(slot),
+      _ => Err(stale(handle)),
+    }
+  }
+}
+
+fn stale<T>(handle: Handle<T>) -> StaleHandle {
+  StaleHandle {
+    module: handle.module_id(),
+  }
+}
+
+fn module_id() -> relib_internal_shared::ModuleId {
+  unsafe { MODULE_ID }
+}
@@ -0,0 +1,76 @@
+use {
+  super::{
+    MODULE_ID, gen_imports,
+    helpers::{assert_allocator_is_still_accessible, unrecoverable},
+    pre_init_alloc,
+  },
+  relib_interface::Transfer,
+  std::alloc::{GlobalAlloc, Layout},
+};
+
+/// Global allocator which forwards all allocations of the module to the global allocator of the host,
+/// host remembers which module owns them and deallocates leaked ones on unload.
+///
+/// Since host and module use the same allocator, values can be moved between them without cloning
+/// if interface is generated with shared allocator mode (see `relib_interface::GenerateOptions::shared_alloc`).
+/// Allocations made before module is initialized by host are made by `System` (they must not be moved to the host).
+///
+/// **note:** [`Module::memory_stats`](https://docs.rs/relib_host/latest/relib_host/struct.Module.html#method.memory_stats),
+/// memory budget and "dealloc_validation" feature are only supported by [`AllocTracker`](crate::AllocTracker)
+/// (host prints a warning if stats or budget are requested).
+#[derive(Default, Debug)]
+pub struct HostAlloc;
+
+impl HostAlloc {
+  pub const fn new() -> Self {
+    HostAlloc
+  }
+}
+
+unsafe impl GlobalAlloc for HostAlloc {
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    if !pre_init_alloc::is_initialized() {
+      return pre_init_alloc::alloc(layout);
+    }
+    assert_allocator_is_still_accessible();
+
+    unsafe { gen_imports::host_alloc(MODULE_ID, layout.into()) }
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    if unsafe { pre_init_alloc::dealloc(ptr, layout) } {
+      return;
+    }
+    assert_allocator_is_still_accessible();
+
+    unsafe { gen_imports::host_dealloc(MODULE_ID, ptr, layout.into()) }
+  }
+}
+
+/// Called by generated code when value is moved from the host to this module
+pub fn adopt_transferred<T: Transfer>(value: &T) {
+  check_global_allocator();
+
+  value.allocations(&mut |ptr, layout| unsafe {
+    gen_imports::host_alloc_adopt(MODULE_ID, ptr, layout.into());
+  });
+}
+
+/// Called by generated code when value is moved from this module to the host
+pub fn release_transferred<T: Transfer>(value: &T) {
+  check_global_allocator();
+
+  value.allocations(&mut |ptr, _| unsafe {
+    gen_imports::host_alloc_release(MODULE_ID, ptr);
+  });
+}
+
+fn check_global_allocator() {
+  if !cfg!(feature = "global_host_alloc") {
+    unrecoverable(
+      "values can only be moved without cloning if module uses global allocator of the host\n\
+      note: enable \"global_host_alloc\" feature of relib_module \
+      (instead of \"global_alloc_tracker\" or \"global_arena_alloc\")",
+    );
+  }
+}
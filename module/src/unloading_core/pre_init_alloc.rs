@@ -0,0 +1,77 @@
+use std::{
+  alloc::{GlobalAlloc, Layout, System},
+  ptr::null_mut,
+  sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering},
+};
+
+// Before module is initialized by host (for example, in DllMain on Windows or in constructors)
+// imports are not available and module id is unknown, so `ArenaAlloc` and `HostAlloc`
+// allocate with `System` instead and remember such allocations to deallocate them with it too.
+
+/// Usually there are only few of them (runtime of std, thread-locals)
+const CAPACITY: usize = 128;
+
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+static ALLOCATIONS: [AtomicPtr<u8>; CAPACITY] = [const { AtomicPtr::new(null_mut()) }; CAPACITY];
+static LIVE_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Called when host initialized the module
+pub fn mark_initialized() {
+  INITIALIZED.store(true, Ordering::SeqCst);
+}
+
+pub fn is_initialized() -> bool {
+  INITIALIZED.load(Ordering::SeqCst)
+}
+
+pub fn alloc(layout: Layout) -> *mut u8 {
+  let ptr = unsafe { System.alloc(layout) };
+  if ptr.is_null() {
+    return ptr;
+  }
+
+  let remembered = ALLOCATIONS.iter().any(|slot| {
+    slot
+      .compare_exchange(null_mut(), ptr, Ordering::SeqCst, Ordering::SeqCst)
+      .is_ok()
+  });
+  if !remembered {
+    // imports can't be called to report it, so it's reported as allocation failure
+    unsafe { System.dealloc(ptr, layout) };
+    return null_mut();
+  }
+
+  LIVE_ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+  ptr
+}
+
+/// Returns `true` if `ptr` was allocated by [`alloc`] (it's deallocated then)
+///
+/// # Safety
+/// `ptr` must be allocated by global allocator of the module with the same `layout`
+pub unsafe fn dealloc(ptr: *mut u8, layout: Layout) -> bool {
+  if !contains(ptr) {
+    return false;
+  }
+
+  let removed = ALLOCATIONS.iter().any(|slot| {
+    slot
+      .compare_exchange(ptr, null_mut(), Ordering::SeqCst, Ordering::SeqCst)
+      .is_ok()
+  });
+  if !removed {
+    return false;
+  }
+
+  LIVE_ALLOCATIONS.fetch_sub(1, Ordering::SeqCst);
+  unsafe { System.dealloc(ptr, layout) };
+  true
+}
+
+pub fn contains(ptr: *mut u8) -> bool {
+  LIVE_ALLOCATIONS.load(Ordering::SeqCst) != 0
+    && ALLOCATIONS
+      .iter()
+      .any(|slot| slot.load(Ordering::SeqCst) == ptr)
+}
@@ -0,0 +1,182 @@
+use {
+  super::helpers::unrecoverable,
+  std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+      Condvar, Mutex, MutexGuard,
+      atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    task::{Context, Poll, Waker},
+    thread::{self, Thread},
+    time::Duration,
+  },
+};
+
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+static NEXT_WAKER_KEY: AtomicU64 = AtomicU64::new(0);
+static WAITERS: Mutex<Waiters> = Mutex::new(Waiters {
+  parked: Vec::new(),
+  wakers: Vec::new(),
+});
+static CONDVAR: Condvar = Condvar::new();
+
+struct Waiters {
+  parked: Vec<Thread>,
+  /// Key is assigned to [`Cancelled`] future on the first poll, it's removed when future is dropped
+  wakers: Vec<(u64, Waker)>,
+}
+
+fn lock_waiters() -> MutexGuard<'static, Waiters> {
+  let Ok(waiters) = WAITERS.lock() else {
+    unrecoverable("failed to lock shutdown waiters");
+  };
+  waiters
+}
+
+/// Returns token which is cancelled by host when it starts unloading this module
+/// (before calling `before_unload`), after that host waits for module threads to finish
+/// (up to 1 second by default, see `UnloadOptions::wait_for_threads` in relib_host).
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// std::thread::spawn(|| {
+///   let token = relib_module::shutdown_token();
+///   while !token.is_cancelled() {
+///     // do some work...
+///
+///     // sleeps but wakes up immediately when module is unloaded
+///     token.park_timeout(Duration::from_secs(1));
+///   }
+/// });
+/// ```
+pub fn shutdown_token() -> ShutdownToken {
+  ShutdownToken(())
+}
+
+/// See [`shutdown_token`].
+#[derive(Debug, Clone, Copy)]
+pub struct ShutdownToken(());
+
+impl ShutdownToken {
+  /// Returns `true` if host started unloading of the module.
+  pub fn is_cancelled(&self) -> bool {
+    CANCELLED.load(Ordering::SeqCst)
+  }
+
+  /// Blocks current thread until token is cancelled or `timeout` is elapsed
+  /// (using condition variable), returns `true` if token is cancelled.
+  pub fn wait_timeout(&self, timeout: Duration) -> bool {
+    let waiters = lock_waiters();
+    let result = CONDVAR.wait_timeout_while(waiters, timeout, |_| !self.is_cancelled());
+    if result.is_err() {
+      unrecoverable("failed to wait for shutdown");
+    }
+    self.is_cancelled()
+  }
+
+  /// Same as [`std::thread::park_timeout`] but current thread is also unparked
+  /// when token is cancelled, returns `true` if token is cancelled.
+  ///
+  /// **note:** like `park_timeout` it may return spuriously.
+  pub fn park_timeout(&self, timeout: Duration) -> bool {
+    let current = thread::current();
+    let id = current.id();
+
+    lock_waiters().parked.push(current);
+
+    if !self.is_cancelled() {
+      thread::park_timeout(timeout);
+    }
+
+    let mut waiters = lock_waiters();
+    if let Some(idx) = waiters.parked.iter().position(|thread| thread.id() == id) {
+      waiters.parked.swap_remove(idx);
+    }
+
+    self.is_cancelled()
+  }
+
+  /// Returns future which is resolved when token is cancelled.
+  pub fn cancelled(&self) -> Cancelled {
+    Cancelled { waker_key: None }
+  }
+}
+
+/// Future returned by [`ShutdownToken::cancelled`].
+#[derive(Debug)]
+pub struct Cancelled {
+  waker_key: Option<u64>,
+}
+
+impl Future for Cancelled {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+    let this = self.get_mut();
+
+    if CANCELLED.load(Ordering::SeqCst) {
+      return Poll::Ready(());
+    }
+
+    let mut waiters = lock_waiters();
+
+    // checking it again under the lock since it could be cancelled
+    // before waker was registered
+    if CANCELLED.load(Ordering::SeqCst) {
+      return Poll::Ready(());
+    }
+
+    let registered = this
+      .waker_key
+      .and_then(|key| waiters.wakers.iter_mut().find(|(k, _)| *k == key));
+
+    match registered {
+      Some((_, waker)) => {
+        // future may be moved to another task between polls
+        if !waker.will_wake(cx.waker()) {
+          *waker = cx.waker().clone();
+        }
+      }
+      None => {
+        let key = NEXT_WAKER_KEY.fetch_add(1, Ordering::Relaxed);
+        waiters.wakers.push((key, cx.waker().clone()));
+        this.waker_key = Some(key);
+      }
+    }
+
+    Poll::Pending
+  }
+}
+
+impl Drop for Cancelled {
+  fn drop(&mut self) {
+    let Some(key) = self.waker_key else {
+      return;
+    };
+
+    let mut waiters = lock_waiters();
+    if let Some(idx) = waiters.wakers.iter().position(|(k, _)| *k == key) {
+      waiters.wakers.swap_remove(idx);
+    }
+  }
+}
+
+pub fn cancel() {
+  let mut waiters = lock_waiters();
+  CANCELLED.store(true, Ordering::SeqCst);
+
+  CONDVAR.notify_all();
+
+  for thread in &waiters.parked {
+    thread.unpark();
+  }
+
+  // waking outside of the lock since executor may poll the future right away
+  let wakers = std::mem::take(&mut waiters.wakers);
+  drop(waiters);
+  for (_, waker) in wakers {
+    waker.wake();
+  }
+}
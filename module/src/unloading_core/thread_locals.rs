@@ -1,2 +1,2 @@
-mod hook;
+pub mod hook;
 pub mod dtors;
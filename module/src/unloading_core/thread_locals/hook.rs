@@ -1,8 +1,37 @@
 use {
   super::{super::helpers::is_it_host_owner_thread, dtors},
-  std::{ffi::c_void, mem::transmute},
+  std::{
+    ffi::c_void,
+    mem::transmute,
+    sync::atomic::{AtomicU64, Ordering},
+  },
 };
 
+/// Threads (not the host owner one) which registered destructors of thread-locals
+/// using original `__cxa_thread_atexit_impl`, glibc won't unmap this module
+/// until they exit. Fixed size array since it's only used for diagnostics
+/// of failed unloading.
+static FOREIGN_DTOR_THREADS: [AtomicU64; 64] = [const { AtomicU64::new(0) }; 64];
+
+pub fn foreign_dtor_threads() -> impl Iterator<Item = u64> {
+  FOREIGN_DTOR_THREADS
+    .iter()
+    .map(|thread| thread.load(Ordering::SeqCst))
+    .filter(|&thread| thread != 0)
+}
+
+fn register_foreign_dtor_thread() {
+  let current = unsafe { libc::gettid() } as u64;
+
+  for slot in &FOREIGN_DTOR_THREADS {
+    match slot.compare_exchange(0, current, Ordering::SeqCst, Ordering::SeqCst) {
+      Ok(_) => return,
+      Err(thread) if thread == current => return,
+      Err(_) => {}
+    }
+  }
+}
+
 // This function is called when some thread-local registers destructor callback (here it's `dtor`)
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn __cxa_thread_atexit_impl(
@@ -25,6 +54,8 @@ pub unsafe extern "C" fn __cxa_thread_atexit_impl(
 
       let dtor = dtor as *mut libc::c_void;
       original_impl(dtor, obj, dso_symbol);
+
+      register_foreign_dtor_thread();
     }
     // otherwise use custom implementation so we can unload them when we
     // no longer need this dynamic library to be loaded
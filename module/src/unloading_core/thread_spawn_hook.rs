@@ -1,4 +1,16 @@
-use std::sync::atomic::{AtomicU64, Ordering};
+use {
+  super::helpers::unrecoverable,
+  relib_internal_shared::{Str, ThreadInfo, VisitThread},
+  std::{
+    backtrace::{Backtrace, BacktraceStatus},
+    ffi::c_void,
+    cell::Cell,
+    sync::{
+      Arc, Mutex, MutexGuard,
+      atomic::{AtomicU64, Ordering},
+    },
+  },
+};
 
 static SPAWNED_THREADS_COUNT: AtomicU64 = AtomicU64::new(0);
 
@@ -6,9 +18,99 @@ pub fn spawned_threads_count() -> u64 {
   SPAWNED_THREADS_COUNT.load(Ordering::Relaxed)
 }
 
+struct SpawnedThread {
+  key: u64,
+  os_id: u64,
+  start_routine: usize,
+  /// Full name of the thread (see `pthread_setname_np` hook), empty if it's not named
+  name: String,
+  /// Captured only if it's enabled by `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE`
+  /// environment variables (see `std::backtrace::Backtrace::capture`),
+  /// shared so it can be formatted without holding the lock of the registry
+  spawn_backtrace: Arc<Backtrace>,
+}
+
+/// Registry of threads spawned by this module to tell host which ones are still running
+static SPAWNED_THREADS: Mutex<Vec<SpawnedThread>> = Mutex::new(Vec::new());
+static NEXT_THREAD_KEY: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+  /// `true` while start routine of a thread spawned by this module is running,
+  /// in that case thread-locals of std are not destroyed yet
+  /// (see `pthread_setname_np` hook)
+  static IN_START_ROUTINE: Cell<bool> = const { Cell::new(false) };
+}
+
+fn lock_spawned_threads() -> MutexGuard<'static, Vec<SpawnedThread>> {
+  let Ok(threads) = SPAWNED_THREADS.lock() else {
+    unrecoverable("failed to lock SPAWNED_THREADS");
+  };
+  threads
+}
+
+pub fn visit_spawned_threads(visit: VisitThread, ctx: *mut c_void) {
+  // backtraces are resolved and formatted after releasing the lock,
+  // otherwise spawning and exiting threads of the module would wait for symbolization
+  let threads: Vec<_> = lock_spawned_threads()
+    .iter()
+    .map(|thread| {
+      (
+        thread.os_id,
+        thread.start_routine,
+        thread.name.clone(),
+        Arc::clone(&thread.spawn_backtrace),
+      )
+    })
+    .collect();
+
+  for (os_id, start_routine, name, spawn_backtrace) in threads {
+    let spawn_backtrace = match spawn_backtrace.status() {
+      BacktraceStatus::Captured => spawn_backtrace.to_string(),
+      _ => String::new(),
+    };
+
+    visit(
+      ctx,
+      ThreadInfo {
+        os_id,
+        start_routine,
+        name: Str::from(name.as_str()),
+        spawn_backtrace: Str::from(spawn_backtrace.as_str()),
+      },
+    );
+  }
+}
+
+pub fn spawned_threads_os_ids() -> Vec<u64> {
+  let threads = lock_spawned_threads();
+  threads.iter().map(|thread| thread.os_id).collect()
+}
+
+fn set_spawned_thread_os_id(key: u64, os_id: u64) {
+  let mut threads = lock_spawned_threads();
+  if let Some(thread) = threads.iter_mut().find(|thread| thread.key == key) {
+    thread.os_id = os_id;
+  }
+}
+
+fn set_spawned_thread_name(os_id: u64, name: String) {
+  let mut threads = lock_spawned_threads();
+  if let Some(thread) = threads.iter_mut().find(|thread| thread.os_id == os_id) {
+    thread.name = name;
+  }
+}
+
+fn remove_spawned_thread(key: u64) {
+  let mut threads = lock_spawned_threads();
+  if let Some(idx) = threads.iter().position(|thread| thread.key == key) {
+    threads.swap_remove(idx);
+  }
+}
+
 type Payload = (
   extern "C" fn(*mut libc::c_void) -> *mut libc::c_void,
   *mut libc::c_void,
+  u64,
 );
 
 #[unsafe(no_mangle)]
@@ -18,7 +120,19 @@ unsafe extern "C" fn pthread_create(
   f: extern "C" fn(*mut libc::c_void) -> *mut libc::c_void,
   value: *mut libc::c_void,
 ) -> libc::c_int {
-  let payload: Payload = (f, value);
+  // thread is registered before it's started since it can finish
+  // even before original pthread_create returns
+  let key = NEXT_THREAD_KEY.fetch_add(1, Ordering::Relaxed);
+  let spawn_backtrace = Arc::new(Backtrace::capture());
+  lock_spawned_threads().push(SpawnedThread {
+    key,
+    os_id: 0,
+    start_routine: f as usize,
+    name: String::new(),
+    spawn_backtrace,
+  });
+
+  let payload: Payload = (f, value, key);
   let payload = Box::new(payload);
   let payload = Box::into_raw(payload);
 
@@ -37,18 +151,59 @@ unsafe extern "C" fn pthread_create(
 
   if result == 0 {
     SPAWNED_THREADS_COUNT.fetch_add(1, Ordering::Relaxed);
+  } else {
+    remove_spawned_thread(key);
   }
 
   result
 }
 
 extern "C" fn thread_start(payload: *mut libc::c_void) -> *mut libc::c_void {
-  let ret = unsafe {
-    let (f, value) = *Box::from_raw(payload as *mut Payload);
-    f(value)
-  };
+  let (f, value, key) = unsafe { *Box::from_raw(payload as *mut Payload) };
+
+  set_spawned_thread_os_id(key, unsafe { libc::gettid() } as u64);
 
+  IN_START_ROUTINE.set(true);
+  let ret = f(value);
+  IN_START_ROUTINE.set(false);
+
+  remove_spawned_thread(key);
   SPAWNED_THREADS_COUNT.fetch_sub(1, Ordering::Relaxed);
 
   ret
 }
+
+// std sets name of the spawned thread (`std::thread::Builder::name`) in the thread itself
+// right after `std::thread::current()` is initialized, name passed to this function
+// is truncated to 15 bytes (limit of Linux), so the full one is taken from std.
+// `std::thread::current()` aborts if thread-locals of std are already destroyed
+// (for example, when it's called from a thread-local destructor), so it's only used
+// while start routine of the thread is running, otherwise name passed to this function is used
+#[unsafe(no_mangle)]
+unsafe extern "C" fn pthread_setname_np(
+  thread: libc::pthread_t,
+  name: *const libc::c_char,
+) -> libc::c_int {
+  if unsafe { libc::pthread_equal(thread, libc::pthread_self()) } != 0 {
+    let full_name = if IN_START_ROUTINE.get() {
+      std::thread::current().name().map(ToOwned::to_owned)
+    } else {
+      None
+    };
+    let name = full_name.unwrap_or_else(|| {
+      unsafe { std::ffi::CStr::from_ptr(name) }
+        .to_string_lossy()
+        .into_owned()
+    });
+    set_spawned_thread_name(unsafe { libc::gettid() } as u64, name);
+  }
+
+  type OriginalImpl =
+    extern "C" fn(thread: libc::pthread_t, name: *const libc::c_char) -> libc::c_int;
+
+  // TODO: SAFETY
+  let original_impl: OriginalImpl =
+    unsafe { std::mem::transmute(libc::dlsym(libc::RTLD_NEXT, c"pthread_setname_np".as_ptr())) };
+
+  original_impl(thread, name)
+}
@@ -0,0 +1,190 @@
+//! Detection of threads which run module code but weren't spawned through
+//! `pthread_create` hook (for example, raw `clone` syscalls or C libraries
+//! resolving pthread functions differently).
+
+use {
+  super::thread_spawn_hook,
+  relib_internal_shared::{UntrackedThread, UntrackedThreadEvidence, VisitUntrackedThread},
+  std::{ffi::c_void, fs, mem::MaybeUninit, ops::Range, thread, time::Duration},
+};
+
+/// How much of the stack (starting from stack pointer) is scanned for return addresses
+const STACK_SCAN_PAGES: usize = 64;
+const PAGE_SIZE: usize = 4096;
+
+/// How many times thread is sampled if it's running at the moment
+const SAMPLE_ATTEMPTS: usize = 10;
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(1);
+
+pub fn visit(visit: VisitUntrackedThread, ctx: *mut c_void) {
+  let ranges = module_code_ranges();
+  if ranges.is_empty() {
+    return;
+  }
+
+  let Ok(tasks) = fs::read_dir("/proc/self/task") else {
+    return;
+  };
+
+  let current_thread = unsafe { libc::gettid() } as u64;
+  let tracked_threads = thread_spawn_hook::spawned_threads_os_ids();
+
+  for task in tasks.flatten() {
+    let Some(os_id) = task
+      .file_name()
+      .to_str()
+      .and_then(|tid| tid.parse::<u64>().ok())
+    else {
+      continue;
+    };
+    if os_id == current_thread || tracked_threads.contains(&os_id) {
+      continue;
+    }
+
+    if let Some((module_address, evidence)) = inspect_thread(os_id, &ranges) {
+      visit(
+        ctx,
+        UntrackedThread {
+          os_id,
+          module_address,
+          evidence,
+        },
+      );
+    }
+  }
+}
+
+fn inspect_thread(os_id: u64, ranges: &[Range<usize>]) -> Option<(usize, UntrackedThreadEvidence)> {
+  let Some((sp, pc)) = sample_thread(os_id)? else {
+    // it may be busy running module code, host decides what to do with it
+    return Some((0, UntrackedThreadEvidence::Running));
+  };
+
+  let in_module = |addr: usize| ranges.iter().any(|range| range.contains(&addr));
+
+  if in_module(pc) {
+    return Some((pc, UntrackedThreadEvidence::InstructionPointer));
+  }
+
+  read_stack(sp)
+    .into_iter()
+    .find(|&word| in_module(word))
+    .map(|addr| (addr, UntrackedThreadEvidence::Stack))
+}
+
+/// Returns stack and instruction pointers of the thread, `Some(None)` if it was running
+/// every time it was sampled, `None` if it can't be inspected at all (for example, it already exited)
+fn sample_thread(os_id: u64) -> Option<Option<(usize, usize)>> {
+  for attempt in 0..SAMPLE_ATTEMPTS {
+    if attempt != 0 {
+      thread::sleep(SAMPLE_INTERVAL);
+    }
+
+    // "<nr> <args...> <sp> <pc>" if thread is blocked (nr is -1 if it's not in syscall),
+    // "running" if it's running right now (nothing can be inspected in that case)
+    let syscall = fs::read_to_string(format!("/proc/self/task/{os_id}/syscall")).ok()?;
+    if syscall.trim() == "running" {
+      continue;
+    }
+
+    let mut fields = syscall.split_whitespace().rev();
+    let pc = parse_hex(fields.next()?)?;
+    let sp = parse_hex(fields.next()?)?;
+    return Some(Some((sp, pc)));
+  }
+
+  Some(None)
+}
+
+fn parse_hex(value: &str) -> Option<usize> {
+  usize::from_str_radix(value.strip_prefix("0x")?, 16).ok()
+}
+
+/// Reads words of the stack of another thread, stops at the end of the stack mapping
+fn read_stack(sp: usize) -> Vec<usize> {
+  let start = sp & !(PAGE_SIZE - 1);
+  let mut buf = vec![0_usize; STACK_SCAN_PAGES * PAGE_SIZE / size_of::<usize>()];
+
+  let local = libc::iovec {
+    iov_base: buf.as_mut_ptr() as *mut c_void,
+    iov_len: buf.len() * size_of::<usize>(),
+  };
+  // page-sized remote iovecs so the read stops at the first unmapped page
+  // instead of failing as a whole
+  let remote: Vec<libc::iovec> = (0..STACK_SCAN_PAGES)
+    .map(|page| libc::iovec {
+      iov_base: (start + page * PAGE_SIZE) as *mut c_void,
+      iov_len: PAGE_SIZE,
+    })
+    .collect();
+
+  let read = unsafe {
+    libc::process_vm_readv(
+      libc::getpid(),
+      &local,
+      1,
+      remote.as_ptr(),
+      remote.len() as libc::c_ulong,
+      0,
+    )
+  };
+  if read <= 0 {
+    return Vec::new();
+  }
+
+  let skip = (sp - start) / size_of::<usize>();
+  let read = read as usize / size_of::<usize>();
+  buf.truncate(read);
+  buf.drain(..skip.min(read));
+  buf
+}
+
+/// Executable segments of this module
+fn module_code_ranges() -> Vec<Range<usize>> {
+  let mut info = MaybeUninit::<libc::Dl_info>::uninit();
+  let found = unsafe { libc::dladdr(module_code_ranges as *const c_void, info.as_mut_ptr()) };
+  if found == 0 {
+    return Vec::new();
+  }
+  // SAFETY: dladdr returned non-zero so info is initialized
+  let base = unsafe { info.assume_init() }.dli_fbase as usize;
+
+  struct Ctx {
+    base: usize,
+    ranges: Vec<Range<usize>>,
+  }
+
+  unsafe extern "C" fn callback(
+    info: *mut libc::dl_phdr_info,
+    _size: libc::size_t,
+    ctx: *mut c_void,
+  ) -> libc::c_int {
+    let ctx = unsafe { &mut *(ctx as *mut Ctx) };
+    let info = unsafe { &*info };
+
+    let addr = info.dlpi_addr as usize;
+    if addr != ctx.base {
+      return 0;
+    }
+
+    let headers = unsafe { std::slice::from_raw_parts(info.dlpi_phdr, info.dlpi_phnum as usize) };
+    for header in headers {
+      if header.p_type == libc::PT_LOAD && header.p_flags & libc::PF_X != 0 {
+        let start = addr + header.p_vaddr as usize;
+        ctx.ranges.push(start..start + header.p_memsz as usize);
+      }
+    }
+
+    // found it, stop iterating
+    1
+  }
+
+  let mut ctx = Ctx {
+    base,
+    ranges: Vec::new(),
+  };
+  unsafe {
+    libc::dl_iterate_phdr(Some(callback), &mut ctx as *mut Ctx as *mut c_void);
+  }
+  ctx.ranges
+}
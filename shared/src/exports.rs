@@ -1,20 +1,41 @@
 use {
-  crate::{ModuleId, SliceAllocation},
+  crate::{AllocStats, BudgetPolicy, ModuleId, SliceAllocation},
   std::ffi::c_void,
 };
 
+#[cfg(target_os = "linux")]
+use crate::{VisitThread, VisitThreadId, VisitUntrackedThread};
+
 #[expect(non_camel_case_types)]
 pub trait ___Internal___Exports___ {
   fn init(host_owner_thread: usize, module: ModuleId, enable_alloc_tracker: bool);
   fn exit(allocs: SliceAllocation);
   fn take_cached_allocs_before_exit();
+  fn cancel_shutdown_token();
   fn lock_module_allocator();
+  fn alloc_stats() -> AllocStats;
+  /// `false` if global allocator of the module is `ArenaAlloc` or `HostAlloc`,
+  /// they don't count allocations so there are no stats and memory budget
+  fn alloc_tracker_is_used() -> bool;
+  /// `u64::MAX` limit means no budget
+  fn set_memory_budget(limit: u64, policy: BudgetPolicy);
+  fn is_over_budget() -> bool;
+  /// Count of alive `relib_interface::ModuleBox` handles created by the module
+  fn module_boxes_count() -> u64;
 
-  // linux-only
+  #[cfg(target_os = "linux")]
   fn spawned_threads_count() -> u64;
+  #[cfg(target_os = "linux")]
+  fn visit_spawned_threads(visit: VisitThread, ctx: *mut c_void);
+  #[cfg(target_os = "linux")]
+  fn visit_untracked_threads(visit: VisitUntrackedThread, ctx: *mut c_void);
+  #[cfg(target_os = "linux")]
+  fn visit_foreign_tls_dtor_threads(visit: VisitThreadId, ctx: *mut c_void);
+  #[cfg(target_os = "linux")]
   fn run_thread_local_dtors();
+  #[cfg(target_os = "linux")]
   fn misc_cleanup();
 
-  // windows-only
+  #[cfg(target_os = "windows")]
   fn set_dealloc_callback(callback: *const c_void);
 }
@@ -6,4 +6,16 @@ pub trait ___Internal___Imports___ {
   fn on_cached_allocs(module: ModuleId, ops: SliceAllocatorOp);
   fn unrecoverable(module: ModuleId, message: Str) -> !;
   fn is_ptr_allocated(module: ModuleId, ptr: *mut u8) -> bool;
+  /// Returns `true` if allocation should be allowed
+  fn on_memory_budget_exceeded(module: ModuleId, requested: usize, live_bytes: u64) -> bool;
+  /// Maps memory region for arena allocator of the module, returns null on failure
+  fn arena_map(module: ModuleId, size: usize, align: usize) -> *mut u8;
+  fn arena_unmap(module: ModuleId, ptr: *mut u8);
+  /// Allocates memory using global allocator of the host, returns null on failure
+  fn host_alloc(module: ModuleId, layout: StableLayout) -> *mut u8;
+  fn host_dealloc(module: ModuleId, ptr: *mut u8, layout: StableLayout);
+  /// Allocation of the value moved from host to the module is now owned by the module
+  fn host_alloc_adopt(module: ModuleId, ptr: *mut u8, layout: StableLayout);
+  /// Allocation of the value moved from module to the host is no longer owned by the module
+  fn host_alloc_release(module: ModuleId, ptr: *mut u8);
 }
@@ -1,6 +1,8 @@
 use std::{
   alloc::Layout,
+  ffi::c_void,
   fmt::{Debug, Formatter, Result as FmtResult},
+  hash::{BuildHasherDefault, Hasher},
 };
 
 pub mod exports;
@@ -17,6 +19,47 @@ pub struct AllocatorPtr(pub *mut u8);
 unsafe impl Send for AllocatorPtr {}
 unsafe impl Sync for AllocatorPtr {}
 
+const ALLOC_SHARD_BITS: u32 = 4;
+
+/// Number of shards of allocation caches in the module and allocation storages in the host.
+/// Allocations are split between shards by pointer (not by thread), so alloc and dealloc
+/// of the same pointer always end up in the same shard and host receives them in the right order
+pub const ALLOC_SHARDS: usize = 1 << ALLOC_SHARD_BITS;
+
+impl AllocatorPtr {
+  /// Index of the shard of this pointer, see [`ALLOC_SHARDS`]
+  pub fn shard(self) -> usize {
+    // low bits of pointers returned by allocators are almost always zeroed because of alignment
+    let hash = ((self.0.addr() as u64) >> 4).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    (hash >> (u64::BITS - ALLOC_SHARD_BITS)) as usize
+  }
+}
+
+/// Hasher for maps with [`AllocatorPtr`] keys, default SipHash is too slow for the allocator hot path
+#[derive(Default)]
+pub struct PtrHasher(u64);
+
+impl Hasher for PtrHasher {
+  fn write(&mut self, bytes: &[u8]) {
+    for byte in bytes {
+      self.0 = self.0.rotate_left(8) ^ u64::from(*byte);
+    }
+  }
+
+  fn write_usize(&mut self, value: usize) {
+    self.0 ^= value as u64;
+  }
+
+  fn finish(&self) -> u64 {
+    // multiplier is different from the one in `AllocatorPtr::shard`,
+    // otherwise high bits of the hash would be the same for all pointers of the shard
+    let hash = self.0.wrapping_mul(0xf135_7aea_2e62_a9c5);
+    hash ^ (hash >> 32)
+  }
+}
+
+pub type PtrBuildHasher = BuildHasherDefault<PtrHasher>;
+
 #[repr(C)]
 #[derive(Clone, PartialEq)]
 pub struct Allocation(pub AllocatorPtr, pub StableLayout);
@@ -81,6 +124,12 @@ impl<T> RawSlice<T> {
     unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
   }
 
+  /// # Safety
+  /// See `Safety` of [`std::slice::from_raw_parts_mut`], the slice must be created from `&mut [T]`
+  pub unsafe fn into_slice_mut<'a>(self) -> &'a mut [T] {
+    unsafe { std::slice::from_raw_parts_mut(self.ptr.cast_mut(), self.len) }
+  }
+
   /// # Safety
   /// See `Safety` of [`std::slice::from_raw_parts`]
   pub unsafe fn to_vec(&self) -> Vec<T>
@@ -100,8 +149,27 @@ impl<T> From<&[T]> for RawSlice<T> {
   }
 }
 
+impl<T> From<&mut [T]> for RawSlice<T> {
+  fn from(value: &mut [T]) -> Self {
+    RawSlice {
+      ptr: value.as_mut_ptr(),
+      len: value.len(),
+    }
+  }
+}
+
+// derive would require T: Copy
+impl<T> Clone for RawSlice<T> {
+  fn clone(&self) -> Self {
+    *self
+  }
+}
+
+impl<T> Copy for RawSlice<T> {}
+
 /// ABI-stable `&str`
 #[repr(C)]
+#[derive(Clone, Copy)]
 pub struct Str(RawSlice<u8>);
 
 impl Str {
@@ -112,6 +180,15 @@ impl Str {
     std::str::from_utf8(bytes).expect("Failed to get valid UTF-8 string slice back")
   }
 
+  /// Same as [`Str::into_str`] but without UTF-8 validation
+  ///
+  /// # Safety
+  /// See `Safety` of [`std::slice::from_raw_parts`], the string must be created from `&str`
+  pub unsafe fn into_str_unchecked<'a>(self) -> &'a str {
+    let bytes = unsafe { self.0.into_slice() };
+    unsafe { std::str::from_utf8_unchecked(bytes) }
+  }
+
   /// # Safety
   /// See `Safety` of [`std::slice::from_raw_parts`]
   pub unsafe fn to_string(&self) -> String {
@@ -139,8 +216,103 @@ impl From<&str> for Str {
 unsafe impl Send for Str {}
 unsafe impl Sync for Str {}
 
+/// ABI-stable `&dyn Fn` or `&mut dyn FnMut`
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Callback<F> {
+  /// Pointer to the reference to the closure
+  pub data: *mut c_void,
+  /// `extern "C" fn(data, success, args...) -> MaybeUninit<R>` which calls the closure,
+  /// `success` is set to false if it panics
+  pub call: F,
+}
+
 pub type ModuleId = u64;
 
+/// Thread spawned by module which is still running
+#[repr(C)]
+pub struct ThreadInfo {
+  /// Id given by operating system, 0 if thread is not started yet
+  pub os_id: u64,
+  /// Address of the function passed to thread spawn function
+  pub start_routine: usize,
+  /// Full name of the thread (not truncated by the OS), empty if it's not named
+  pub name: Str,
+  /// Empty if backtrace wasn't captured
+  pub spawn_backtrace: Str,
+}
+
+pub type VisitThread = extern "C" fn(ctx: *mut c_void, thread: ThreadInfo);
+
+/// Thread which runs module code but wasn't spawned by it
+/// (or was spawned bypassing thread spawn hook)
+#[repr(C)]
+pub struct UntrackedThread {
+  pub os_id: u64,
+  /// Address inside of the module code which was found (zero if evidence is `Running`)
+  pub module_address: usize,
+  pub evidence: UntrackedThreadEvidence,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UntrackedThreadEvidence {
+  /// Thread is currently executing module code
+  InstructionPointer,
+  /// Stack of the thread contains return address into module code
+  Stack,
+  /// Thread was running every time it was sampled, so it couldn't be inspected
+  Running,
+}
+
+pub type VisitThreadId = extern "C" fn(ctx: *mut c_void, os_id: u64);
+
+pub type VisitUntrackedThread = extern "C" fn(ctx: *mut c_void, thread: UntrackedThread);
+
+/// What module allocator does when allocation would exceed memory budget
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BudgetPolicy {
+  /// Return null
+  Fail,
+  /// Allocate anyway
+  Allow,
+  /// Ask host (`on_memory_budget_exceeded` import)
+  Callback,
+}
+
+pub const ALLOC_SIZE_CLASSES: usize = 21;
+
+/// Statistics of global allocator of the module
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct AllocStats {
+  pub live_allocations: u64,
+  pub live_bytes: u64,
+  pub peak_bytes: u64,
+  pub total_allocations: u64,
+  pub total_deallocations: u64,
+  /// Number of live allocations by size class, see [`alloc_size_class`]
+  pub live_by_size_class: [u64; ALLOC_SIZE_CLASSES],
+}
+
+/// Index of size class of allocation: first one is for sizes up to 16 bytes,
+/// each next one doubles the upper bound and the last one is for everything bigger
+pub fn alloc_size_class(size: usize) -> usize {
+  // number of bits needed for (size - 1), minus 4 bits of the first class
+  let class = (usize::BITS - (size.max(16) - 1).leading_zeros()) as usize - 4;
+  class.min(ALLOC_SIZE_CLASSES - 1)
+}
+
+/// Upper bound (inclusive) of size class, `None` for the last one
+pub fn alloc_size_class_max(class: usize) -> Option<usize> {
+  if class >= ALLOC_SIZE_CLASSES - 1 {
+    None
+  } else {
+    Some(16 << class)
+  }
+}
+
 #[macro_export]
 macro_rules! output_to_return_type {
   ($output:ident) => {
@@ -5,4 +5,16 @@ fn main() {
     test_shared::IMPORTS,
     "test_shared::imports::Imports",
   );
+
+  if std::env::var_os("CARGO_FEATURE_HOST_ALLOC").is_some() {
+    relib_interface::host::generate_with_options(
+      &relib_interface::GenerateOptions::default()
+        .prefix("host_alloc")
+        .shared_alloc(true),
+      test_shared::HOST_ALLOC_EXPORTS,
+      "test_shared::host_alloc_exports::HostAllocExports",
+      test_shared::HOST_ALLOC_IMPORTS,
+      "test_shared::host_alloc_imports::HostAllocImports",
+    );
+  }
 }
@@ -0,0 +1,21 @@
+use cfg_if::cfg_if;
+
+cfg_if! {
+  if #[cfg(feature = "alloc_bench")] {
+    use crate::shared::{init_module_imports, load_module};
+
+    /// Timings depend too much on the machine to assert them, so it's not run by testing/runner,
+    /// run it manually in release mode:
+    /// `cargo build --workspace --release --features alloc_bench && target/release/test_host`
+    pub fn main() {
+      // main of the module runs the benchmark and prints the results
+      let (module, _) = load_module::<(), ()>(init_module_imports, true);
+
+      module.unload().unwrap();
+    }
+  } else {
+    pub fn main() {
+      panic!("this branch must not be called");
+    }
+  }
+}
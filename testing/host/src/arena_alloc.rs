@@ -0,0 +1,64 @@
+use cfg_if::cfg_if;
+
+cfg_if! {
+  if #[cfg(feature = "arena_alloc")] {
+    use {
+      crate::shared::{ModuleExports, init_module_imports, load_module},
+      libloading::library_filename,
+      relib_host::{MemoryBudget, Module, SoakOptions, SoakResource},
+      std::time::Instant,
+      test_host_shared::current_target_dir,
+    };
+
+    pub fn main() {
+      let (module, _) = load_module::<ModuleExports, ()>(init_module_imports, true);
+
+      // not supported by arena allocator, host prints a warning
+      module.set_memory_budget(Some(MemoryBudget::new(1)));
+      assert!(unsafe { module.exports().alloc_mem() }.is_some());
+      assert!(!module.is_over_budget());
+      assert_eq!(module.memory_stats().total_allocations, 0);
+
+      let start = Instant::now();
+      module.unload().unwrap_or_else(|e| {
+        panic!("{e:#}");
+      });
+      println!("unloading of module with 200k leaked allocations took {:?}", start.elapsed());
+
+      let path = current_target_dir().join(library_filename("test_module"));
+      let report = unsafe {
+        relib_host::soak(
+          path,
+          SoakOptions::default().cycles(6).warmup_cycles(2),
+          || init_module_imports,
+          |module: &Module<ModuleExports>| {
+            let ret = module.call_main::<()>();
+            assert!(ret.is_some(), "module main fn panicked");
+          },
+        )
+      };
+      let report = report.unwrap_or_else(|e| {
+        panic!("soak failed: {e:#}");
+      });
+
+      // all regions of the arena must be unmapped
+      assert_eq!(
+        report.samples.last().unwrap().tracked_allocations,
+        report.baseline.tracked_allocations
+      );
+
+      let growth: Vec<_> = report
+        .growth
+        .iter()
+        .filter(|growth| growth.resource != SoakResource::ResidentMemory)
+        .collect();
+      assert!(growth.is_empty(), "{report}");
+
+      println!("checked: {report}");
+    }
+  } else {
+    pub fn main() {
+      panic!("this branch must not be called");
+    }
+  }
+}
@@ -0,0 +1,36 @@
+use {
+  crate::shared::{init_module_imports, load_module},
+  cfg_if::cfg_if,
+  relib_host::{Module, ModuleExportsForHost},
+};
+
+pub fn main() {
+  let (module, _) = load_module::<(), ()>(init_module_imports, true);
+  unload_module(module);
+}
+
+fn unload_module<E: ModuleExportsForHost>(module: Module<E>) {
+  cfg_if! {
+    if #[cfg(feature = "before_unload_timeout")] {
+      use {
+        std::time::Duration,
+        relib_host::{UnloadError, UnloadOptions},
+      };
+
+      let options = UnloadOptions::default().before_unload_timeout(Duration::from_millis(300));
+      let err = module.unload_with(options).unwrap_err();
+      let UnloadError::BeforeUnloadTimedOut(_, hung) = err else {
+        panic!("unexpected error: {err}");
+      };
+      assert_eq!(hung.call, "before_unload");
+
+      #[cfg(target_os = "linux")]
+      assert!(hung.thread.os_id.is_some());
+
+      println!("checked");
+    } else {
+      drop(module);
+      panic!("this branch must not be called");
+    }
+  }
+}
@@ -0,0 +1,44 @@
+use cfg_if::cfg_if;
+
+cfg_if! {
+  if #[cfg(feature = "call_timeout")] {
+    use {
+      crate::shared::{init_module_imports, load_module},
+      relib_host::UnloadError,
+      std::time::Duration,
+      test_shared::call_timeout::gen_exports::ModuleExports,
+    };
+
+    pub fn main() {
+      let (module, main) = load_module::<ModuleExports, u8>(init_module_imports, true);
+      assert_eq!(main, Some(1));
+
+      let main = unsafe { module.call_main_with_timeout::<u8>(Duration::from_secs(5)) };
+      assert_eq!(main.unwrap(), Some(1));
+
+      let sum = module.call_with_timeout(Duration::from_secs(5), |exports| unsafe { exports.add(1, 2) });
+      assert_eq!(sum.unwrap(), Some(3));
+      assert!(!module.is_hung());
+
+      let hung = module
+        .call_with_timeout(Duration::from_millis(300), |exports| unsafe { exports.hang() })
+        .unwrap_err();
+      assert_eq!(hung.call, "exports");
+      assert_eq!(hung.timeout, Duration::from_millis(300));
+
+      #[cfg(target_os = "linux")]
+      assert!(hung.thread.os_id.is_some());
+
+      assert!(module.is_hung());
+
+      // thread is still stuck in the module, so it can't be unloaded
+      let Err(UnloadError::ModuleHung(_)) = module.unload() else {
+        panic!("unloading must fail");
+      };
+    }
+  } else {
+    pub fn main() {
+      panic!("this branch must not be called");
+    }
+  }
+}
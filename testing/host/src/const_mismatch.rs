@@ -0,0 +1,17 @@
+use {
+  crate::shared::{ModuleExports, init_module_imports},
+  relib_host::LoadError,
+  test_host_shared::load_module_with_result,
+};
+
+pub fn main() {
+  // module is compiled with a different value of EXPORTS_VERSION constant
+  for _ in 1..=2 {
+    let result = load_module_with_result::<ModuleExports, ()>(init_module_imports, true);
+    let Err(LoadError::ConstMismatch { trait_path, name }) = result else {
+      panic!("expected ConstMismatch, got: {:?}", result.map(|_| ()));
+    };
+    assert_eq!(trait_path, "test_shared::exports::Exports");
+    assert_eq!(name, "EXPORTS_VERSION");
+  }
+}
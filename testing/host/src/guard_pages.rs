@@ -0,0 +1,32 @@
+use cfg_if::cfg_if;
+
+cfg_if! {
+  if #[cfg(feature = "guard_pages")] {
+    use {
+      crate::shared::{ModuleExports, init_module_imports, load_module},
+      relib_host::UnloadOptions,
+    };
+
+    pub fn main() {
+      let (module, fn_addr) = load_module::<ModuleExports, usize>(init_module_imports, true);
+      let fn_addr = fn_addr.unwrap();
+      let in_module: extern "C" fn() -> u32 = unsafe { std::mem::transmute(fn_addr) };
+      assert_eq!(in_module(), 42);
+
+      module
+        .unload_with(UnloadOptions::default().guard_unloaded_memory(true))
+        .unwrap_or_else(|e| {
+          panic!("{e:#}");
+        });
+
+      eprintln!("this is expected:");
+      in_module();
+
+      unreachable!("call into unloaded module must abort the process");
+    }
+  } else {
+    pub fn main() {
+      panic!("this branch must not be called");
+    }
+  }
+}
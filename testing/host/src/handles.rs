@@ -0,0 +1,123 @@
+use cfg_if::cfg_if;
+
+cfg_if! {
+  if #[cfg(feature = "handles")] {
+    use {
+      crate::shared::{current_target_dir, init_module_imports, load_module, load_module_with_name},
+      libloading::library_filename,
+      relib_interface::StaleHandle,
+      std::fs,
+      test_shared::handles::gen_exports::ModuleExports,
+    };
+
+    pub fn main() {
+      let (module, _) = load_module::<ModuleExports, ()>(init_module_imports, true);
+      let exports = module.exports();
+
+      let (first, second) = unsafe {
+        let first = exports.create_widget(1).unwrap();
+        assert_eq!(first.module_id(), module.id());
+        assert_eq!(exports.widget_size(first), Some(Ok(1)));
+        assert_eq!(exports.resize_widget(first, 2), Some(Ok(())));
+        assert_eq!(exports.widget_size(first), Some(Ok(2)));
+        assert_eq!(exports.alive_widgets(), Some(1));
+
+        let stale = Err(StaleHandle { module: module.id() });
+        assert_eq!(exports.destroy_widget(first), Some(Ok(2)));
+        assert_eq!(exports.destroy_widget(first), Some(stale));
+        assert_eq!(exports.widget_size(first), Some(stale));
+        assert_eq!(exports.alive_widgets(), Some(0));
+
+        // slot of the removed widget is reused with the next generation
+        let second = exports.create_widget(3).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(exports.widget_size(second), Some(Ok(3)));
+        assert_eq!(exports.widget_size(first), Some(stale));
+
+        // table stays usable after panic in `with` callback
+        assert_eq!(exports.panic_in_widget(second), None);
+        assert_eq!(exports.widget_size(second), Some(Ok(3)));
+        assert_eq!(exports.resize_widget(second, 4), Some(Ok(())));
+        assert_eq!(exports.widget_size(second), Some(Ok(4)));
+
+        // handles are only resolved by the table which created them,
+        // even if another table has a value of the same type in the same slot
+        let other = exports.create_other_widget(5).unwrap();
+        assert_eq!(exports.other_widget_size(other), Some(Ok(5)));
+        assert_eq!(exports.widget_size(other), Some(stale));
+        assert_eq!(exports.other_widget_size(second), Some(stale));
+
+        (first, second)
+      };
+
+      module.unload().unwrap_or_else(|e| {
+        panic!("{e:#}");
+      });
+
+      // every load has its own module id, so handles of the previous one are stale
+
+      let (module, _) = load_module::<ModuleExports, ()>(init_module_imports, true);
+      let exports = module.exports();
+
+      unsafe {
+        let third = exports.create_widget(4).unwrap();
+        assert_ne!(third.module_id(), second.module_id());
+        assert_eq!(exports.widget_size(third), Some(Ok(4)));
+
+        for handle in [first, second] {
+          assert_eq!(
+            exports.widget_size(handle),
+            Some(Err(StaleHandle { module: handle.module_id() }))
+          );
+        }
+        assert_eq!(exports.resize_widget(second, 5), Some(Err(StaleHandle { module: second.module_id() })));
+        assert_eq!(exports.alive_widgets(), Some(1));
+      }
+
+      // handles of another module which is loaded at the same time are stale too
+
+      let target_dir = current_target_dir();
+      fs::copy(
+        target_dir.join(library_filename("test_module")),
+        target_dir.join(library_filename("test_module_handles")),
+      )
+      .unwrap();
+
+      let (another_module, _) =
+        load_module_with_name::<ModuleExports, ()>(init_module_imports, "test_module_handles", true);
+      let another_exports = another_module.exports();
+
+      unsafe {
+        let handle = exports.create_widget(6).unwrap();
+        let another_handle = another_exports.create_widget(7).unwrap();
+        assert_ne!(handle.module_id(), another_handle.module_id());
+        assert_eq!(another_handle.module_id(), another_module.id());
+
+        assert_eq!(exports.widget_size(handle), Some(Ok(6)));
+        assert_eq!(another_exports.widget_size(another_handle), Some(Ok(7)));
+        assert_eq!(
+          exports.widget_size(another_handle),
+          Some(Err(StaleHandle { module: another_module.id() }))
+        );
+        assert_eq!(
+          another_exports.widget_size(handle),
+          Some(Err(StaleHandle { module: module.id() }))
+        );
+        assert_eq!(exports.alive_widgets(), Some(2));
+        assert_eq!(another_exports.alive_widgets(), Some(1));
+      }
+
+      another_module.unload().unwrap_or_else(|e| {
+        panic!("{e:#}");
+      });
+
+      module.unload().unwrap_or_else(|e| {
+        panic!("{e:#}");
+      });
+    }
+  } else {
+    pub fn main() {
+      panic!("this branch must not be called");
+    }
+  }
+}
@@ -0,0 +1,122 @@
+use cfg_if::cfg_if;
+
+cfg_if! {
+  if #[cfg(feature = "host_alloc")] {
+    use {
+      crate::shared::load_module,
+      libloading::{Library, library_filename},
+      relib_host::{MemoryBudget, Module, SoakOptions, SoakResource},
+      test_host_shared::current_target_dir,
+      test_shared::host_alloc_imports::HostAllocImports,
+    };
+
+    relib_interface::include_exports!(gen_exports, "host_alloc");
+    relib_interface::include_imports!(gen_imports, "host_alloc");
+    use gen_exports::ModuleExports;
+    use gen_imports::ModuleImportsImpl;
+
+    impl HostAllocImports for ModuleImportsImpl {
+      fn host_string(len: usize) -> (usize, String) {
+        let string = "h".repeat(len);
+        (string.as_ptr() as usize, string)
+      }
+
+      fn host_result(ok: bool) -> Result<String, u8> {
+        if ok {
+          Ok("host".to_owned())
+        } else {
+          Err(u8::MAX)
+        }
+      }
+    }
+
+    fn init_imports(library: &Library) {
+      crate::shared::init_module_imports(library);
+      gen_imports::init_imports(library);
+    }
+
+    pub fn main() {
+      let (module, _) = load_module::<ModuleExports, ()>(init_imports, true);
+      let exports = module.exports();
+
+      // not supported by host allocator, host prints a warning
+      module.set_memory_budget(Some(MemoryBudget::new(1)));
+      assert_eq!(module.memory_stats().total_allocations, 0);
+
+      let (ptr, string) = unsafe { exports.string(1024) }.unwrap();
+      assert_eq!(string.as_ptr() as usize, ptr, "string must not be cloned");
+
+      let nested = unsafe { exports.nested() }.unwrap();
+      let boxed = unsafe { exports.boxed() }.unwrap();
+
+      unsafe { exports.leak_host_string(1024 * 1024) }.unwrap();
+
+      let round_trip = unsafe { exports.host_string_round_trip(1024) }.unwrap();
+      let result_ok = unsafe { exports.host_result_round_trip(true) }.unwrap();
+      let result_err = unsafe { exports.host_result_round_trip(false) }.unwrap();
+
+      module.unload().unwrap_or_else(|e| {
+        panic!("{e:#}");
+      });
+
+      // values moved to the host must survive unloading
+      // (allocate a lot to reuse the memory if it was deallocated)
+      let garbage: Vec<Vec<u8>> = (0..1000).map(|_| vec![0xff; 1024]).collect();
+      drop(garbage);
+
+      assert_eq!(string, "m".repeat(1024));
+      assert_eq!(nested.len(), 100);
+      assert_eq!(nested[0].as_deref(), Some("0"));
+      assert_eq!(nested[1], None);
+      assert_eq!(nested[98].as_deref(), Some("98"));
+      assert_eq!(*boxed, (u64::MAX, "boxed".to_owned()));
+      assert_eq!(round_trip, "h".repeat(1024) + "module");
+      assert_eq!(result_ok.as_deref(), Ok("hostmodule"));
+      assert_eq!(result_err, Err("error 255".to_owned()));
+
+      // and deallocated by the host allocator
+      drop((string, nested, boxed, round_trip, result_ok, result_err));
+
+      let path = current_target_dir().join(library_filename("test_module"));
+      let report = unsafe {
+        relib_host::soak(
+          path,
+          SoakOptions::default().cycles(6).warmup_cycles(2),
+          || init_imports,
+          |module: &Module<ModuleExports>| {
+            let ret = module.call_main::<()>();
+            assert!(ret.is_some(), "module main fn panicked");
+
+            let exports = module.exports();
+            let (_, string) = exports.string(100).unwrap();
+            let nested = exports.nested().unwrap();
+            exports.leak_host_string(100).unwrap();
+            drop((string, nested));
+          },
+        )
+      };
+      let report = report.unwrap_or_else(|e| {
+        panic!("soak failed: {e:#}");
+      });
+
+      // all leaked allocations must be deallocated by host on unload
+      assert_eq!(
+        report.samples.last().unwrap().tracked_allocations,
+        report.baseline.tracked_allocations
+      );
+
+      let growth: Vec<_> = report
+        .growth
+        .iter()
+        .filter(|growth| growth.resource != SoakResource::ResidentMemory)
+        .collect();
+      assert!(growth.is_empty(), "{report}");
+
+      println!("checked: {report}");
+    }
+  } else {
+    pub fn main() {
+      panic!("this branch must not be called");
+    }
+  }
+}
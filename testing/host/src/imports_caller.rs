@@ -0,0 +1,84 @@
+use cfg_if::cfg_if;
+
+cfg_if! {
+  if #[cfg(feature = "imports_caller")] {
+    use {
+      crate::shared::{ModuleExports, current_target_dir, init_module_imports},
+      libloading::library_filename,
+      relib_host::{LoadError, LoadOptions, Module},
+    };
+
+    const CONTEXT: u64 = 123;
+
+    pub fn main() {
+      assert!(relib_host::caller().is_none());
+
+      let module = load(LoadOptions::default().imports_context(CONTEXT));
+      unsafe {
+        let exports = module.exports();
+        assert_eq!(exports.caller_module_id(), Some(module.id()));
+        assert_eq!(exports.caller_context(), Some(CONTEXT));
+      }
+
+      // caller is only available while import is executed
+      assert!(relib_host::caller().is_none());
+
+      unload(module);
+
+      let module = load(LoadOptions::default().grant_imports(["Imports::caller_module_id"]));
+      unsafe {
+        let exports = module.exports();
+        assert_eq!(exports.caller_module_id(), Some(module.id()));
+
+        eprintln!("panic is expected:");
+        // import is not granted so module panics when it calls it
+        assert_eq!(exports.caller_context(), None);
+      }
+      unload(module);
+
+      let module = load(LoadOptions::default().grant_imports(["Imports"]));
+      unsafe {
+        assert_eq!(module.exports().caller_context(), Some(0));
+      }
+      unload(module);
+
+      let path = current_target_dir().join(library_filename("test_module"));
+      let options = LoadOptions::default().grant_imports(["Imports::caller_context", "Imports::typo", "Typo"]);
+      let result = unsafe {
+        relib_host::load_module_with::<ModuleExports>(path, init_module_imports, options)
+      };
+      let Err(LoadError::UnknownGrantedImports(unknown)) = result else {
+        panic!("expected UnknownGrantedImports");
+      };
+      assert_eq!(unknown, ["Imports::typo", "Typo"]);
+
+      // module wasn't loaded so it can be loaded again
+      unload(load(LoadOptions::default()));
+    }
+
+    fn load(options: LoadOptions) -> Module<ModuleExports> {
+      let path = current_target_dir().join(library_filename("test_module"));
+      let module = unsafe {
+        relib_host::load_module_with::<ModuleExports>(path, init_module_imports, options)
+      };
+      let module = module.unwrap_or_else(|e| {
+        panic!("{e:#}");
+      });
+
+      unsafe {
+        module.call_main::<()>().unwrap();
+      }
+      module
+    }
+
+    fn unload(module: Module<ModuleExports>) {
+      module.unload().unwrap_or_else(|e| {
+        panic!("{e:#}");
+      });
+    }
+  } else {
+    pub fn main() {
+      panic!("this branch must not be called");
+    }
+  }
+}
@@ -0,0 +1,121 @@
+use {
+  crate::shared::{ModuleExports, init_module_imports, load_module},
+  libloading::library_filename,
+  relib_host::{InspectError, ModuleInspection, inspect},
+  std::{fs, path::Path},
+  test_host_shared::current_target_dir,
+};
+
+pub fn main() {
+  let path = current_target_dir().join(library_filename("test_module"));
+
+  let inspection = inspect(&path).unwrap_or_else(|e| {
+    panic!("failed to inspect module: {e:#}");
+  });
+
+  assert!(inspection.has_compilation_info_symbol);
+  assert!(inspection.is_compatible_with_host(), "{inspection:#?}");
+  assert!(!inspection.unloading_will_fail(), "{inspection:#?}");
+
+  for export in ["__relib__main", "__relib__Exports_empty"] {
+    assert!(
+      inspection.exports.iter().any(|e| e == export),
+      "{export} not found in {:?}",
+      inspection.exports
+    );
+  }
+  assert!(
+    inspection
+      .post_exports
+      .iter()
+      .any(|e| e == "__post__relib__Exports_ref_owned_ret"),
+    "{:?}",
+    inspection.post_exports
+  );
+  assert!(
+    inspection
+      .imports
+      .iter()
+      .any(|e| e == "__relib__Imports_empty"),
+    "{:?}",
+    inspection.imports
+  );
+
+  // build script and proc macro produce the same representation of signatures
+  for (trait_name, function) in [
+    ("Exports", "fn ref_ret<'a>(r: RStr<'a>) -> RStr<'a>"),
+    ("MacroExports", "fn ref_ret<'a>(r: RStr<'a>) -> RStr<'a>"),
+    ("Imports", "fn slice_mut(slice: &mut [u8])"),
+  ] {
+    let interface = inspection
+      .interfaces
+      .iter()
+      .find(|interface| interface.trait_name == trait_name)
+      .unwrap_or_else(|| panic!("{trait_name} not found in {:#?}", inspection.interfaces));
+    assert!(
+      interface.functions.iter().any(|f| f == function),
+      "{function} not found in {:#?}",
+      interface.functions
+    );
+  }
+
+  let err = inspect("Cargo.toml").unwrap_err();
+  assert!(matches!(err, InspectError::UnsupportedFormat), "{err}");
+
+  check_corrupted_files(&path);
+
+  // it must be actually loadable
+  let _ = load_module::<ModuleExports, ()>(init_module_imports, true);
+
+  println!("checked");
+}
+
+// malformed files must be rejected (or inspected partially) without panicking
+fn check_corrupted_files(path: &Path) {
+  let data = fs::read(path).unwrap();
+
+  let err = inspect_bytes("truncated", &data[..64]).unwrap_err();
+  assert!(matches!(err, InspectError::UnsupportedFormat), "{err}");
+
+  if cfg!(target_os = "linux") {
+    // section header table offset (e_shoff) pointing at the end of address space
+    let mut corrupted = data.clone();
+    corrupted[0x28..0x30].copy_from_slice(&u64::MAX.to_le_bytes());
+    let err = inspect_bytes("huge_shoff", &corrupted).unwrap_err();
+    assert!(matches!(err, InspectError::UnsupportedFormat), "{err}");
+
+    // every section except symbol and string tables is (virtually) as large as address space
+    const SHT_STRTAB: u32 = 3;
+    const SHT_DYNSYM: u32 = 11;
+    let mut corrupted = data.clone();
+    let shoff = u64::from_le_bytes(data[0x28..0x30].try_into().unwrap()) as usize;
+    let shentsize = u16::from_le_bytes(data[0x3a..0x3c].try_into().unwrap()) as usize;
+    let shnum = u16::from_le_bytes(data[0x3c..0x3e].try_into().unwrap()) as usize;
+    for idx in 0..shnum {
+      let base = shoff + idx * shentsize;
+      let type_ = u32::from_le_bytes(data[base + 0x4..base + 0x8].try_into().unwrap());
+      if matches!(type_, SHT_STRTAB | SHT_DYNSYM) {
+        continue;
+      }
+      corrupted[base + 0x10..base + 0x18].copy_from_slice(&(u64::MAX - 4).to_le_bytes());
+      corrupted[base + 0x20..base + 0x28].copy_from_slice(&u64::MAX.to_le_bytes());
+    }
+    let inspection = inspect_bytes("huge_sections", &corrupted).unwrap();
+    assert!(inspection.has_compilation_info_symbol, "{inspection:#?}");
+    assert_eq!(inspection.compilation_info, None);
+  } else {
+    // PE header offset (e_lfanew) pointing at the end of the file
+    let mut corrupted = data.clone();
+    corrupted[0x3c..0x40].copy_from_slice(&u32::MAX.to_le_bytes());
+    let err = inspect_bytes("huge_lfanew", &corrupted).unwrap_err();
+    assert!(matches!(err, InspectError::UnsupportedFormat), "{err}");
+  }
+}
+
+fn inspect_bytes(name: &str, data: &[u8]) -> Result<ModuleInspection, InspectError> {
+  let path = current_target_dir().join(format!("corrupted_module_{name}"));
+  fs::write(&path, data).unwrap();
+  let result = inspect(&path);
+  fs::remove_file(&path).unwrap();
+  result
+}
@@ -0,0 +1,103 @@
+use cfg_if::cfg_if;
+
+cfg_if! {
+  if #[cfg(feature = "interface_macro")] {
+    use {
+      crate::shared::{init_module_imports, load_module},
+      abi_stable::std_types::{RStr, RVec},
+      libloading::Library,
+      test_shared::interface_macro::{
+        DEFAULT_RETURN_VALUE, MacroImports, gen_exports::ModuleExports, gen_imports,
+      },
+    };
+
+    struct Imports;
+
+    impl MacroImports for Imports {
+      fn primitive(p: i32) -> i32 {
+        assert!(relib_host::caller().is_some());
+        p * 3
+      }
+
+      fn ref_ret<'a>(r: RStr<'a>) -> RStr<'a> {
+        r.slice(1..)
+      }
+
+      fn str_ret(s: &str) -> &str {
+        &s[1..]
+      }
+
+      fn alloc_vec(len: usize) -> RVec<u8> {
+        vec![0; len].into()
+      }
+
+      fn callback_panic(callback: &dyn Fn()) {
+        callback();
+        unreachable!();
+      }
+    }
+
+    fn init_imports(library: &Library) {
+      init_module_imports(library);
+      gen_imports::init_imports::<Imports>(library);
+    }
+
+    pub fn main() {
+      let (module, _) = load_module::<ModuleExports, ()>(init_imports, true);
+      let exports = module.exports();
+
+      // closure of the module panics in the import, so host panics in it too
+      // and module raises the panic again (see testing/runner/src/callback_panic_in_import.rs)
+      if cfg!(feature = "callback_panic_in_import") {
+        assert_eq!(unsafe { exports.import_callback_panic() }, None);
+        return;
+      }
+
+      unsafe {
+        assert_eq!(exports.primitive(2), Some(4));
+
+        let string = "hello";
+        assert_eq!(exports.ref_ret(string.into()), Some("ello".into()));
+        assert_eq!(exports.owned_ret(string.into()).unwrap(), "hello!");
+        assert_eq!(exports.str_ret(string), Some("ello"));
+
+        let mut bytes = [0; 3];
+        exports.slice_mut(&mut bytes).unwrap();
+        assert_eq!(bytes, [u8::MAX; 3]);
+
+        assert_eq!(exports.result(true), Some(Ok(u8::MAX)));
+        assert_eq!(exports.result(false), Some(Err("error".into())));
+
+        assert_eq!(exports.default_impl(), Some(DEFAULT_RETURN_VALUE));
+
+        #[cfg(target_os = "linux")]
+        assert_eq!(exports.cfg_target_os(), Some(u8::MAX));
+        #[cfg(not(target_os = "linux"))]
+        assert_eq!(exports.cfg_target_os(), Some(u16::MAX));
+
+        exports.call_imports().unwrap();
+
+        assert_eq!(exports.panic(), None);
+
+        // panic of the closure is propagated through the module back to the host,
+        // resume_unwind doesn't call panic hook, which would capture a backtrace with frames of the module
+        // if it's enabled, and std keeps the module file mapped after resolving them
+        assert_eq!(
+          exports.callback_panic(&|| std::panic::resume_unwind(Box::new("expected closure panic"))),
+          None
+        );
+
+        assert_eq!(exports.callback_ret_ref(&|s| &s[1..], &|slice| &slice[1..]), Some(true));
+
+      }
+
+      module.unload().unwrap_or_else(|e| {
+        panic!("{e:#}");
+      });
+    }
+  } else {
+    pub fn main() {
+      panic!("this branch must not be called");
+    }
+  }
+}
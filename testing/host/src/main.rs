@@ -5,6 +5,24 @@ mod no_unloading;
 mod exportify;
 mod threads_check;
 mod before_unload_panic;
+mod before_unload_timeout;
+mod shutdown_token;
+mod untracked_threads;
+mod unloading_fail_diagnosis;
+mod inspect;
+mod soak;
+mod memory_stats;
+mod memory_budget;
+mod alloc_bench;
+mod arena_alloc;
+mod host_alloc;
+mod guard_pages;
+mod interface_macro;
+mod imports_caller;
+mod module_box;
+mod handles;
+mod call_timeout;
+mod const_mismatch;
 mod code_change;
 mod multiple_modules;
 mod panic_in_interface_module;
@@ -30,6 +48,42 @@ fn main() {
     threads_check::main();
   } else if cfg!(feature = "before_unload_panic") {
     before_unload_panic::main();
+  } else if cfg!(feature = "before_unload_timeout") {
+    before_unload_timeout::main();
+  } else if cfg!(feature = "shutdown_token") {
+    shutdown_token::main();
+  } else if cfg!(feature = "untracked_threads") {
+    untracked_threads::main();
+  } else if cfg!(feature = "unloading_fail_diagnosis") {
+    unloading_fail_diagnosis::main();
+  } else if cfg!(feature = "inspect") {
+    inspect::main();
+  } else if cfg!(feature = "soak") {
+    soak::main();
+  } else if cfg!(feature = "memory_stats") {
+    memory_stats::main();
+  } else if cfg!(feature = "memory_budget") {
+    memory_budget::main();
+  } else if cfg!(feature = "alloc_bench") {
+    alloc_bench::main();
+  } else if cfg!(feature = "arena_alloc") {
+    arena_alloc::main();
+  } else if cfg!(feature = "host_alloc") {
+    host_alloc::main();
+  } else if cfg!(feature = "guard_pages") {
+    guard_pages::main();
+  } else if cfg!(feature = "interface_macro") {
+    interface_macro::main();
+  } else if cfg!(feature = "imports_caller") {
+    imports_caller::main();
+  } else if cfg!(feature = "module_box") {
+    module_box::main();
+  } else if cfg!(feature = "handles") {
+    handles::main();
+  } else if cfg!(feature = "call_timeout") {
+    call_timeout::main();
+  } else if cfg!(feature = "const_mismatch") {
+    const_mismatch::main();
   } else if cfg!(feature = "code_change") {
     code_change::main();
   } else if cfg!(feature = "multiple_modules") {
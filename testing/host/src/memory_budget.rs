@@ -0,0 +1,63 @@
+use cfg_if::cfg_if;
+
+cfg_if! {
+  if #[cfg(feature = "memory_budget")] {
+    use {
+      crate::shared::init_module_imports,
+      libloading::library_filename,
+      relib_host::{LoadOptions, MemoryBudget, MemoryBudgetPolicy},
+      std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+      },
+      test_host_shared::current_target_dir,
+    };
+
+    const LIMIT: u64 = 1024 * 1024;
+
+    pub fn main() {
+      let path = current_target_dir().join(library_filename("test_module"));
+      let options = LoadOptions::default().memory_budget(MemoryBudget::new(LIMIT));
+      let module = unsafe { relib_host::load_module_with::<()>(path, init_module_imports, options) };
+      let module = module.unwrap();
+
+      let allocated = || unsafe { module.call_main::<bool>() }.unwrap();
+
+      assert!(!module.is_over_budget());
+      assert!(!allocated());
+      assert!(module.is_over_budget());
+
+      module.set_memory_budget(Some(
+        MemoryBudget::new(LIMIT).policy(MemoryBudgetPolicy::Allow),
+      ));
+      assert!(!module.is_over_budget());
+      assert!(allocated());
+      assert!(module.is_over_budget());
+
+      let requested = Arc::new(AtomicUsize::new(0));
+      let callback = {
+        let requested = requested.clone();
+        Arc::new(move |exceeded: &relib_host::BudgetExceeded| {
+          assert_eq!(exceeded.limit_bytes, LIMIT);
+          requested.store(exceeded.requested_bytes, Ordering::SeqCst);
+          true
+        })
+      };
+      module.set_memory_budget(Some(
+        MemoryBudget::new(LIMIT).policy(MemoryBudgetPolicy::Callback(callback)),
+      ));
+      assert!(allocated());
+      assert_eq!(requested.load(Ordering::SeqCst), 2 * 1024 * 1024);
+
+      module.set_memory_budget(None);
+      assert!(allocated());
+      assert!(!module.is_over_budget());
+
+      module.unload().unwrap();
+    }
+  } else {
+    pub fn main() {
+      panic!("this branch must not be called");
+    }
+  }
+}
@@ -0,0 +1,48 @@
+use cfg_if::cfg_if;
+
+cfg_if! {
+  if #[cfg(feature = "memory_stats")] {
+    use {
+      crate::shared::{init_module_imports, load_module},
+      relib_host::SizeClass,
+    };
+
+    pub fn main() {
+      let (module, _) = load_module::<(), ()>(init_module_imports, true);
+
+      let stats = module.memory_stats();
+
+      assert!(stats.live_bytes >= 1024 * 1024);
+      assert!(stats.peak_bytes >= stats.live_bytes);
+      assert!(stats.total_allocations >= 101);
+      assert!(stats.total_deallocations >= 100);
+      assert_eq!(
+        stats.total_allocations - stats.total_deallocations,
+        stats.live_allocations
+      );
+      assert_eq!(
+        stats
+          .size_classes
+          .iter()
+          .map(|class| class.live_allocations)
+          .sum::<u64>(),
+        stats.live_allocations
+      );
+
+      let megabyte_class = stats
+        .size_classes
+        .iter()
+        .find(|class| class.max_size == Some(1024 * 1024));
+      assert!(
+        matches!(megabyte_class, Some(SizeClass { live_allocations: 1.., .. })),
+        "{megabyte_class:?}"
+      );
+
+      module.unload().unwrap();
+    }
+  } else {
+    pub fn main() {
+      panic!("this branch must not be called");
+    }
+  }
+}
@@ -0,0 +1,103 @@
+use cfg_if::cfg_if;
+
+cfg_if! {
+  if #[cfg(feature = "module_box")] {
+    use {
+      crate::shared::{init_module_imports, load_module},
+      relib_host::UnloadError,
+      std::panic::{AssertUnwindSafe, catch_unwind},
+      test_shared::module_box::{Counter, gen_exports::ModuleExports},
+    };
+
+    pub fn main() {
+      let (module, _) = load_module::<ModuleExports, ()>(init_module_imports, true);
+      let exports = module.exports();
+
+      unsafe {
+        let mut counter = exports.counter("first").unwrap();
+        assert_eq!(counter.increment(2), 2);
+        assert_eq!(counter.increment(1), 3);
+        assert_eq!(counter.name(), "first");
+        assert_eq!(counter.label(), "first: 3");
+        assert_eq!(counter.checked(true), Ok(3));
+        assert_eq!(counter.checked(false), Err("error".into()));
+        assert_eq!(counter.sized_only(), u8::MAX);
+
+        let mut values = Vec::new();
+        counter.for_each(&mut |value| values.push(value));
+        assert_eq!(values, [0, 1, 2]);
+
+        // panic of the module is propagated to the host
+        eprintln!("panics are expected:");
+        let result = catch_unwind(AssertUnwindSafe(|| counter.panic()));
+        assert!(result.is_err());
+
+        // ownership is moved back to the module
+        assert_eq!(exports.take_counter(counter), Some(4));
+        assert_eq!(exports.dropped_counters(), Some(1));
+
+        let counter = exports.counter_result(true).unwrap().unwrap();
+        assert_eq!(counter.name(), "ok");
+        drop(counter);
+        assert_eq!(exports.dropped_counters(), Some(2));
+
+        assert!(matches!(exports.counter_result(false), Some(Err(_))));
+
+        // panic in drop of the value is propagated to the host...
+        let counter = exports.counter("panic in drop").unwrap();
+        let result = catch_unwind(AssertUnwindSafe(|| drop(counter)));
+        assert!(result.is_err());
+
+        // ...but not while host is already panicking, otherwise process would be aborted
+        let counter = exports.counter("panic in drop").unwrap();
+        let result = catch_unwind(AssertUnwindSafe(|| {
+          let _counter = counter;
+          panic!("expected panic");
+        }));
+        assert!(result.is_err());
+        assert_eq!(exports.dropped_counters(), Some(4));
+      }
+
+      module.unload().unwrap_or_else(|e| {
+        panic!("{e:#}");
+      });
+
+      // module can't be unloaded while its boxes are alive
+
+      let (module, _) = load_module::<ModuleExports, ()>(init_module_imports, true);
+      let counter = unsafe { module.exports().counter("alive") }.unwrap();
+      assert_eq!(module.module_boxes_count(), 1);
+
+      drop(counter);
+      assert_eq!(module.module_boxes_count(), 0);
+      module.unload().unwrap_or_else(|e| {
+        panic!("{e:#}");
+      });
+
+      // box kept by the module is dropped in before_unload
+
+      let (module, _) = load_module::<ModuleExports, ()>(init_module_imports, true);
+      unsafe { module.exports().keep_counter() }.unwrap();
+      assert_eq!(module.module_boxes_count(), 1);
+      module.unload().unwrap_or_else(|e| {
+        panic!("{e:#}");
+      });
+
+      let (module, _) = load_module::<ModuleExports, ()>(init_module_imports, true);
+      let counter = unsafe { module.exports().counter("alive") }.unwrap();
+
+      let Err(UnloadError::ModuleBoxesAlive(_, count)) = module.unload() else {
+        panic!("unloading must fail");
+      };
+      assert_eq!(count, 1);
+
+      // module is leaked so the box is still valid
+      assert_eq!(counter.name(), "alive");
+      drop(counter);
+    }
+  } else {
+    pub fn main() {
+      panic!("this branch must not be called");
+    }
+  }
+}
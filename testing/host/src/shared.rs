@@ -1,7 +1,11 @@
 use {
   abi_stable::std_types::{RStr, RString, RVec},
   std::cell::Cell,
-  test_shared::{SIZE_200_MB, imports::Imports},
+  test_shared::{
+    SIZE_200_MB,
+    imports::{IMPORTS_NUMBER, IMPORTS_STR, Imports},
+    interface_types::Number,
+  },
 };
 pub use test_host_shared::*;
 
@@ -58,6 +62,75 @@ impl Imports for ModuleImportsImpl {
     p
   }
 
+  fn result(ok: bool) -> Result<RString, RString> {
+    if ok {
+      Ok("ok".into())
+    } else {
+      Err("err".into())
+    }
+  }
+
+  fn result_primitive(ok: bool) -> Result<u8, u16> {
+    if ok { Ok(u8::MAX) } else { Err(u16::MAX) }
+  }
+
+  fn str_ret(s: &str) -> &str {
+    &s[1..]
+  }
+
+  fn str_result(s: &str) -> Result<&str, RString> {
+    if s.is_empty() {
+      Err("empty".into())
+    } else {
+      Ok(s)
+    }
+  }
+
+  fn slice_sum(slice: &[u32]) -> u32 {
+    slice.iter().sum()
+  }
+
+  fn slice_mut(slice: &mut [u8]) {
+    slice.iter_mut().for_each(|value| *value += 1);
+  }
+
+  fn option_ref(value: Option<&u64>) -> Option<&u64> {
+    value
+  }
+
+  fn callback(value: u32, callback: &dyn Fn(u32) -> u32) -> u32 {
+    callback(value) + 1
+  }
+
+  fn callback_mut(count: u32, callback: &mut dyn FnMut(&str)) {
+    for idx in 0..count {
+      callback(&idx.to_string());
+    }
+  }
+
+  #[cfg(target_os = "linux")]
+  fn cfg_target_os() -> u8 {
+    u8::MAX
+  }
+
+  #[cfg(not(target_os = "linux"))]
+  fn cfg_target_os() -> u16 {
+    u16::MAX
+  }
+
+  fn trait_file_items(number: Number) -> RString {
+    format!("{IMPORTS_STR} {}", number + IMPORTS_NUMBER).into()
+  }
+
+  fn caller_module_id() -> u64 {
+    relib_host::caller().unwrap().module_id()
+  }
+
+  fn caller_context() -> u64 {
+    let caller = relib_host::caller().unwrap();
+    caller.context::<u64>().copied().unwrap_or_default()
+  }
+
   fn alloc_mem() -> RVec<u8> {
     alloc_some_bytes().into()
   }
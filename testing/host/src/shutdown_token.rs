@@ -0,0 +1,27 @@
+use {
+  crate::shared::{init_module_imports, load_module},
+  cfg_if::cfg_if,
+  relib_host::{Module, ModuleExportsForHost},
+};
+
+pub fn main() {
+  let (module, _) = load_module::<(), ()>(init_module_imports, true);
+  unload_module(module);
+}
+
+fn unload_module<E: ModuleExportsForHost>(module: Module<E>) {
+  cfg_if! {
+    if #[cfg(feature = "shutdown_token")] {
+      // module doesn't export before_unload, its threads must finish by themselves
+      // (host waits for them by default)
+      module.unload().unwrap_or_else(|e| {
+        panic!("{e:#}");
+      });
+
+      println!("checked");
+    } else {
+      drop(module);
+      panic!("this branch must not be called");
+    }
+  }
+}
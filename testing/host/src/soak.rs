@@ -0,0 +1,93 @@
+use cfg_if::cfg_if;
+
+cfg_if! {
+  if #[cfg(feature = "soak")] {
+    use {
+      crate::shared::{ModuleExports, init_module_imports},
+      libloading::library_filename,
+      relib_host::{Module, SoakOptions, SoakResource},
+      test_host_shared::current_target_dir,
+    };
+
+    pub fn main() {
+      let path = current_target_dir().join(library_filename("test_module"));
+
+      let report = unsafe {
+        relib_host::soak(
+          path,
+          SoakOptions::default().cycles(8).warmup_cycles(2),
+          || init_module_imports,
+          |module: &Module<ModuleExports>| {
+            let ret = module.call_main::<()>();
+            assert!(ret.is_some(), "module main fn panicked");
+          },
+        )
+      };
+      let report = report.unwrap_or_else(|e| {
+        panic!("soak failed: {e:#}");
+      });
+
+      assert_eq!(report.samples.len(), 8);
+      assert_eq!(
+        report.samples.last().unwrap().tracked_allocations,
+        report.baseline.tracked_allocations
+      );
+
+      // resident memory depends too much on allocator of the host to assert it
+      let growth: Vec<_> = report
+        .growth
+        .iter()
+        .filter(|growth| growth.resource != SoakResource::ResidentMemory)
+        .collect();
+      assert!(growth.is_empty(), "{report}");
+
+      println!("checked: {report}");
+
+      check_leaking_module();
+    }
+
+    /// Module which leaks file descriptor on every cycle (it's not closed by relib on unload)
+    fn check_leaking_module() {
+      if !cfg!(target_os = "linux") {
+        // file descriptors are only measured on Linux
+        return;
+      }
+
+      let path = current_target_dir().join(library_filename("test_module"));
+
+      let report = unsafe {
+        relib_host::soak(
+          path,
+          SoakOptions::default().cycles(6).warmup_cycles(1),
+          || init_module_imports,
+          |module: &Module<ModuleExports>| {
+            let ret = module.exports().leak_file();
+            assert!(ret.is_some(), "leak_file panicked");
+          },
+        )
+      };
+      let report = report.unwrap_or_else(|e| {
+        panic!("soak failed: {e:#}");
+      });
+
+      assert!(!report.is_leak_free(), "{report}");
+      let growth = report
+        .growth
+        .iter()
+        .find(|growth| growth.resource == SoakResource::FileDescriptors)
+        .unwrap_or_else(|| panic!("file descriptors growth is not reported: {report}"));
+      assert_eq!(growth.per_cycle, 1, "{report}");
+      assert_eq!(growth.last - growth.first, 4, "{report}");
+      assert!(
+        report.to_string().contains("file descriptors grew from"),
+        "{report}"
+      );
+
+      println!("checked leaking module: {report}");
+    }
+  } else {
+    pub fn main() {
+      panic!("this branch must not be called");
+    }
+  }
+}
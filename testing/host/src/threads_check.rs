@@ -12,10 +12,24 @@ pub fn main() {
 fn unload_module<E: ModuleExportsForHost>(module: Module<E>) {
   cfg_if! {
     if #[cfg(feature = "threads_check")] {
-      use relib_host::UnloadError;
+      use {
+        std::time::Duration,
+        relib_host::{UnloadError, UnloadOptions},
+      };
 
-      let err = module.unload().unwrap_err();
-      assert!(matches!(err, UnloadError::ThreadsStillRunning(..)));
+      let threads = module.threads();
+      assert!(
+        threads.iter().any(|thread| thread.name.as_deref() == Some("still running (long name)")),
+        "{threads:?}"
+      );
+
+      let options = UnloadOptions::default().wait_for_threads(Duration::from_millis(500));
+      let err = module.unload_with(options).unwrap_err();
+      let UnloadError::ThreadsStillRunning(_, threads) = err else {
+        panic!("unexpected error: {err}");
+      };
+      assert_eq!(threads.len(), 1, "{threads:?}");
+      assert_eq!(threads[0].name.as_deref(), Some("still running (long name)"));
 
       println!("checked");
     } else {
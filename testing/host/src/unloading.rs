@@ -82,6 +82,37 @@ unsafe fn test_exports(exports: &ModuleExports) -> Option<()> {
     let ret = exports.primitive_ret(i32::MIN)?;
     assert_eq!(ret, i32::MIN);
 
+    assert_eq!(exports.result(true)?, Ok("ok".into()));
+    assert_eq!(exports.result(false)?, Err("err".into()));
+    assert_eq!(exports.result_primitive(true)?, Ok(u8::MAX));
+    assert_eq!(exports.result_primitive(false)?, Err(u16::MAX));
+
+    assert_eq!(exports.str_ret(&string)?, &string[1..]);
+    assert_eq!(exports.str_result(&string)?, Ok(string.as_str()));
+    assert_eq!(exports.str_result("")?, Err("empty".into()));
+    assert_eq!(exports.slice_sum(&[1, 2, 3])?, 6);
+    let mut bytes = [0, 1, 2];
+    exports.slice_mut(&mut bytes)?;
+    assert_eq!(bytes, [1, 2, 3]);
+    assert_eq!(exports.option_ref(Some(&u64::MAX))?, Some(&u64::MAX));
+    assert_eq!(exports.option_ref(None)?, None);
+
+    assert_eq!(exports.callback(1, &|value| value * 2)?, 3);
+    let mut strings = Vec::new();
+    exports.callback_mut(3, &mut |s| strings.push(s.to_owned()))?;
+    assert_eq!(strings, ["0", "1", "2"]);
+
+    #[cfg(target_os = "linux")]
+    assert_eq!(exports.cfg_target_os()?, u8::MAX);
+    #[cfg(not(target_os = "linux"))]
+    assert_eq!(exports.cfg_target_os()?, u16::MAX);
+
+    assert_eq!(exports.trait_file_items(1)?, "exports 43");
+    // module ids start from 1
+    assert_ne!(exports.caller_module_id()?, 0);
+    // module is loaded without imports context
+    assert_eq!(exports.caller_context()?, 0);
+
     THREAD_LOCAL_DROP_CALL_STATE.set(DropCallState::NotCalled);
     exports.thread_locals()?;
 
@@ -106,6 +137,8 @@ unsafe fn test_exports(exports: &ModuleExports) -> Option<()> {
     let _suppress_unused_warn = || {
       #[expect(unreachable_code, clippy::diverging_sub_expression)]
       exports._params_lt_and_output_without(unreachable!(), unreachable!())?;
+      // used by soak scenario (it's leaking file descriptor)
+      exports.leak_file()?;
 
       Some(())
     };
@@ -0,0 +1,46 @@
+use {
+  crate::shared::{init_module_imports, load_module},
+  cfg_if::cfg_if,
+  relib_host::{Module, ModuleExportsForHost},
+};
+
+pub fn main() {
+  let (module, _) = load_module::<(), ()>(init_module_imports, true);
+  unload_module(module);
+}
+
+fn unload_module<E: ModuleExportsForHost>(module: Module<E>) {
+  cfg_if! {
+    if #[cfg(feature = "unloading_fail_diagnosis")] {
+      use {
+        libloading::{Library, library_filename},
+        relib_host::{UnloadBlocker, UnloadError},
+        test_host_shared::current_target_dir,
+      };
+
+      // holding another reference to the module so dynamic linker won't unload it
+      let path = current_target_dir().join(library_filename("test_module"));
+      let another_reference = unsafe { Library::new(path) }.unwrap();
+
+      let err = module.unload().unwrap_err();
+      let UnloadError::UnloadingFail(_, diagnosis) = err else {
+        panic!("unexpected error: {err}");
+      };
+      assert!(
+        diagnosis
+          .findings
+          .iter()
+          .any(|finding| matches!(finding, UnloadBlocker::StillReferenced)),
+        "{diagnosis}"
+      );
+
+      // module memory is already freed, it must never be used again
+      std::mem::forget(another_reference);
+
+      println!("checked");
+    } else {
+      drop(module);
+      panic!("this branch must not be called");
+    }
+  }
+}
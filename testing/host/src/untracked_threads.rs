@@ -0,0 +1,42 @@
+use {
+  crate::shared::{init_module_imports, load_module},
+  cfg_if::cfg_if,
+  relib_host::{Module, ModuleExportsForHost},
+};
+
+pub fn main() {
+  let (module, _) = load_module::<(), ()>(init_module_imports, true);
+  unload_module(module);
+}
+
+fn unload_module<E: ModuleExportsForHost>(module: Module<E>) {
+  cfg_if! {
+    if #[cfg(feature = "untracked_threads")] {
+      use relib_host::{UnloadError, UnloadOptions, UntrackedThreadsPolicy};
+
+      assert!(module.threads().is_empty());
+
+      // thread is sleeping in libc so it's only found by its stack (low confidence evidence),
+      // with default policy it's reported as a warning (see testing/runner/src/untracked_threads_warn.rs)
+      if cfg!(feature = "untracked_threads_warn") {
+        module.unload().unwrap_or_else(|e| {
+          panic!("{e:#}");
+        });
+        println!("checked");
+        return;
+      }
+
+      let options = UnloadOptions::default().untracked_threads(UntrackedThreadsPolicy::Refuse);
+      let err = module.unload_with(options).unwrap_err();
+      let UnloadError::UntrackedThreadsRunning(_, threads) = err else {
+        panic!("unexpected error: {err}");
+      };
+      assert_eq!(threads.len(), 1, "{threads:?}");
+
+      println!("checked");
+    } else {
+      drop(module);
+      panic!("this branch must not be called");
+    }
+  }
+}
@@ -22,7 +22,7 @@ fn unload_module<E: ModuleExportsForHost>(module: Module<E>) {
     if #[cfg(feature = "windows_background_threads_fail")] {
       use relib_host::UnloadError;
 
-      let Err(UnloadError::ThreadsStillRunning(_)) = dbg!(module.unload()) else {
+      let Err(UnloadError::ThreadsStillRunning(..)) = dbg!(module.unload()) else {
         unreachable!();
       };
     } else {
@@ -0,0 +1,77 @@
+use std::{
+  alloc::{GlobalAlloc, Layout, System},
+  hint::black_box,
+  thread,
+  time::{Duration, Instant},
+};
+
+const ITERATIONS: usize = 20_000;
+const BATCH: usize = 32;
+
+/// Compares global allocator of the module (AllocTracker) with `System` allocator,
+/// numbers of the previous tracker (single mutex-protected cache) are recorded in docs.md ("Module alloc tracker")
+#[relib_module::export]
+pub fn main() {
+  println!("threads | System (ns/op) | AllocTracker (ns/op) | overhead");
+  for threads in [1, 4] {
+    let system = bench(
+      threads,
+      |layout| unsafe { System.alloc(layout) },
+      |ptr, layout| unsafe { System.dealloc(ptr, layout) },
+    );
+    let tracker = bench(
+      threads,
+      |layout| unsafe { std::alloc::alloc(layout) },
+      |ptr, layout| unsafe { std::alloc::dealloc(ptr, layout) },
+    );
+
+    let system = per_op(system);
+    let tracker = per_op(tracker);
+    println!(
+      "{threads:>7} | {system:>14.1} | {tracker:>20.1} | {:.1}x",
+      tracker / system
+    );
+  }
+}
+
+/// Every thread allocates batch of small allocations and deallocates them
+fn bench(
+  threads: usize,
+  alloc: impl Fn(Layout) -> *mut u8 + Sync,
+  dealloc: impl Fn(*mut u8, Layout) + Sync,
+) -> Duration {
+  let start = Instant::now();
+
+  thread::scope(|scope| {
+    let threads: Vec<_> = (0..threads)
+      .map(|_| {
+        scope.spawn(|| {
+          let mut ptrs = [std::ptr::null_mut(); BATCH];
+          for iteration in 0..ITERATIONS {
+            let layout = Layout::from_size_align(16 + iteration % 256, 8).unwrap();
+            for ptr in &mut ptrs {
+              *ptr = black_box(alloc(layout));
+              assert!(!ptr.is_null());
+            }
+            for ptr in ptrs {
+              dealloc(ptr, layout);
+            }
+          }
+        })
+      })
+      .collect();
+
+    // scope only waits for closures to return, not for threads to exit,
+    // joining them so module has no running threads when benchmark is finished
+    for thread in threads {
+      thread.join().unwrap();
+    }
+  });
+
+  start.elapsed()
+}
+
+/// Wall time of alloc + dealloc pair (every thread does the same number of them in parallel)
+fn per_op(elapsed: Duration) -> f64 {
+  elapsed.as_nanos() as f64 / (ITERATIONS * BATCH) as f64
+}
@@ -0,0 +1,74 @@
+use std::{collections::HashMap, hint::black_box, sync::Mutex, thread};
+
+#[relib_module::export]
+pub fn main() {
+  check_alloc_before_init();
+
+  // freed by relib (unmapped with the whole arena)
+  let leaked: Vec<Box<[u8]>> = (0..200_000)
+    .map(|idx| vec![idx as u8; 1 + idx % 100].into_boxed_slice())
+    .collect();
+  for (idx, bytes) in leaked.iter().enumerate() {
+    assert!(bytes.iter().all(|byte| *byte == idx as u8));
+  }
+  black_box(Box::leak(Box::new(leaked)));
+
+  // bigger than any size class, it has its own region
+  let big = vec![7_u8; 1024 * 1024];
+  assert!(big.iter().all(|byte| *byte == 7));
+  drop(big);
+
+  // over-aligned allocations
+  let aligned = Box::new(Aligned([1; 64]));
+  assert_eq!(&raw const *aligned as usize % 8192, 0);
+  assert_eq!(aligned.0[63], 1);
+
+  // reallocations and reuse of freed blocks from multiple threads
+  thread::scope(|scope| {
+    let threads: Vec<_> = (0..4)
+      .map(|thread| {
+        scope.spawn(move || {
+          let mut map = HashMap::new();
+          let mut string = String::new();
+          for idx in 0..10_000 {
+            map.insert(idx, idx * thread);
+            string.push_str("relib");
+          }
+          assert_eq!(map.len(), 10_000);
+          assert_eq!(map[&9_999], 9_999 * thread);
+          assert_eq!(string.len(), 5 * 10_000);
+        })
+      })
+      .collect();
+
+    // scope only waits for closures to return, not for threads to exit
+    // (unloading doesn't wait for threads by default)
+    for thread in threads {
+      thread.join().unwrap();
+    }
+  });
+}
+
+// allocated by constructor of the module before host initialized it (so it's allocated by System)
+static BEFORE_INIT: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
+extern "C" fn alloc_before_init() {
+  let strings = (0..10).map(|idx| idx.to_string()).collect();
+  *BEFORE_INIT.lock().unwrap() = Some(strings);
+}
+
+#[used]
+#[cfg_attr(target_os = "linux", unsafe(link_section = ".init_array"))]
+#[cfg_attr(target_os = "windows", unsafe(link_section = ".CRT$XCU"))]
+static ALLOC_BEFORE_INIT: extern "C" fn() = alloc_before_init;
+
+fn check_alloc_before_init() {
+  let mut strings = BEFORE_INIT.lock().unwrap().take().unwrap();
+  assert_eq!(strings[9], "9");
+  // reallocated and deallocated by global allocator of the module
+  strings.extend((10..1000).map(|idx| idx.to_string()));
+  assert_eq!(strings[999], "999");
+}
+
+#[repr(align(8192))]
+struct Aligned([u8; 64]);
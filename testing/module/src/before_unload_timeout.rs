@@ -0,0 +1,12 @@
+use std::{thread, time::Duration};
+
+#[relib_module::export]
+pub fn main() {}
+
+#[relib_module::export]
+pub fn before_unload() {
+  // simulating a dead lock
+  loop {
+    thread::sleep(Duration::from_secs(1));
+  }
+}
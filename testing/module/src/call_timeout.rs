@@ -0,0 +1,24 @@
+use {
+  std::{thread, time::Duration},
+  test_shared::call_timeout::CallTimeoutExports,
+};
+
+#[relib_module::export]
+pub fn main() -> u8 {
+  1
+}
+
+struct Exports;
+
+#[relib_interface::exports]
+impl CallTimeoutExports for Exports {
+  fn add(a: u32, b: u32) -> u32 {
+    a + b
+  }
+
+  fn hang() {
+    loop {
+      thread::sleep(Duration::from_secs(1));
+    }
+  }
+}
@@ -0,0 +1,5 @@
+// never called since host refuses to load this module
+#[relib_module::export]
+pub fn main() {
+  println!("[module] const_mismatch");
+}
@@ -0,0 +1,9 @@
+extern "C" fn in_module() -> u32 {
+  42
+}
+
+/// Returns address of the function which host will call after unloading
+#[relib_module::export]
+pub fn main() -> usize {
+  in_module as *const () as usize
+}
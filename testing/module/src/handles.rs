@@ -0,0 +1,48 @@
+use {
+  relib_interface::{Handle, StaleHandle},
+  relib_module::HandleTable,
+  test_shared::handles::{HandlesExports, Widget},
+};
+
+#[relib_module::export]
+pub fn main() {}
+
+static WIDGETS: HandleTable<Widget> = HandleTable::new();
+static OTHER_WIDGETS: HandleTable<Widget> = HandleTable::new();
+
+struct Exports;
+
+#[relib_interface::exports]
+impl HandlesExports for Exports {
+  fn create_widget(size: u32) -> Handle<Widget> {
+    WIDGETS.insert(Widget { size })
+  }
+
+  fn widget_size(widget: Handle<Widget>) -> Result<u32, StaleHandle> {
+    WIDGETS.with(widget, |widget| widget.size)
+  }
+
+  fn resize_widget(widget: Handle<Widget>, size: u32) -> Result<(), StaleHandle> {
+    WIDGETS.with(widget, |widget| widget.size = size)
+  }
+
+  fn destroy_widget(widget: Handle<Widget>) -> Result<u32, StaleHandle> {
+    WIDGETS.remove(widget).map(|widget| widget.size)
+  }
+
+  fn alive_widgets() -> u32 {
+    WIDGETS.len() as u32
+  }
+
+  fn panic_in_widget(widget: Handle<Widget>) -> Result<(), StaleHandle> {
+    WIDGETS.with(widget, |_| panic!("expected panic in HandleTable::with"))
+  }
+
+  fn create_other_widget(size: u32) -> Handle<Widget> {
+    OTHER_WIDGETS.insert(Widget { size })
+  }
+
+  fn other_widget_size(widget: Handle<Widget>) -> Result<u32, StaleHandle> {
+    OTHER_WIDGETS.with(widget, |widget| widget.size)
+  }
+}
@@ -0,0 +1,92 @@
+use {
+  std::{hint::black_box, mem::forget, sync::Mutex, thread},
+  test_shared::host_alloc_exports::HostAllocExports,
+};
+
+relib_interface::include_exports!(gen_exports, "host_alloc");
+relib_interface::include_imports!(gen_imports, "host_alloc");
+use gen_exports::ModuleExportsImpl;
+
+#[relib_module::export]
+pub fn main() {
+  check_alloc_before_init();
+
+  // deallocated by host on unload
+  forget(black_box(vec![1_u8; 1024 * 1024]));
+
+  thread::scope(|scope| {
+    let threads: Vec<_> = (0..4)
+      .map(|_| {
+        scope.spawn(|| {
+          let strings: Vec<String> = (0..10_000).map(|idx| idx.to_string()).collect();
+          assert_eq!(strings[9_999], "9999");
+        })
+      })
+      .collect();
+
+    // scope only waits for closures to return, not for threads to exit
+    // (unloading doesn't wait for threads by default)
+    for thread in threads {
+      thread.join().unwrap();
+    }
+  });
+}
+
+// allocated by constructor of the module before host initialized it (so it's allocated by System)
+static BEFORE_INIT: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
+extern "C" fn alloc_before_init() {
+  let strings = (0..10).map(|idx| idx.to_string()).collect();
+  *BEFORE_INIT.lock().unwrap() = Some(strings);
+}
+
+#[used]
+#[cfg_attr(target_os = "linux", unsafe(link_section = ".init_array"))]
+#[cfg_attr(target_os = "windows", unsafe(link_section = ".CRT$XCU"))]
+static ALLOC_BEFORE_INIT: extern "C" fn() = alloc_before_init;
+
+fn check_alloc_before_init() {
+  let mut strings = BEFORE_INIT.lock().unwrap().take().unwrap();
+  assert_eq!(strings[9], "9");
+  // reallocated and deallocated by global allocator of the module
+  strings.extend((10..1000).map(|idx| idx.to_string()));
+  assert_eq!(strings[999], "999");
+}
+
+impl HostAllocExports for ModuleExportsImpl {
+  fn string(len: usize) -> (usize, String) {
+    let string = "m".repeat(len);
+    (string.as_ptr() as usize, string)
+  }
+
+  fn nested() -> Vec<Option<String>> {
+    (0..100)
+      .map(|idx| (idx % 2 == 0).then(|| idx.to_string()))
+      .collect()
+  }
+
+  fn boxed() -> Box<(u64, String)> {
+    Box::new((u64::MAX, "boxed".to_owned()))
+  }
+
+  fn leak_host_string(len: usize) {
+    let (_, string) = unsafe { gen_imports::host_string(len) };
+    forget(string);
+  }
+
+  fn host_string_round_trip(len: usize) -> String {
+    let (ptr, mut string) = unsafe { gen_imports::host_string(len) };
+    assert_eq!(string.as_ptr() as usize, ptr, "string must not be cloned");
+
+    // reallocated by the module
+    string.push_str("module");
+    string
+  }
+
+  fn host_result_round_trip(ok: bool) -> Result<String, String> {
+    match unsafe { gen_imports::host_result(ok) } {
+      Ok(string) => Ok(string + "module"),
+      Err(code) => Err(format!("error {code}")),
+    }
+  }
+}
@@ -0,0 +1,2 @@
+#[relib_module::export]
+pub fn main() {}
@@ -0,0 +1,86 @@
+use {
+  abi_stable::std_types::{RStr, RString},
+  test_shared::interface_macro::{MacroExports, gen_imports},
+};
+
+#[relib_module::export]
+pub fn main() {}
+
+struct Exports;
+
+#[relib_interface::exports]
+impl MacroExports for Exports {
+  fn primitive(p: i32) -> i32 {
+    p * 2
+  }
+
+  fn ref_ret<'a>(r: RStr<'a>) -> RStr<'a> {
+    r.slice(1..)
+  }
+
+  fn owned_ret(r: RStr) -> RString {
+    format!("{r}!").into()
+  }
+
+  fn str_ret(s: &str) -> &str {
+    &s[1..]
+  }
+
+  fn slice_mut(slice: &mut [u8]) {
+    slice.fill(u8::MAX);
+  }
+
+  fn result(ok: bool) -> Result<u8, RString> {
+    if ok { Ok(u8::MAX) } else { Err("error".into()) }
+  }
+
+  #[cfg(target_os = "linux")]
+  fn cfg_target_os() -> u8 {
+    u8::MAX
+  }
+
+  #[cfg(not(target_os = "linux"))]
+  fn cfg_target_os() -> u16 {
+    u16::MAX
+  }
+
+  fn panic() {
+    panic!("expected panic");
+  }
+
+  fn callback_panic(callback: &dyn Fn()) {
+    callback();
+    unreachable!();
+  }
+
+  fn callback_ret_ref(
+    str_callback: &dyn Fn(&str) -> &str,
+    slice_callback: &dyn Fn(&[u32]) -> &[u32],
+  ) -> bool {
+    let string = String::from("hello");
+    let slice = vec![1, 2, 3];
+    str_callback(&string) == "ello" && slice_callback(&slice) == [2, 3]
+  }
+
+  fn call_imports() {
+    unsafe {
+      assert_eq!(gen_imports::primitive(2), 6);
+
+      let string = "hello";
+      assert_eq!(gen_imports::ref_ret(string.into()), "ello");
+      assert_eq!(gen_imports::str_ret(string), "ello");
+
+      let vec = gen_imports::alloc_vec(1024);
+      assert_eq!(vec.len(), 1024);
+    }
+  }
+
+  fn import_callback_panic() {
+    unsafe {
+      gen_imports::callback_panic(&|| {
+        std::panic::resume_unwind(Box::new("expected closure panic"))
+      });
+    }
+    unreachable!();
+  }
+}
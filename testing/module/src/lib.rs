@@ -14,6 +14,60 @@ mod threads_check;
 #[cfg(feature = "before_unload_panic")]
 mod before_unload_panic;
 
+#[cfg(feature = "before_unload_timeout")]
+mod before_unload_timeout;
+
+#[cfg(feature = "shutdown_token")]
+mod shutdown_token;
+
+#[cfg(feature = "untracked_threads")]
+mod untracked_threads;
+
+#[cfg(feature = "unloading_fail_diagnosis")]
+mod unloading_fail_diagnosis;
+
+#[cfg(feature = "inspect")]
+mod inspect;
+
+#[cfg(feature = "soak")]
+mod soak;
+
+#[cfg(feature = "memory_stats")]
+mod memory_stats;
+
+#[cfg(feature = "memory_budget")]
+mod memory_budget;
+
+#[cfg(feature = "alloc_bench")]
+mod alloc_bench;
+
+#[cfg(feature = "arena_alloc")]
+mod arena_alloc;
+
+#[cfg(feature = "host_alloc")]
+mod host_alloc;
+
+#[cfg(feature = "guard_pages")]
+mod guard_pages;
+
+#[cfg(feature = "interface_macro")]
+mod interface_macro;
+
+#[cfg(feature = "imports_caller")]
+mod imports_caller;
+
+#[cfg(feature = "module_box")]
+mod module_box;
+
+#[cfg(feature = "handles")]
+mod handles;
+
+#[cfg(feature = "call_timeout")]
+mod call_timeout;
+
+#[cfg(feature = "const_mismatch")]
+mod const_mismatch;
+
 #[cfg(feature = "code_change")]
 mod code_change;
 
@@ -0,0 +1,6 @@
+/// Returns `true` if 2 MiB allocation succeeded
+#[relib_module::export]
+pub fn main() -> bool {
+  let mut vec = Vec::<u8>::new();
+  vec.try_reserve_exact(2 * 1024 * 1024).is_ok()
+}
@@ -0,0 +1,11 @@
+#[relib_module::export]
+pub fn main() {
+  // freed by relib on unload
+  let leaked = Box::leak(vec![1_u8; 1024 * 1024].into_boxed_slice());
+  std::hint::black_box(leaked);
+
+  for _ in 0..100 {
+    let short_lived = std::hint::black_box(vec![0_u8; 100]);
+    drop(short_lived);
+  }
+}
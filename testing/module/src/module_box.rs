@@ -0,0 +1,103 @@
+use {
+  abi_stable::std_types::RString,
+  relib_interface::ModuleBox,
+  std::{
+    cell::RefCell,
+    sync::atomic::{AtomicU32, Ordering},
+  },
+  test_shared::module_box::{Counter, ModuleBoxExports},
+};
+
+#[relib_module::export]
+pub fn main() {}
+
+#[relib_module::export]
+pub fn before_unload() {
+  // host checks alive boxes after this callback, so the box kept by the module doesn't prevent unloading
+  KEPT_COUNTER.take();
+}
+
+static DROPPED_COUNTERS: AtomicU32 = AtomicU32::new(0);
+
+thread_local! {
+  // box isn't Send, host calls keep_counter and unloads the module on the same thread
+  static KEPT_COUNTER: RefCell<Option<ModuleBox<dyn Counter>>> = const { RefCell::new(None) };
+}
+
+struct ModuleCounter {
+  name: String,
+  value: u32,
+}
+
+impl Counter for ModuleCounter {
+  fn increment(&mut self, by: u32) -> u32 {
+    self.value += by;
+    self.value
+  }
+
+  fn name(&self) -> &str {
+    &self.name
+  }
+
+  fn label(&self) -> RString {
+    format!("{}: {}", self.name, self.value).into()
+  }
+
+  fn checked(&self, ok: bool) -> Result<u32, RString> {
+    if ok {
+      Ok(self.value)
+    } else {
+      Err("error".into())
+    }
+  }
+
+  fn for_each(&self, callback: &mut dyn FnMut(u32)) {
+    (0..self.value).for_each(callback);
+  }
+
+  fn panic(&self) {
+    panic!("expected panic");
+  }
+}
+
+impl Drop for ModuleCounter {
+  fn drop(&mut self) {
+    DROPPED_COUNTERS.fetch_add(1, Ordering::SeqCst);
+
+    if self.name == "panic in drop" {
+      panic!("expected panic in drop");
+    }
+  }
+}
+
+struct Exports;
+
+#[relib_interface::exports]
+impl ModuleBoxExports for Exports {
+  fn counter(name: &str) -> ModuleBox<dyn Counter> {
+    ModuleBox::new(ModuleCounter {
+      name: name.to_owned(),
+      value: 0,
+    })
+  }
+
+  fn counter_result(ok: bool) -> Result<ModuleBox<dyn Counter>, RString> {
+    if ok {
+      Ok(Self::counter("ok"))
+    } else {
+      Err("error".into())
+    }
+  }
+
+  fn take_counter(mut counter: ModuleBox<dyn Counter>) -> u32 {
+    counter.increment(1)
+  }
+
+  fn dropped_counters() -> u32 {
+    DROPPED_COUNTERS.load(Ordering::SeqCst)
+  }
+
+  fn keep_counter() {
+    KEPT_COUNTER.set(Some(Self::counter("kept")));
+  }
+}
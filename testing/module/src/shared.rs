@@ -1,6 +1,7 @@
 use {
   abi_stable::std_types::{RStr, RString, RVec},
   std::{
+    fs::File,
     mem::forget,
     sync::{
       Mutex, MutexGuard,
@@ -8,7 +9,10 @@ use {
     },
     thread::{self, JoinHandle},
   },
-  test_shared::{SIZE_200_MB, assert_mem_dealloc, exports::Exports},
+  test_shared::{
+    SIZE_200_MB, assert_mem_dealloc,
+    exports::{EXPORTS_NUMBER, EXPORTS_STR, Exports, NumberAlias},
+  },
 };
 
 relib_interface::include_imports!();
@@ -47,6 +51,74 @@ impl Exports for ModuleExportsImpl {
     p
   }
 
+  fn result(ok: bool) -> Result<RString, RString> {
+    if ok {
+      Ok("ok".into())
+    } else {
+      Err("err".into())
+    }
+  }
+
+  fn result_primitive(ok: bool) -> Result<u8, u16> {
+    if ok { Ok(u8::MAX) } else { Err(u16::MAX) }
+  }
+
+  fn str_ret(s: &str) -> &str {
+    &s[1..]
+  }
+
+  fn str_result(s: &str) -> Result<&str, RString> {
+    if s.is_empty() {
+      Err("empty".into())
+    } else {
+      Ok(s)
+    }
+  }
+
+  fn slice_sum(slice: &[u32]) -> u32 {
+    slice.iter().sum()
+  }
+
+  fn slice_mut(slice: &mut [u8]) {
+    slice.iter_mut().for_each(|value| *value += 1);
+  }
+
+  fn option_ref(value: Option<&u64>) -> Option<&u64> {
+    value
+  }
+
+  fn callback(value: u32, callback: &dyn Fn(u32) -> u32) -> u32 {
+    callback(value) + 1
+  }
+
+  fn callback_mut(count: u32, callback: &mut dyn FnMut(&str)) {
+    for idx in 0..count {
+      callback(&idx.to_string());
+    }
+  }
+
+  #[cfg(target_os = "linux")]
+  fn cfg_target_os() -> u8 {
+    u8::MAX
+  }
+
+  #[cfg(not(target_os = "linux"))]
+  fn cfg_target_os() -> u16 {
+    u16::MAX
+  }
+
+  fn trait_file_items(number: NumberAlias) -> RString {
+    format!("{EXPORTS_STR} {}", number + EXPORTS_NUMBER).into()
+  }
+
+  fn caller_module_id() -> u64 {
+    unsafe { gen_imports::caller_module_id() }
+  }
+
+  fn caller_context() -> u64 {
+    unsafe { gen_imports::caller_context() }
+  }
+
   fn call_imports() {
     unsafe {
       gen_imports::empty();
@@ -71,6 +143,33 @@ impl Exports for ModuleExportsImpl {
       let ret = gen_imports::primitive_ret(i32::MIN);
       assert_eq!(ret, i32::MIN);
 
+      assert_eq!(gen_imports::result(true), Ok("ok".into()));
+      assert_eq!(gen_imports::result(false), Err("err".into()));
+      assert_eq!(gen_imports::result_primitive(true), Ok(u8::MAX));
+      assert_eq!(gen_imports::result_primitive(false), Err(u16::MAX));
+
+      assert_eq!(gen_imports::str_ret(&string), &string[1..]);
+      assert_eq!(gen_imports::str_result(&string), Ok(string.as_str()));
+      assert_eq!(gen_imports::str_result(""), Err("empty".into()));
+      assert_eq!(gen_imports::slice_sum(&[1, 2, 3]), 6);
+      let mut bytes = [0, 1, 2];
+      gen_imports::slice_mut(&mut bytes);
+      assert_eq!(bytes, [1, 2, 3]);
+      assert_eq!(gen_imports::option_ref(Some(&u64::MAX)), Some(&u64::MAX));
+      assert_eq!(gen_imports::option_ref(None), None);
+
+      assert_eq!(gen_imports::callback(1, &|value| value * 2), 3);
+      let mut strings = Vec::new();
+      gen_imports::callback_mut(3, &mut |s| strings.push(s.to_owned()));
+      assert_eq!(strings, ["0", "1", "2"]);
+
+      #[cfg(target_os = "linux")]
+      assert_eq!(gen_imports::cfg_target_os(), u8::MAX);
+      #[cfg(not(target_os = "linux"))]
+      assert_eq!(gen_imports::cfg_target_os(), u16::MAX);
+
+      assert_eq!(gen_imports::trait_file_items(1), "imports 25");
+
       assert_mem_dealloc(|| {
         let mem = gen_imports::alloc_mem();
         assert_eq!(mem.len(), SIZE_200_MB);
@@ -88,6 +187,10 @@ impl Exports for ModuleExportsImpl {
     forget(alloc_some_bytes());
   }
 
+  fn leak_file() {
+    forget(File::open("Cargo.toml").unwrap());
+  }
+
   fn thread_locals() {
     struct TlsWithDrop {
       _mem: Vec<u8>,
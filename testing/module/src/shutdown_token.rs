@@ -0,0 +1,52 @@
+use {
+  relib_module::shutdown_token,
+  std::{
+    future::Future,
+    pin::pin,
+    sync::Arc,
+    task::{Context, Poll, Wake},
+    thread::{self, Thread},
+    time::Duration,
+  },
+};
+
+#[relib_module::export]
+pub fn main() {
+  let token = shutdown_token();
+  assert!(!token.is_cancelled());
+
+  thread::spawn(
+    move || {
+      while !token.park_timeout(Duration::from_secs(1000)) {}
+    },
+  );
+
+  thread::spawn(
+    move || {
+      while !token.wait_timeout(Duration::from_secs(1000)) {}
+    },
+  );
+
+  thread::spawn(move || {
+    block_on(token.cancelled());
+    assert!(token.is_cancelled());
+  });
+}
+
+fn block_on(future: impl Future<Output = ()>) {
+  struct ThreadWaker(Thread);
+
+  impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+      self.0.unpark();
+    }
+  }
+
+  let waker = Arc::new(ThreadWaker(thread::current())).into();
+  let mut cx = Context::from_waker(&waker);
+  let mut future = pin!(future);
+
+  while future.as_mut().poll(&mut cx) == Poll::Pending {
+    thread::park();
+  }
+}
@@ -0,0 +1,19 @@
+use std::{fs::File, thread};
+
+#[relib_module::export]
+pub fn main() {
+  // everything here must be cleaned up by module itself or by relib on unload
+
+  // freed by relib
+  let leaked = Box::leak(vec![0_u8; 1024 * 1024].into_boxed_slice());
+  std::hint::black_box(leaked);
+
+  let file = File::open("Cargo.toml").unwrap();
+  drop(file);
+
+  thread::spawn(|| {
+    let _ = std::backtrace::Backtrace::force_capture();
+  })
+  .join()
+  .unwrap();
+}
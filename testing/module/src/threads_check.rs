@@ -1,8 +1,23 @@
-use std::{thread, time::Duration};
+use std::{sync::mpsc, thread, time::Duration};
 
 #[relib_module::export]
 pub fn main() {
+  // host reads the name right after main returns, so waiting for the thread
+  // to start (name is recorded when std sets it in the thread itself)
+  let (started_sender, started_receiver) = mpsc::channel();
+
+  thread::Builder::new()
+    .name("still running (long name)".to_owned())
+    .spawn(move || {
+      started_sender.send(()).unwrap();
+      thread::sleep(Duration::from_secs(1000000));
+    })
+    .unwrap();
+
+  started_receiver.recv().unwrap();
+
+  // detached thread which is already winding down when host unloads the module
   thread::spawn(|| {
-    thread::sleep(Duration::from_secs(1000000));
+    thread::sleep(Duration::from_millis(100));
   });
 }
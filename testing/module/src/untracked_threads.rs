@@ -0,0 +1,30 @@
+use std::{ffi::c_void, mem::transmute, ptr, thread, time::Duration};
+
+type PthreadCreate = unsafe extern "C" fn(
+  native: *mut libc::pthread_t,
+  attr: *const libc::pthread_attr_t,
+  f: extern "C" fn(*mut c_void) -> *mut c_void,
+  value: *mut c_void,
+) -> libc::c_int;
+
+#[relib_module::export]
+pub fn main() {
+  // original pthread_create from libc bypassing the hook of relib_module,
+  // same as if some C library called it
+  let pthread_create: PthreadCreate =
+    unsafe { transmute(libc::dlsym(libc::RTLD_NEXT, c"pthread_create".as_ptr())) };
+
+  let mut native = 0;
+  let result =
+    unsafe { pthread_create(&mut native, ptr::null(), untracked_thread, ptr::null_mut()) };
+  assert_eq!(result, 0);
+
+  // giving it time to start
+  thread::sleep(Duration::from_millis(100));
+}
+
+extern "C" fn untracked_thread(_: *mut c_void) -> *mut c_void {
+  loop {
+    thread::sleep(Duration::from_secs(1000));
+  }
+}
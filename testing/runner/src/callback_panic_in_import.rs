@@ -0,0 +1,36 @@
+use {
+  crate::helpers::{cmd, host_bin_by_directory},
+  std::process::Command,
+};
+
+pub fn main() {
+  let (build_debug, build_release) = cmd!(
+    "cargo",
+    "build",
+    "--workspace",
+    "--features",
+    "callback_panic_in_import"
+  );
+
+  // closure of the module panics in the import, host panics in it too
+  // and module raises the panic again, so export returns None instead of aborting the process
+  let run_host = |directory: &str| {
+    let output = Command::new(host_bin_by_directory(directory))
+      .output()
+      .unwrap();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    println!("stderr:\n{stderr}");
+
+    assert!(stderr.contains(r#"Closure passed as "callback" panicked"#));
+    assert!(stderr.contains(r#"Closure passed to import "callback_panic" panicked"#));
+    assert!(!stderr.contains("aborting"));
+    dbg!(output.status);
+    assert!(output.status.success());
+  };
+
+  build_debug();
+  run_host("debug");
+  build_release();
+  run_host("release");
+}
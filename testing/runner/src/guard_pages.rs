@@ -0,0 +1,28 @@
+use {
+  crate::helpers::{cmd, host_bin_by_directory},
+  std::process::Command,
+};
+
+pub fn main() {
+  let (build_debug, build_release) =
+    cmd!("cargo", "build", "--workspace", "--features", "guard_pages");
+
+  // call into unloaded module aborts host process thats why it should be tested in a different way
+  let run_host = |directory: &str| {
+    let output = Command::new(host_bin_by_directory(directory))
+      .output()
+      .unwrap();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    println!("stderr:\n{stderr}");
+
+    assert!(stderr.contains("[relib] called into unloaded module (or accessed its memory): "));
+    dbg!(output.status);
+    assert!(!output.status.success());
+  };
+
+  build_debug();
+  run_host("debug");
+  build_release();
+  run_host("release");
+}
@@ -3,10 +3,15 @@ use helpers::{call_host_by_directory, cmd};
 mod code_change;
 mod multiple_modules;
 mod panic_in_interface_host;
+mod callback_panic_in_import;
 mod backtrace_unloading_host_as_dylib;
 #[cfg(target_os = "windows")]
 mod windows_background_threads;
 mod dealloc_validation;
+#[cfg(target_os = "linux")]
+mod guard_pages;
+#[cfg(target_os = "linux")]
+mod untracked_threads_warn;
 
 const TEST_FEATURES: &[&str] = &[
   "parallel_module_loading",
@@ -25,6 +30,25 @@ const TEST_FEATURES: &[&str] = &[
   #[cfg(target_os = "linux")]
   "threads_check",
   "before_unload_panic",
+  "before_unload_timeout",
+  "shutdown_token",
+  #[cfg(target_os = "linux")]
+  "untracked_threads",
+  #[cfg(target_os = "linux")]
+  "unloading_fail_diagnosis",
+  "inspect",
+  "soak",
+  "memory_stats",
+  "memory_budget",
+  // alloc_bench only prints the results, it's run manually (see testing/host/src/alloc_bench.rs)
+  "arena_alloc",
+  "host_alloc",
+  "interface_macro",
+  "imports_caller",
+  "module_box",
+  "handles",
+  "call_timeout",
+  "const_mismatch",
   // panic_in_interface_host is in its own module
   "panic_in_interface_module",
 ];
@@ -36,10 +60,15 @@ fn main() {
   multiple_modules::main();
   code_change::main();
   panic_in_interface_host::main();
+  callback_panic_in_import::main();
   backtrace_unloading_host_as_dylib::main();
   #[cfg(target_os = "windows")]
   windows_background_threads::main();
   dealloc_validation::main();
+  #[cfg(target_os = "linux")]
+  guard_pages::main();
+  #[cfg(target_os = "linux")]
+  untracked_threads_warn::main();
 
   println!();
   println!();
@@ -0,0 +1,34 @@
+use {
+  crate::helpers::{cmd, host_bin_by_directory},
+  std::process::Command,
+};
+
+pub fn main() {
+  let (build_debug, build_release) = cmd!(
+    "cargo",
+    "build",
+    "--workspace",
+    "--features",
+    "untracked_threads_warn"
+  );
+
+  // with default policy host unloads the module but warns about the thread
+  // which has return address into module code on its stack
+  let run_host = |directory: &str| {
+    let output = Command::new(host_bin_by_directory(directory))
+      .output()
+      .unwrap();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    println!("stderr:\n{stderr}");
+
+    assert!(stderr.contains("[relib] warning: module code may still be running"));
+    assert!(stderr.contains("(low confidence) has return address into"));
+    assert!(output.status.success());
+  };
+
+  build_debug();
+  run_host("debug");
+  build_release();
+  run_host("release");
+}
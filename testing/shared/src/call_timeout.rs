@@ -0,0 +1,8 @@
+//! Interface of "call_timeout" test, exports are called with a watchdog timeout
+
+#[relib_interface::exports]
+pub trait CallTimeoutExports {
+  fn add(a: u32, b: u32) -> u32;
+  /// Simulates a dead lock
+  fn hang();
+}
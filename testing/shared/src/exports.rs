@@ -1,4 +1,19 @@
-use abi_stable::std_types::{RStr, RString, RVec};
+use {
+  super::*,
+  abi_stable::std_types::{RStr, RString, RString as RenamedString, RVec},
+};
+
+pub type NumberAlias = interface_types::Number;
+
+pub const EXPORTS_NUMBER: NumberAlias = 42;
+pub const EXPORTS_STR: &str = "exports";
+
+// module is compiled with a different value in const_mismatch test
+// (attributes of the trait file are evaluated in the crate where bindings are included)
+#[cfg(not(feature = "const_mismatch_module"))]
+pub const EXPORTS_VERSION: u8 = 1;
+#[cfg(feature = "const_mismatch_module")]
+pub const EXPORTS_VERSION: u8 = 2;
 
 pub trait Exports {
   // ------------------------------- codegen testing
@@ -22,6 +37,30 @@ pub trait Exports {
   fn primitive(p: i32);
   fn primitive_ret(p: i32) -> i32;
 
+  fn result(ok: bool) -> Result<RString, RString>;
+  fn result_primitive(ok: bool) -> Result<u8, u16>;
+
+  fn str_ret(s: &str) -> &str;
+  fn str_result(s: &str) -> Result<&str, RString>;
+  fn slice_sum(slice: &[u32]) -> u32;
+  fn slice_mut(slice: &mut [u8]);
+  fn option_ref(value: Option<&u64>) -> Option<&u64>;
+
+  fn callback(value: u32, callback: &dyn Fn(u32) -> u32) -> u32;
+  fn callback_mut(count: u32, callback: &mut dyn FnMut(&str));
+
+  #[cfg(target_os = "linux")]
+  fn cfg_target_os() -> u8;
+  #[cfg(not(target_os = "linux"))]
+  fn cfg_target_os() -> u16;
+  #[cfg(any())]
+  fn cfg_disabled(_: TypeWhichDoesNotExist);
+
+  fn trait_file_items(number: NumberAlias) -> RenamedString;
+
+  fn caller_module_id() -> u64;
+  fn caller_context() -> u64;
+
   fn alloc_mem() -> RVec<u8>;
 
   fn panic();
@@ -30,6 +69,8 @@ pub trait Exports {
   // ------------------------------- other
 
   fn leak();
+  // opened file is not closed by relib on unload (see soak)
+  fn leak_file();
   fn thread_locals();
 
   fn call_imports();
@@ -0,0 +1,22 @@
+//! Interface of "handles" test, host holds handles of values owned by the module
+
+use relib_interface::{Handle, StaleHandle};
+
+/// Value owned by the module, host only holds handles of it
+pub struct Widget {
+  pub size: u32,
+}
+
+#[relib_interface::exports]
+pub trait HandlesExports {
+  fn create_widget(size: u32) -> Handle<Widget>;
+  fn widget_size(widget: Handle<Widget>) -> Result<u32, StaleHandle>;
+  fn resize_widget(widget: Handle<Widget>, size: u32) -> Result<(), StaleHandle>;
+  fn destroy_widget(widget: Handle<Widget>) -> Result<u32, StaleHandle>;
+  fn alive_widgets() -> u32;
+  fn panic_in_widget(widget: Handle<Widget>) -> Result<(), StaleHandle>;
+
+  /// Second table of widgets
+  fn create_other_widget(size: u32) -> Handle<Widget>;
+  fn other_widget_size(widget: Handle<Widget>) -> Result<u32, StaleHandle>;
+}
@@ -0,0 +1,13 @@
+pub trait HostAllocExports {
+  /// Returns address of the string buffer so host can check that it wasn't cloned
+  fn string(len: usize) -> (usize, String);
+  fn nested() -> Vec<Option<String>>;
+  fn boxed() -> Box<(u64, String)>;
+
+  /// Moves string from the host to the module and leaks it (it must be deallocated on unload)
+  fn leak_host_string(len: usize);
+  /// Moves string from the host to the module and back
+  fn host_string_round_trip(len: usize) -> String;
+  /// Passes result of the host import back to the host
+  fn host_result_round_trip(ok: bool) -> Result<String, String>;
+}
@@ -0,0 +1,5 @@
+pub trait HostAllocImports {
+  /// Returns address of the string buffer so module can check that it wasn't cloned
+  fn host_string(len: usize) -> (usize, String);
+  fn host_result(ok: bool) -> Result<String, u8>;
+}
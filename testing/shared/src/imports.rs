@@ -1,4 +1,10 @@
-use abi_stable::std_types::{RStr, RString, RVec};
+use {
+  self::super::interface_types::Number as ImportsNumber,
+  abi_stable::std_types::{RStr, RString, RString as RenamedString, RVec},
+};
+
+pub const IMPORTS_NUMBER: ImportsNumber = 24;
+pub const IMPORTS_STR: &str = "imports";
 
 pub trait Imports {
   // ------------------------------- codegen testing
@@ -22,6 +28,30 @@ pub trait Imports {
   fn primitive(p: i32);
   fn primitive_ret(p: i32) -> i32;
 
+  fn result(ok: bool) -> Result<RString, RString>;
+  fn result_primitive(ok: bool) -> Result<u8, u16>;
+
+  fn str_ret(s: &str) -> &str;
+  fn str_result(s: &str) -> Result<&str, RString>;
+  fn slice_sum(slice: &[u32]) -> u32;
+  fn slice_mut(slice: &mut [u8]);
+  fn option_ref(value: Option<&u64>) -> Option<&u64>;
+
+  fn callback(value: u32, callback: &dyn Fn(u32) -> u32) -> u32;
+  fn callback_mut(count: u32, callback: &mut dyn FnMut(&str));
+
+  #[cfg(target_os = "linux")]
+  fn cfg_target_os() -> u8;
+  #[cfg(not(target_os = "linux"))]
+  fn cfg_target_os() -> u16;
+  #[cfg(any())]
+  fn cfg_disabled(_: TypeWhichDoesNotExist);
+
+  fn trait_file_items(number: ImportsNumber) -> RenamedString;
+
+  fn caller_module_id() -> u64;
+  fn caller_context() -> u64;
+
   fn alloc_mem() -> RVec<u8>;
 
   fn panic();
@@ -0,0 +1,43 @@
+//! Interface of "interface_macro" test which is generated by proc-macros instead of build scripts,
+//! so both traits can be defined in the same file with other items
+
+use abi_stable::std_types::{RStr, RString, RVec};
+
+pub const DEFAULT_RETURN_VALUE: u8 = 123;
+
+#[relib_interface::exports]
+pub trait MacroExports {
+  fn primitive(p: i32) -> i32;
+  fn ref_ret<'a>(r: RStr<'a>) -> RStr<'a>;
+  fn owned_ret(r: RStr) -> RString;
+  fn str_ret(s: &str) -> &str;
+  fn slice_mut(slice: &mut [u8]);
+  fn result(ok: bool) -> Result<u8, RString>;
+
+  fn default_impl() -> u8 {
+    DEFAULT_RETURN_VALUE
+  }
+
+  #[cfg(target_os = "linux")]
+  fn cfg_target_os() -> u8;
+  #[cfg(not(target_os = "linux"))]
+  fn cfg_target_os() -> u16;
+
+  fn panic();
+  fn callback_panic(callback: &dyn Fn());
+  fn callback_ret_ref(
+    str_callback: &dyn Fn(&str) -> &str,
+    slice_callback: &dyn Fn(&[u32]) -> &[u32],
+  ) -> bool;
+  fn call_imports();
+  fn import_callback_panic();
+}
+
+#[relib_interface::imports]
+pub trait MacroImports {
+  fn primitive(p: i32) -> i32;
+  fn ref_ret<'a>(r: RStr<'a>) -> RStr<'a>;
+  fn str_ret(s: &str) -> &str;
+  fn alloc_vec(len: usize) -> RVec<u8>;
+  fn callback_panic(callback: &dyn Fn());
+}
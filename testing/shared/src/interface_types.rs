@@ -0,0 +1,3 @@
+//! Items which are imported in trait files using relative paths
+
+pub type Number = u32;
@@ -1,9 +1,20 @@
 pub mod exports;
 pub mod imports;
+pub mod host_alloc_exports;
+pub mod host_alloc_imports;
+pub mod interface_macro;
+pub mod module_box;
+pub mod handles;
+pub mod call_timeout;
+pub mod interface_types;
 
 pub const EXPORTS: &str = include_str!("exports.rs");
 pub const IMPORTS: &str = include_str!("imports.rs");
 
+/// Interface of "host_alloc" test which is generated with shared allocator mode
+pub const HOST_ALLOC_EXPORTS: &str = include_str!("host_alloc_exports.rs");
+pub const HOST_ALLOC_IMPORTS: &str = include_str!("host_alloc_imports.rs");
+
 pub const SIZE_200_MB: usize = 1024 * 1024 * 200;
 
 pub fn print_memory_use() {
@@ -0,0 +1,31 @@
+//! Interface of "module_box" test, trait objects are moved between host and module as `ModuleBox<dyn Trait>`
+
+use {abi_stable::std_types::RString, relib_interface::ModuleBox};
+
+#[relib_interface::dyn_trait]
+pub trait Counter {
+  fn increment(&mut self, by: u32) -> u32;
+  fn name(&self) -> &str;
+  fn label(&self) -> RString;
+  fn checked(&self, ok: bool) -> Result<u32, RString>;
+  fn for_each(&self, callback: &mut dyn FnMut(u32));
+  fn panic(&self);
+
+  fn sized_only(&self) -> u8
+  where
+    Self: Sized,
+  {
+    u8::MAX
+  }
+}
+
+#[relib_interface::exports]
+pub trait ModuleBoxExports {
+  fn counter(name: &str) -> ModuleBox<dyn Counter>;
+  fn counter_result(ok: bool) -> Result<ModuleBox<dyn Counter>, RString>;
+  /// Takes the ownership of the box back
+  fn take_counter(counter: ModuleBox<dyn Counter>) -> u32;
+  fn dropped_counters() -> u32;
+  /// Module keeps the box itself and drops it in `before_unload`
+  fn keep_counter();
+}